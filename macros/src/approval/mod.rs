@@ -1 +1,2 @@
 pub mod simple_multisig;
+pub mod weighted_multisig;
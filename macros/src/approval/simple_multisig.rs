@@ -41,6 +41,14 @@ pub fn expand(meta: SimpleMultisigMeta) -> Result<TokenStream, darling::Error> {
         }
     });
 
+    let manager = quote! {
+        #me::approval::ApprovalManager<
+            #action,
+            #me::approval::simple_multisig::ApprovalState,
+            #me::approval::simple_multisig::Configuration<Self>,
+        >
+    };
+
     Ok(quote! {
         impl #imp #me::approval::ApprovalManagerInternal<
                 #action,
@@ -64,5 +72,28 @@ pub fn expand(meta: SimpleMultisigMeta) -> Result<TokenStream, darling::Error> {
                 }
             }
         }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #ident #ty #wher {
+            pub fn multisig_list_requests(
+                &self,
+                from_index: Option<u32>,
+                limit: Option<u32>,
+            ) -> Vec<(
+                u32,
+                #me::approval::ActionRequest<#action, #me::approval::simple_multisig::ApprovalState>,
+            )> {
+                <Self as #manager>::list_requests(
+                    from_index.unwrap_or(0),
+                    limit
+                        .unwrap_or(#me::approval::DEFAULT_LIST_REQUESTS_LIMIT)
+                        .min(#me::approval::MAX_LIST_REQUESTS_LIMIT),
+                )
+            }
+
+            pub fn multisig_count_requests(&self) -> u32 {
+                <Self as #manager>::count_requests()
+            }
+        }
     })
 }
@@ -0,0 +1,98 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(weighted_multisig), supports(struct_named))]
+pub struct WeightedMultisigMeta {
+    pub storage_key: Option<Expr>,
+    pub action: Expr,
+    pub weight: Expr,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: WeightedMultisigMeta) -> Result<TokenStream, darling::Error> {
+    let WeightedMultisigMeta {
+        storage_key,
+        action,
+        weight,
+        generics,
+        ident,
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    let manager = quote! {
+        #me::approval::ApprovalManager<
+            #action,
+            #me::approval::weighted_multisig::ApprovalState,
+            #me::approval::weighted_multisig::Configuration<Self>,
+        >
+    };
+
+    Ok(quote! {
+        impl #imp #me::approval::ApprovalManagerInternal<
+                #action,
+                #me::approval::weighted_multisig::ApprovalState,
+                #me::approval::weighted_multisig::Configuration<Self>,
+            > for #ident #ty #wher {
+            #root
+        }
+
+        impl #imp #me::approval::weighted_multisig::AccountWeighter for #ident #ty #wher {
+            type AuthorizationError = #me::approval::weighted_multisig::macro_types::NoWeight;
+
+            fn account_weight(account_id: &#near_sdk::AccountId) -> Result<u64, Self::AuthorizationError> {
+                let weight: u64 = (#weight)(account_id);
+
+                if weight > 0 {
+                    Ok(weight)
+                } else {
+                    Err(#me::approval::weighted_multisig::macro_types::NoWeight)
+                }
+            }
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #ident #ty #wher {
+            pub fn multisig_list_requests(
+                &self,
+                from_index: Option<u32>,
+                limit: Option<u32>,
+            ) -> Vec<(
+                u32,
+                #me::approval::ActionRequest<#action, #me::approval::weighted_multisig::ApprovalState>,
+            )> {
+                <Self as #manager>::list_requests(
+                    from_index.unwrap_or(0),
+                    limit
+                        .unwrap_or(#me::approval::DEFAULT_LIST_REQUESTS_LIMIT)
+                        .min(#me::approval::MAX_LIST_REQUESTS_LIMIT),
+                )
+            }
+
+            pub fn multisig_count_requests(&self) -> u32 {
+                <Self as #manager>::count_requests()
+            }
+        }
+    })
+}
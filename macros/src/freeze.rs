@@ -0,0 +1,79 @@
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(freeze), supports(struct_named))]
+pub struct FreezeMeta {
+    pub storage_key: Option<Expr>,
+    pub nep141_hook: Flag,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: FreezeMeta) -> Result<TokenStream, darling::Error> {
+    let FreezeMeta {
+        storage_key,
+        nep141_hook,
+        ident,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::new(#storage_key)
+            }
+        }
+    });
+
+    let nep141_hook = nep141_hook.is_present().then(|| {
+        quote! {
+            impl #imp #me::standard::nep141::Nep141Hook for #ident #ty #wher {
+                fn before_transfer(&mut self, transfer: &#me::standard::nep141::Nep141Transfer) {
+                    <Self as #me::freeze::Freeze>::before_nep141_transfer(transfer);
+                }
+
+                fn before_mint(&mut self, mint: &#me::standard::nep141::Nep141Mint) {
+                    <Self as #me::freeze::Freeze>::before_nep141_mint(mint);
+                }
+
+                fn before_burn(&mut self, burn: &#me::standard::nep141::Nep141Burn) {
+                    <Self as #me::freeze::Freeze>::before_nep141_burn(burn);
+                }
+
+                fn refundable_amount(&self, refund: &#me::standard::nep141::Nep141Transfer) -> u128 {
+                    <Self as #me::freeze::Freeze>::refundable_nep141_amount(refund)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::freeze::FreezeInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::freeze::FreezeExternal for #ident #ty #wher {
+            fn frz_is_frozen(&self, account_id: #near_sdk::AccountId) -> bool {
+                <Self as #me::freeze::Freeze>::is_frozen(&account_id)
+            }
+        }
+
+        #nep141_hook
+    })
+}
@@ -5,13 +5,17 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input, AttributeArgs, DeriveInput, Item};
 
 mod approval;
+mod freeze;
 mod migrate;
 mod owner;
 mod pause;
 mod rbac;
 mod rename;
 mod standard;
+mod transfer_fee;
 mod upgrade;
+mod vesting;
+mod wrapped_near;
 
 fn default_crate_name() -> syn::Path {
     syn::parse_str("::near_sdk_contract_tools").unwrap()
@@ -95,6 +99,24 @@ pub fn derive_pause(input: TokenStream) -> TokenStream {
     make_derive(input, pause::expand)
 }
 
+/// Makes it possible to freeze individual accounts. Provides an
+/// implementation of the `Freeze` trait.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~f"`) using `#[freeze(storage_key = "<expression>")]`.
+///
+/// Use `#[freeze(nep141_hook)]` to also implement `Nep141Hook`, rejecting
+/// NEP-141 transfers and transfer call refunds from or to frozen accounts, as
+/// well as mints to and burns from frozen accounts. The NEP-141 hooks must
+/// not be disabled (`no_hooks`). A contract can only have one `Nep141Hook`
+/// implementation, so to combine this with other hooks (e.g.
+/// `#[vesting(nep141_hook)]`), implement `Nep141Hook` manually and call the
+/// `Freeze::before_nep141_*` functions and `Freeze::refundable_nep141_amount`.
+#[proc_macro_derive(Freeze, attributes(freeze))]
+pub fn derive_freeze(input: TokenStream) -> TokenStream {
+    make_derive(input, freeze::expand)
+}
+
 /// Adds role-based access control. No external methods are exposed.
 ///
 /// The roles prefix can be specified using `#[rbac(roles = "MyRoles")]`.
@@ -114,11 +136,69 @@ pub fn derive_rbac(input: TokenStream) -> TokenStream {
 ///
 /// The storage key prefix for the fields can be optionally specified (default:
 /// `"~$141"`) using `#[nep141(storage_key = "<expression>")]`.
+///
+/// Use `#[nep141(require_registration)]` to reject deposits and transfers to
+/// accounts that are not registered with the NEP-145 controller, which must
+/// then be implemented as well (e.g. using `#[derive(Nep145)]`).
+///
+/// Use `#[nep141(allowance)]` to also expose `ft_approve`, `ft_allowance`, and
+/// `ft_transfer_from` (see: `Nep141AllowanceController`). Transfers performed
+/// by `ft_transfer_from` call the `Nep141Hook` hooks, like `ft_transfer`.
+/// `ft_approve` charges the owner the storage fee for new allowances, and
+/// refunds it when an allowance is cleared or used up. The unused attached
+/// deposit of `ft_approve` is always returned.
+///
+/// Use `#[nep141(transfer_batch)]` to also expose `ft_transfer_batch`, which
+/// performs many transfers from the predecessor in one call and emits a
+/// single `ft_transfer` event (see: `Nep141Controller::transfer_batch`).
+///
+/// Use `#[nep141(snapshot)]` to record balance and total supply checkpoints
+/// on every change, and to expose `ft_balance_of_at` and `ft_total_supply_at`
+/// (see: `Nep141SnapshotController`). Requires `require_registration`. The
+/// storage fee for the checkpoints written by `ft_transfer`,
+/// `ft_transfer_call` (including its refund), `ft_transfer_batch`, and
+/// `ft_transfer_from` is charged to the predecessor's available NEP-145
+/// storage balance. Checkpoints written by other methods must be paid for by
+/// them (see: `nep145::charge_storage_fee`).
+///
+/// Use `#[nep141(max_supply = "<expression>")]` to cap the total supply at
+/// this `u128` value. Mints and deposits that would raise the total supply
+/// above it fail (see: `Nep141ControllerInternal::max_supply`).
+///
+/// The gas budgets for transfer calls can be optionally specified (defaults:
+/// `GAS_FOR_RESOLVE_TRANSFER` and `GAS_FOR_FT_TRANSFER_CALL`) using
+/// `#[nep141(gas_for_resolve_transfer = "<expression>")]` and
+/// `#[nep141(gas_for_ft_transfer_call = "<expression>")]`. The gas for
+/// transfer calls is reserved out of the prepaid gas, and must be at least the
+/// gas for resolving them.
+///
+/// Transfers, batch transfers, mints, and burns performed through
+/// `Nep141Controller` (e.g. `Nep141Controller::transfer`) call the
+/// `Nep141Hook` hooks. `Nep141Controller::transfer_unchecked` moves tokens
+/// without calling them. Hooks can be disabled using `#[nep141(no_hooks)]`.
+/// For transfers performed by `ft_transfer_call`, the hooks receive the
+/// message in `Nep141Transfer::msg` (see: `Nep141Transfer::is_transfer_call`).
 #[proc_macro_derive(Nep141, attributes(nep141))]
 pub fn derive_nep141(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep141::expand)
 }
 
+/// Adds NEP-145 storage management functionality to a contract. Exposes
+/// `storage_*` functions to the public blockchain, implements internal
+/// controller functionality (see: [`near_sdk_contract_tools::standard::nep145`]).
+///
+/// Storage balance bounds default to a minimum of the storage fee for one
+/// account's storage balance record and no maximum. Contracts that store more
+/// data per account should set the bounds during contract initialization
+/// using `Nep145Controller::set_storage_balance_bounds`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$145"`) using `#[nep145(storage_key = "<expression>")]`.
+#[proc_macro_derive(Nep145, attributes(nep145))]
+pub fn derive_nep145(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep145::expand)
+}
+
 /// Adds NEP-148 fungible token metadata functionality to a contract. Metadata
 /// is hardcoded into the contract code, and is therefore not stored in storage.
 ///
@@ -137,16 +217,211 @@ pub fn derive_nep148(input: TokenStream) -> TokenStream {
     make_derive(input, standard::nep148::expand)
 }
 
+/// Adds NEP-171 non-fungible token core functionality to a contract. Exposes
+/// `nft_*` functions to the public blockchain, implements internal controller
+/// and receiver functionality (see: [`near_sdk_contract_tools::standard::nep171`]).
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$171"`) using `#[nep171(storage_key = "<expression>")]`.
+///
+/// Extensions to the core functionality (e.g. approval management) can be
+/// specified using `#[nep171(extension = "<type>")]`. Multiple extensions can
+/// be combined in a tuple: `#[nep171(extension = "(A, B)")]`.
+///
+/// Transfers performed through `Nep171Controller` (including
+/// `nft_transfer_payout`) call the `Nep171Hook` hooks. Returning a token in
+/// `nft_resolve_transfer` does not. Hooks can be disabled using
+/// `#[nep171(no_hooks)]`.
+#[proc_macro_derive(Nep171, attributes(nep171))]
+pub fn derive_nep171(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep171::expand)
+}
+
+/// Adds NEP-177 non-fungible token metadata functionality to a contract.
+/// Exposes `nft_metadata` to the public blockchain, and implements internal
+/// controller functionality for contract and token metadata (see:
+/// [`near_sdk_contract_tools::standard::nep177`]). Contract metadata and
+/// token metadata are stored in storage.
+///
+/// Requires the NEP-171 controller to be implemented as well (e.g. using
+/// `#[derive(Nep171)]`). Use `#[nep171(extension = "Nep177Extension")]` to
+/// include token metadata in `nft_token` responses.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$177"`) using `#[nep177(storage_key = "<expression>")]`.
+#[proc_macro_derive(Nep177, attributes(nep177))]
+pub fn derive_nep177(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep177::expand)
+}
+
+/// Adds NEP-178 non-fungible token approval management functionality to a
+/// contract. Exposes `nft_approve`, `nft_revoke`, `nft_revoke_all`, and
+/// `nft_is_approved` to the public blockchain, and implements internal
+/// controller functionality (see: [`near_sdk_contract_tools::standard::nep178`]).
+///
+/// Requires the NEP-171 controller to be implemented as well (e.g. using
+/// `#[derive(Nep171)]`). Use `#[nep171(extension = "Nep178Extension")]` to
+/// allow approved accounts to transfer tokens.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$178"`) using `#[nep178(storage_key = "<expression>")]`.
+#[proc_macro_derive(Nep178, attributes(nep178))]
+pub fn derive_nep178(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep178::expand)
+}
+
+/// Adds NEP-181 non-fungible token enumeration functionality to a contract.
+/// Exposes `nft_total_supply`, `nft_tokens`, `nft_supply_for_owner`, and
+/// `nft_tokens_for_owner` to the public blockchain, and implements internal
+/// controller functionality (see: [`near_sdk_contract_tools::standard::nep181`]).
+///
+/// Requires the NEP-171 controller to be implemented as well (e.g. using
+/// `#[derive(Nep171)]`). Use `#[nep171(extension = "Nep181Extension")]` to
+/// keep the enumeration indexes up-to-date.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$181"`) using `#[nep181(storage_key = "<expression>")]`.
+#[proc_macro_derive(Nep181, attributes(nep181))]
+pub fn derive_nep181(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep181::expand)
+}
+
+/// Adds NEP-199 non-fungible token royalty and payout functionality to a
+/// contract. Exposes `nft_payout` and `nft_transfer_payout` to the public
+/// blockchain, and implements internal controller functionality (see:
+/// [`near_sdk_contract_tools::standard::nep199`]).
+///
+/// Requires the NEP-171 controller to be implemented as well (e.g. using
+/// `#[derive(Nep171)]`). `nft_transfer_payout` performs transfers through the
+/// NEP-171 controller, so transfer extensions apply. Use
+/// `#[nep171(extension = "Nep199Extension")]` to remove royalties when tokens
+/// are burned.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$199"`) using `#[nep199(storage_key = "<expression>")]`.
+///
+/// Royalty tables may include at most `max_len_payout - 1` accounts. The
+/// maximum payout length can be optionally specified (default:
+/// `MAX_LEN_PAYOUT`) using `#[nep199(max_len_payout = "<expression>")]`.
+///
+/// `nft_transfer_payout` calls the `Nep171Hook` transfer hooks, as
+/// configured by the `Nep171` derive macro.
+#[proc_macro_derive(Nep199, attributes(nep199))]
+pub fn derive_nep199(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep199::expand)
+}
+
+/// Adds NEP-245 multi-token core functionality to a contract. Exposes `mt_*`
+/// functions to the public blockchain, implements internal controller and
+/// receiver functionality (see: [`near_sdk_contract_tools::standard::nep245`]).
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$245"`) using `#[nep245(storage_key = "<expression>")]`.
+///
+/// Transfers performed through `Nep245Controller` call the `Nep245Hook`
+/// hooks. Refunds in `mt_resolve_transfer` do not. Hooks can be disabled
+/// using `#[nep245(no_hooks)]`.
+#[proc_macro_derive(Nep245, attributes(nep245))]
+pub fn derive_nep245(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep245::expand)
+}
+
+/// Adds NEP-330 contract source metadata functionality to a contract.
+/// Exposes `contract_source_metadata` to the public blockchain, and implements
+/// internal controller functionality (see:
+/// [`near_sdk_contract_tools::standard::nep330`]). Metadata is hardcoded into
+/// the contract code.
+///
+/// Fields may be specified in the `#[nep330(...)]` attribute:
+///  - `version` (optional) - Default: the `CARGO_PKG_VERSION` of the crate.
+///  - `link` (optional) - Default: the `CARGO_PKG_REPOSITORY` of the crate, if
+///    set.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~$330"`) using `#[nep330(storage_key = "<expression>")]`.
+#[proc_macro_derive(Nep330, attributes(nep330))]
+pub fn derive_nep330(input: TokenStream) -> TokenStream {
+    make_derive(input, standard::nep330::expand)
+}
+
 /// Implements NEP-141 and NEP-148 functionality, like
 /// `#[derive(Nep141, Nep148)]`.
 ///
 /// Attributes are the union of those for the constituent derive macros.
 /// Specify attributes with `#[fungible_token(...)]`.
+///
+/// `#[fungible_token(require_registration)]` additionally implements NEP-145,
+/// and requires accounts to be registered before they can receive tokens. The
+/// default minimum storage balance covers both the storage balance record and
+/// the token balance record of an account. The NEP-145 storage key can be
+/// specified with `nep145_storage_key = "<expression>"`. Unregistering an
+/// account with a positive token balance burns the balance if `force` is set,
+/// and fails otherwise.
+///
+/// External mint and burn methods can be optionally generated:
+///
+///  - `mint = "owner" | "role(<expression>)"` - Generates
+///    `ft_mint(account_id, amount, memo)`, callable by the owner (see:
+///    `Owner`) or by accounts with the given role (see: `Rbac`).
+///  - `burn = "owner" | "role(<expression>)" | "self"` - Generates
+///    `ft_burn(account_id, amount, memo)` with the same guards, or, for
+///    `"self"`, `ft_burn(amount, memo)`, which burns the predecessor's own
+///    tokens.
+///
+/// Both methods require a deposit of exactly 1 yoctoNEAR. With `snapshot`,
+/// both also charge the storage fee for their checkpoints to the predecessor's
+/// available NEP-145 storage balance.
 #[proc_macro_derive(FungibleToken, attributes(fungible_token))]
 pub fn derive_fungible_token(input: TokenStream) -> TokenStream {
     make_derive(input, standard::fungible_token::expand)
 }
 
+/// Charges a fee on NEP-141 transfers. Provides an implementation of the
+/// `TransferFee` trait, and exposes `fee_config` and `fee_is_exempt`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~tf"`) using `#[transfer_fee(storage_key = "<expression>")]`.
+///
+/// Use `#[transfer_fee(nep141_hook)]` to also implement `Nep141Hook`,
+/// charging the fee to the receiver after each transfer. The NEP-141 hooks
+/// must not be disabled (`no_hooks`). A contract can only have one
+/// `Nep141Hook` implementation, so to combine this with other hooks,
+/// implement `Nep141Hook` manually and call
+/// `TransferFee::before_nep141_transfer` and
+/// `TransferFee::charge_transfer_fee`.
+#[proc_macro_derive(TransferFee, attributes(transfer_fee))]
+pub fn derive_transfer_fee(input: TokenStream) -> TokenStream {
+    make_derive(input, transfer_fee::expand)
+}
+
+/// Adds cliff + linear vesting schedules for NEP-141 tokens. Provides an
+/// implementation of the `Vesting` trait, and exposes `vest_schedule_of`,
+/// `vest_claimable_of`, `vest_locked_of`, and `vest_claim`.
+///
+/// The storage key prefix for the fields can be optionally specified (default:
+/// `"~v"`) using `#[vesting(storage_key = "<expression>")]`.
+///
+/// Use `#[vesting(nep141_hook)]` to also implement `Nep141Hook`, rejecting
+/// NEP-141 transfers and burns of locked tokens, and limiting transfer call
+/// refunds to unlocked tokens. The NEP-141 hooks must not be disabled
+/// (`no_hooks`). A contract can only have one `Nep141Hook` implementation,
+/// so to combine this with other hooks, implement `Nep141Hook` manually and
+/// call the `Vesting::before_nep141_*` functions and
+/// `Vesting::refundable_nep141_amount`.
+#[proc_macro_derive(Vesting, attributes(vesting))]
+pub fn derive_vesting(input: TokenStream) -> TokenStream {
+    make_derive(input, vesting::expand)
+}
+
+/// Makes a fungible token that is backed 1:1 by native NEAR. Exposes
+/// `near_deposit` and `near_withdraw` (see: `WrappedNear`). The contract
+/// must implement NEP-141 with NEP-145 registration as well (e.g. using
+/// `#[derive(FungibleToken)]` with `#[fungible_token(require_registration)]`).
+#[proc_macro_derive(WrappedNear, attributes(wrapped_near))]
+pub fn derive_wrapped_near(input: TokenStream) -> TokenStream {
+    make_derive(input, wrapped_near::expand)
+}
+
 /// Migrate a contract's default struct from one schema to another.
 ///
 /// Fields may be specified in the `#[migrate(...)]` attribute.
@@ -166,9 +441,14 @@ pub fn derive_migrate(input: TokenStream) -> TokenStream {
     make_derive(input, migrate::expand)
 }
 
-/// Create a simple multisig component. Does not expose any functions to the
-/// blockchain. Creates implementations for `ApprovalManager` and
-/// `AccountApprover` for the target contract struct.
+/// Create a simple multisig component. Creates implementations for
+/// `ApprovalManager` and `AccountApprover` for the target contract struct.
+///
+/// Exposes the view functions `multisig_list_requests(from_index, limit)` and
+/// `multisig_count_requests()` to enumerate pending requests. `limit`
+/// defaults to `DEFAULT_LIST_REQUESTS_LIMIT` and is capped at
+/// `MAX_LIST_REQUESTS_LIMIT`. Requests created before upgrading to a version
+/// with these views must be indexed using `ApprovalManager::index_requests`.
 ///
 /// Fields may be specified in the `#[simple_multisig(...)]` attribute.
 ///
@@ -182,6 +462,29 @@ pub fn derive_simple_multisig(input: TokenStream) -> TokenStream {
     make_derive(input, approval::simple_multisig::expand)
 }
 
+/// Create a weighted multisig component. Creates implementations for
+/// `ApprovalManager` and `AccountWeighter` for the target contract struct.
+///
+/// Exposes the view functions `multisig_list_requests(from_index, limit)` and
+/// `multisig_count_requests()` to enumerate pending requests. `limit`
+/// defaults to `DEFAULT_LIST_REQUESTS_LIMIT` and is capped at
+/// `MAX_LIST_REQUESTS_LIMIT`. Requests created before upgrading to a version
+/// with these views must be indexed using `ApprovalManager::index_requests`.
+///
+/// Fields may be specified in the `#[weighted_multisig(...)]` attribute.
+///
+/// Fields include:
+///  - `storage_key` Storage prefix for multisig data (optional, default: `b"~am"`)
+///  - `action` What sort of approval `Action` can be approved by the multisig
+///     component?
+///  - `weight` Function of type `fn(&AccountId) -> u64` that returns the
+///     approval weight of an account. Accounts with a weight of zero may not
+///     act on requests.
+#[proc_macro_derive(WeightedMultisig, attributes(weighted_multisig))]
+pub fn derive_weighted_multisig(input: TokenStream) -> TokenStream {
+    make_derive(input, approval::weighted_multisig::expand)
+}
+
 /// Smart `#[event]` macro.
 ///
 /// See documentation on the [`derive@Nep297`] derive macro for more details.
@@ -210,6 +513,7 @@ pub fn event(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///  - `migrate_method_name` - The name of the method to call after the upgrade. Default `"migrate"`.
 ///  - `migrate_method_args` - The input to send to the migrate function. Default empty vector.
 ///  - `migrate_minimum_gas` - How much gas to guarantee the migrate function, otherwise reject. Default 15T.
+///  - `nep330` - If included, records the version of the code being replaced using the `Nep330Controller` implementation (e.g. from `#[derive(Nep330)]`).
 #[proc_macro_derive(Upgrade, attributes(upgrade))]
 pub fn derive_upgrade(input: TokenStream) -> TokenStream {
     make_derive(input, upgrade::expand)
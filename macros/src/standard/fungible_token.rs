@@ -1,9 +1,43 @@
-use darling::{util::Flag, FromDeriveInput};
+use darling::{util::Flag, FromDeriveInput, FromMeta};
+use once_cell::sync::OnceCell;
 use proc_macro2::TokenStream;
 use quote::quote;
+use regex::Regex;
 use syn::Expr;
 
-use super::{nep141, nep148};
+use super::{nep141, nep145, nep148};
+
+/// Who may call a generated `ft_mint` or `ft_burn` method
+#[derive(Debug, Clone)]
+pub enum Guard {
+    Owner,
+    Role(Box<syn::Expr>),
+    /// The predecessor may only burn its own tokens
+    Predecessor,
+}
+
+impl FromMeta for Guard {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        static REGEX: OnceCell<Regex> = OnceCell::new();
+
+        if value == "owner" {
+            Ok(Guard::Owner)
+        } else if value == "self" {
+            Ok(Guard::Predecessor)
+        } else {
+            let r = REGEX.get_or_init(|| Regex::new(r"^role\((.+)\)$").unwrap());
+            r.captures(value)
+                .and_then(|c| c.get(1))
+                .and_then(|s| syn::parse_str::<Expr>(s.as_str()).ok())
+                .map(|e| Guard::Role(Box::new(e)))
+                .ok_or_else(|| {
+                    darling::Error::custom(format!(
+                        r#"Invalid value "{value}", expected "owner", "self", or "role(...)""#,
+                    ))
+                })
+        }
+    }
+}
 
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(fungible_token), supports(struct_named))]
@@ -11,6 +45,18 @@ pub struct FungibleTokenMeta {
     // NEP-141 fields
     pub storage_key: Option<Expr>,
     pub no_hooks: Flag,
+    pub require_registration: Flag,
+    pub nep145_storage_key: Option<Expr>,
+    pub allowance: Flag,
+    pub transfer_batch: Flag,
+    pub snapshot: Flag,
+    pub max_supply: Option<Expr>,
+    pub gas_for_resolve_transfer: Option<Expr>,
+    pub gas_for_ft_transfer_call: Option<Expr>,
+
+    // Mint & burn
+    pub mint: Option<Guard>,
+    pub burn: Option<Guard>,
 
     // NEP-148 fields
     pub spec: Option<String>,
@@ -36,6 +82,17 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
     let FungibleTokenMeta {
         storage_key,
         no_hooks,
+        require_registration,
+        nep145_storage_key,
+        allowance,
+        transfer_batch,
+        snapshot,
+        max_supply,
+        gas_for_resolve_transfer,
+        gas_for_ft_transfer_call,
+
+        mint,
+        burn,
 
         spec,
         name,
@@ -52,9 +109,18 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
         near_sdk,
     } = meta;
 
+    let charge_checkpoint_storage = snapshot.is_present();
+
     let expand_nep141 = nep141::expand(nep141::Nep141Meta {
         storage_key,
         no_hooks,
+        require_registration,
+        allowance,
+        transfer_batch,
+        snapshot,
+        max_supply,
+        gas_for_resolve_transfer,
+        gas_for_ft_transfer_call,
 
         generics: generics.clone(),
         ident: ident.clone(),
@@ -63,6 +129,186 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
         near_sdk: near_sdk.clone(),
     });
 
+    let nep145_storage_key_without_registration =
+        nep145_storage_key.is_some() && !require_registration.is_present();
+
+    let expand_nep145 = require_registration.is_present().then(|| {
+        let before_unregister = quote! {
+            let token_balance =
+                <Self as #me::standard::nep141::Nep141Controller>::balance_of(&account_id);
+
+            if token_balance > 0 {
+                if !force {
+                    #near_sdk::env::panic_str(
+                        "Cannot unregister an account with a positive token balance without `force`",
+                    );
+                }
+
+                #me::standard::nep141::Nep141Controller::burn(
+                    self,
+                    account_id.clone(),
+                    token_balance,
+                    None,
+                );
+            }
+
+            <Self as #me::standard::nep141::Nep141ControllerInternal>::slot_account(&account_id)
+                .remove();
+        };
+
+        // Registration also creates the account's token balance record
+        let internal = quote! {
+            fn storage_usage_per_account() -> u64 {
+                let account_id = #me::standard::nep145::longest_account_id();
+
+                #me::standard::nep145::record_storage_usage(
+                    &<Self as #me::standard::nep145::Nep145ControllerInternal>::slot_storage_balance(
+                        &account_id,
+                    ),
+                    &#me::standard::nep145::StorageBalance {
+                        total: 0.into(),
+                        available: 0.into(),
+                    },
+                ) + #me::standard::nep145::record_storage_usage(
+                    &<Self as #me::standard::nep141::Nep141ControllerInternal>::slot_account(
+                        &account_id,
+                    ),
+                    &0,
+                )
+            }
+        };
+
+        nep145::expand_with(
+            nep145::Nep145Meta {
+                storage_key: nep145_storage_key,
+
+                generics: generics.clone(),
+                ident: ident.clone(),
+
+                me: me.clone(),
+                near_sdk: near_sdk.clone(),
+            },
+            nep145::Nep145Additions {
+                before_unregister: Some(before_unregister),
+                internal: Some(internal),
+            },
+        )
+    });
+
+    let mut e = darling::Error::accumulator();
+
+    if nep145_storage_key_without_registration {
+        e.push(darling::Error::custom(
+            "`nep145_storage_key` requires `require_registration`",
+        ));
+    }
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let guard = |guard: &Guard| match guard {
+        Guard::Owner => quote! {
+            <Self as #me::owner::Owner>::require_owner();
+        },
+        Guard::Role(role) => quote! {
+            <Self as #me::rbac::Rbac>::require_role(&#role);
+        },
+        Guard::Predecessor => quote! {},
+    };
+
+    // Checkpoints are paid for by the predecessor's storage balance
+    let (measure_storage, charge_storage) = if charge_checkpoint_storage {
+        (
+            quote! {
+                let initial_storage_usage = #near_sdk::env::storage_usage();
+            },
+            quote! {
+                #me::standard::nep145::charge_storage_fee(
+                    self,
+                    &#near_sdk::env::predecessor_account_id(),
+                    initial_storage_usage,
+                    0,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let mint = mint.and_then(|mint| {
+        if let Guard::Predecessor = mint {
+            e.push(darling::Error::custom(
+                r#"Invalid value "self" for `mint`, expected "owner" or "role(...)""#,
+            ));
+            return None;
+        }
+
+        let guard = guard(&mint);
+
+        Some(quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                #[payable]
+                pub fn ft_mint(
+                    &mut self,
+                    account_id: #near_sdk::AccountId,
+                    amount: #near_sdk::json_types::U128,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::assert_one_yocto();
+                    #guard
+                    #measure_storage
+
+                    #me::standard::nep141::Nep141Controller::mint(
+                        self,
+                        account_id,
+                        amount.into(),
+                        memo,
+                    );
+                    #charge_storage
+                }
+            }
+        })
+    });
+
+    let burn = burn.map(|burn| {
+        let guard = guard(&burn);
+
+        let (account_id_parameter, account_id) = match burn {
+            Guard::Predecessor => (
+                quote! {},
+                quote! { let account_id = #near_sdk::env::predecessor_account_id(); },
+            ),
+            _ => (quote! { account_id: #near_sdk::AccountId, }, quote! {}),
+        };
+
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #ident #ty #wher {
+                #[payable]
+                pub fn ft_burn(
+                    &mut self,
+                    #account_id_parameter
+                    amount: #near_sdk::json_types::U128,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::assert_one_yocto();
+                    #guard
+                    #account_id
+                    #measure_storage
+
+                    #me::standard::nep141::Nep141Controller::burn(
+                        self,
+                        account_id,
+                        amount.into(),
+                        memo,
+                    );
+                    #charge_storage
+                }
+            }
+        }
+    });
+
     let expand_nep148 = nep148::expand(nep148::Nep148Meta {
         spec,
         name,
@@ -79,13 +325,15 @@ pub fn expand(meta: FungibleTokenMeta) -> Result<TokenStream, darling::Error> {
         near_sdk,
     });
 
-    let mut e = darling::Error::accumulator();
-
     let nep141 = e.handle(expand_nep141);
+    let nep145 = expand_nep145.and_then(|r| e.handle(r));
     let nep148 = e.handle(expand_nep148);
 
     e.finish_with(quote! {
         #nep141
+        #nep145
         #nep148
+        #mint
+        #burn
     })
 }
@@ -2,5 +2,13 @@ pub mod event;
 pub mod fungible_token;
 
 pub mod nep141;
+pub mod nep145;
 pub mod nep148;
+pub mod nep171;
+pub mod nep177;
+pub mod nep178;
+pub mod nep181;
+pub mod nep199;
+pub mod nep245;
 pub mod nep297;
+pub mod nep330;
@@ -10,6 +10,13 @@ use syn::Expr;
 pub struct Nep141Meta {
     pub storage_key: Option<Expr>,
     pub no_hooks: Flag,
+    pub require_registration: Flag,
+    pub allowance: Flag,
+    pub transfer_batch: Flag,
+    pub snapshot: Flag,
+    pub max_supply: Option<Expr>,
+    pub gas_for_resolve_transfer: Option<Expr>,
+    pub gas_for_ft_transfer_call: Option<Expr>,
     pub generics: syn::Generics,
     pub ident: syn::Ident,
 
@@ -24,6 +31,13 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
     let Nep141Meta {
         storage_key,
         no_hooks,
+        require_registration,
+        allowance,
+        transfer_batch,
+        snapshot,
+        max_supply,
+        gas_for_resolve_transfer,
+        gas_for_ft_transfer_call,
         generics,
         ident,
 
@@ -31,8 +45,53 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
         near_sdk,
     } = meta;
 
+    if snapshot.is_present() && !require_registration.is_present() {
+        return Err(darling::Error::custom(
+            "`snapshot` requires `require_registration`",
+        ));
+    }
+
     let (imp, ty, wher) = generics.split_for_impl();
 
+    // Checkpoints are paid for by the predecessor's storage balance
+    let measure_storage = snapshot.is_present().then(|| {
+        quote! {
+            let initial_storage_usage = #near_sdk::env::storage_usage();
+        }
+    });
+    let charge_storage = |account_id: TokenStream,
+                          initial_storage_usage: TokenStream,
+                          additional_storage_usage: TokenStream| {
+        snapshot.is_present().then(|| {
+            quote! {
+                #me::standard::nep145::charge_storage_fee(
+                    self,
+                    &#account_id,
+                    #initial_storage_usage,
+                    #additional_storage_usage,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+            }
+        })
+    };
+    let charge_transfer = charge_storage(
+        quote! { sender_id },
+        quote! { initial_storage_usage },
+        quote! { 0 },
+    );
+    // The owner is refunded for a used up allowance record separately
+    let charge_transfer_from = charge_storage(
+        quote! { spender_id },
+        quote! { initial_storage_usage - storage_released },
+        quote! { 0 },
+    );
+    // The refund may write a checkpoint for both accounts
+    let charge_transfer_call = charge_storage(
+        quote! { sender_id },
+        quote! { initial_storage_usage },
+        quote! { 2 * #me::standard::nep141::checkpoint_storage_usage::<Self>() },
+    );
+
     let root = storage_key.map(|storage_key| {
         quote! {
             fn root() -> #me::slot::Slot<()> {
@@ -41,21 +100,266 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
         }
     });
 
-    let before_transfer = no_hooks.is_present().not().then(|| {
+    let gas_for_resolve_transfer = gas_for_resolve_transfer.map(|gas| {
+        quote! {
+            fn gas_for_resolve_transfer() -> #near_sdk::Gas {
+                #gas
+            }
+        }
+    });
+
+    let gas_for_ft_transfer_call = gas_for_ft_transfer_call.map(|gas| {
+        quote! {
+            fn gas_for_ft_transfer_call() -> #near_sdk::Gas {
+                #gas
+            }
+        }
+    });
+
+    let is_account_registered = require_registration.is_present().then(|| {
+        quote! {
+            fn is_account_registered(account_id: &#near_sdk::AccountId) -> bool {
+                <Self as #me::standard::nep145::Nep145Controller>::get_storage_balance(account_id)
+                    .is_some()
+            }
+        }
+    });
+
+    let max_supply = max_supply.map(|max_supply| {
+        quote! {
+            fn max_supply() -> Option<u128> {
+                Some(#max_supply)
+            }
+        }
+    });
+
+    let record_checkpoints = snapshot.is_present().then(|| {
+        quote! {
+            fn record_checkpoints() -> bool {
+                true
+            }
+        }
+    });
+
+    let controller_hooks = no_hooks.is_present().not().then(|| {
+        quote! {
+            fn hook_transfer<E>(
+                contract: &mut Self,
+                transfer: &#me::standard::nep141::Nep141Transfer,
+                f: impl FnOnce(&mut Self) -> Result<(), E>,
+            ) -> Result<(), E> {
+                let hook_state = <Self as #me::standard::nep141::Nep141Hook::<_>>::before_transfer(contract, transfer);
+                f(contract)?;
+                <Self as #me::standard::nep141::Nep141Hook::<_>>::after_transfer(contract, transfer, hook_state);
+                Ok(())
+            }
+
+            fn hook_mint<E>(
+                contract: &mut Self,
+                mint: &#me::standard::nep141::Nep141Mint,
+                f: impl FnOnce(&mut Self) -> Result<(), E>,
+            ) -> Result<(), E> {
+                let hook_state = <Self as #me::standard::nep141::Nep141Hook::<_>>::before_mint(contract, mint);
+                f(contract)?;
+                <Self as #me::standard::nep141::Nep141Hook::<_>>::after_mint(contract, mint, hook_state);
+                Ok(())
+            }
+
+            fn refundable_amount(
+                contract: &Self,
+                refund: &#me::standard::nep141::Nep141Transfer,
+            ) -> u128 {
+                <Self as #me::standard::nep141::Nep141Hook::<_>>::refundable_amount(contract, refund)
+            }
+
+            fn hook_burn<E>(
+                contract: &mut Self,
+                burn: &#me::standard::nep141::Nep141Burn,
+                f: impl FnOnce(&mut Self) -> Result<(), E>,
+            ) -> Result<(), E> {
+                let hook_state = <Self as #me::standard::nep141::Nep141Hook::<_>>::before_burn(contract, burn);
+                f(contract)?;
+                <Self as #me::standard::nep141::Nep141Hook::<_>>::after_burn(contract, burn, hook_state);
+                Ok(())
+            }
+        }
+    });
+
+    let allowance = allowance.is_present().then(|| {
         quote! {
-            let hook_state = <Self as #me::standard::nep141::Nep141Hook::<_>>::before_transfer(self, &transfer);
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141Allowance for #ident #ty #wher {
+                #[payable]
+                fn ft_approve(
+                    &mut self,
+                    spender_id: #near_sdk::AccountId,
+                    amount: #near_sdk::json_types::U128,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::require!(
+                        #near_sdk::env::attached_deposit() > 0,
+                        "Requires attached deposit of at least 1 yoctoNEAR",
+                    );
+
+                    let owner_id = #near_sdk::env::predecessor_account_id();
+                    let initial_storage_usage = #near_sdk::env::storage_usage();
+
+                    #me::standard::nep141::Nep141AllowanceController::approve(
+                        self,
+                        owner_id.clone(),
+                        spender_id,
+                        amount.into(),
+                        memo,
+                    );
+
+                    // The owner pays for a new allowance record and is
+                    // refunded when it is cleared. The unused attached
+                    // deposit is always returned.
+                    #me::utils::apply_storage_fee_and_refund(initial_storage_usage, 0);
+                    #me::utils::refund_released_storage_fee(initial_storage_usage, owner_id);
+                }
+
+                fn ft_allowance(
+                    &self,
+                    owner_id: #near_sdk::AccountId,
+                    spender_id: #near_sdk::AccountId,
+                ) -> #near_sdk::json_types::U128 {
+                    <Self as #me::standard::nep141::Nep141AllowanceController>::allowance(
+                        &owner_id,
+                        &spender_id,
+                    )
+                    .into()
+                }
+
+                #[payable]
+                fn ft_transfer_from(
+                    &mut self,
+                    owner_id: #near_sdk::AccountId,
+                    receiver_id: #near_sdk::AccountId,
+                    amount: #near_sdk::json_types::U128,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::assert_one_yocto();
+                    let spender_id = #near_sdk::env::predecessor_account_id();
+                    let amount: u128 = amount.into();
+
+                    let allowance_slot =
+                        <Self as #me::standard::nep141::Nep141ControllerInternal>::slot_allowance(
+                            &owner_id,
+                            &spender_id,
+                        );
+                    let allowance =
+                        <Self as #me::standard::nep141::Nep141AllowanceController>::allowance(
+                            &owner_id,
+                            &spender_id,
+                        );
+                    #measure_storage
+
+                    #me::standard::nep141::Nep141AllowanceController::transfer_from(
+                        self,
+                        &spender_id,
+                        owner_id.clone(),
+                        receiver_id,
+                        amount,
+                        memo,
+                    );
+
+                    // Refund the owner for the allowance record once it is
+                    // used up. It is removed last, so the storage usage
+                    // before its removal excludes any other storage changes
+                    // (e.g. checkpoints).
+                    let storage_released = if allowance > 0 && !allowance_slot.exists() {
+                        #me::standard::nep145::record_storage_usage(&allowance_slot, &allowance)
+                    } else {
+                        0
+                    };
+                    #me::utils::refund_released_storage_fee(
+                        #near_sdk::env::storage_usage() + storage_released,
+                        owner_id,
+                    );
+
+                    #charge_transfer_from
+                }
+            }
         }
     });
 
-    let after_transfer = no_hooks.is_present().not().then(|| {
+    let transfer_batch = transfer_batch.is_present().then(|| {
         quote! {
-            <Self as #me::standard::nep141::Nep141Hook::<_>>::after_transfer(self, &transfer, hook_state);
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141TransferBatch for #ident #ty #wher {
+                #[payable]
+                fn ft_transfer_batch(
+                    &mut self,
+                    receiver_ids: Vec<#near_sdk::AccountId>,
+                    amounts: Vec<#near_sdk::json_types::U128>,
+                    memo: Option<String>,
+                ) {
+                    #near_sdk::assert_one_yocto();
+                    #near_sdk::require!(
+                        receiver_ids.len() == amounts.len(),
+                        "Length mismatch between receiver IDs and amounts",
+                    );
+                    let sender_id = #near_sdk::env::predecessor_account_id();
+                    #measure_storage
+
+                    let transfers = receiver_ids
+                        .into_iter()
+                        .zip(amounts)
+                        .map(|(receiver_id, amount)| #me::standard::nep141::Nep141Transfer {
+                            sender_id: sender_id.clone(),
+                            receiver_id,
+                            amount: amount.into(),
+                            memo: memo.clone(),
+                            msg: None,
+                        })
+                        .collect();
+
+                    #me::standard::nep141::Nep141Controller::transfer_batch(self, transfers);
+                    #charge_transfer
+                }
+            }
+        }
+    });
+
+    let snapshot = snapshot.is_present().then(|| {
+        quote! {
+            #[#near_sdk::near_bindgen]
+            impl #imp #me::standard::nep141::Nep141Snapshot for #ident #ty #wher {
+                fn ft_balance_of_at(
+                    &self,
+                    account_id: #near_sdk::AccountId,
+                    block_height: #near_sdk::json_types::U64,
+                ) -> #near_sdk::json_types::U128 {
+                    <Self as #me::standard::nep141::Nep141SnapshotController>::balance_of_at(
+                        &account_id,
+                        block_height.into(),
+                    )
+                    .into()
+                }
+
+                fn ft_total_supply_at(
+                    &self,
+                    block_height: #near_sdk::json_types::U64,
+                ) -> #near_sdk::json_types::U128 {
+                    <Self as #me::standard::nep141::Nep141SnapshotController>::total_supply_at(
+                        block_height.into(),
+                    )
+                    .into()
+                }
+            }
         }
     });
 
     Ok(quote! {
         impl #imp #me::standard::nep141::Nep141ControllerInternal for #ident #ty #wher {
             #root
+            #gas_for_resolve_transfer
+            #gas_for_ft_transfer_call
+            #is_account_registered
+            #max_supply
+            #record_checkpoints
+            #controller_hooks
         }
 
         #[#near_sdk::near_bindgen]
@@ -67,36 +371,18 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                 amount: #near_sdk::json_types::U128,
                 memo: Option<String>,
             ) {
-                use #me::{
-                    standard::{
-                        nep141::{Nep141Controller, event},
-                        nep297::Event,
-                    },
-                };
-
                 #near_sdk::assert_one_yocto();
                 let sender_id = #near_sdk::env::predecessor_account_id();
-                let amount: u128 = amount.into();
-
-                let transfer = #me::standard::nep141::Nep141Transfer {
-                    sender_id: sender_id.clone(),
-                    receiver_id: receiver_id.clone(),
-                    amount,
-                    memo: memo.clone(),
-                    msg: None,
-                };
-
-                #before_transfer
+                #measure_storage
 
-                Nep141Controller::transfer(
+                #me::standard::nep141::Nep141Controller::transfer(
                     self,
                     sender_id.clone(),
-                    receiver_id.clone(),
-                    amount,
+                    receiver_id,
+                    amount.into(),
                     memo,
                 );
-
-                #after_transfer
+                #charge_transfer
             }
 
             #[payable]
@@ -109,31 +395,20 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
             ) -> #near_sdk::Promise {
                 #near_sdk::assert_one_yocto();
                 let sender_id = #near_sdk::env::predecessor_account_id();
-                let amount: u128 = amount.into();
-
-                let transfer = #me::standard::nep141::Nep141Transfer {
-                    sender_id: sender_id.clone(),
-                    receiver_id: receiver_id.clone(),
-                    amount,
-                    memo: memo.clone(),
-                    msg: None,
-                };
-
-                #before_transfer
+                #measure_storage
 
-                let r = #me::standard::nep141::Nep141Controller::transfer_call(
+                let promise = #me::standard::nep141::Nep141Controller::transfer_call(
                     self,
                     sender_id.clone(),
-                    receiver_id.clone(),
-                    amount,
+                    receiver_id,
+                    amount.into(),
                     memo,
-                    msg.clone(),
+                    msg,
                     #near_sdk::env::prepaid_gas(),
                 );
+                #charge_transfer_call
 
-                #after_transfer
-
-                r
+                promise
             }
 
             fn ft_total_supply(&self) -> #near_sdk::json_types::U128 {
@@ -162,5 +437,9 @@ pub fn expand(meta: Nep141Meta) -> Result<TokenStream, darling::Error> {
                 ).into()
             }
         }
+
+        #allowance
+        #transfer_batch
+        #snapshot
     })
 }
@@ -0,0 +1,164 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep145), supports(struct_named))]
+pub struct Nep145Meta {
+    pub storage_key: Option<Expr>,
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep145Meta) -> Result<TokenStream, darling::Error> {
+    expand_with(meta, Default::default())
+}
+
+/// Code added to the generated implementation by other components
+#[derive(Default)]
+pub struct Nep145Additions {
+    /// Executed in `storage_unregister` after it has been determined that the
+    /// predecessor (`account_id`) is registered. May read the `force: bool`
+    /// argument.
+    pub before_unregister: Option<TokenStream>,
+    /// Additional items in the `Nep145ControllerInternal` implementation
+    pub internal: Option<TokenStream>,
+}
+
+pub fn expand_with(
+    meta: Nep145Meta,
+    additions: Nep145Additions,
+) -> Result<TokenStream, darling::Error> {
+    let Nep145Additions {
+        before_unregister,
+        internal,
+    } = additions;
+
+    let Nep145Meta {
+        storage_key,
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    // `force` has no effect unless there is account data to clean up
+    let before_unregister = before_unregister.unwrap_or_else(|| quote! { let _ = force; });
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep145::Nep145ControllerInternal for #ident #ty #wher {
+            #root
+            #internal
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep145::Nep145 for #ident #ty #wher {
+            #[payable]
+            fn storage_deposit(
+                &mut self,
+                account_id: Option<#near_sdk::AccountId>,
+                registration_only: Option<bool>,
+            ) -> #me::standard::nep145::StorageBalance {
+                let predecessor = #near_sdk::env::predecessor_account_id();
+                let account_id = account_id.unwrap_or_else(|| predecessor.clone());
+
+                let (balance, refund) =
+                    #me::standard::nep145::Nep145Controller::deposit_to_storage_account(
+                        self,
+                        &account_id,
+                        #near_sdk::env::attached_deposit(),
+                        registration_only.unwrap_or(false),
+                    )
+                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+
+                if refund > 0 {
+                    #near_sdk::Promise::new(predecessor).transfer(refund);
+                }
+
+                balance
+            }
+
+            #[payable]
+            fn storage_withdraw(
+                &mut self,
+                amount: Option<#near_sdk::json_types::U128>,
+            ) -> #me::standard::nep145::StorageBalance {
+                use #me::standard::nep145::Nep145Controller;
+
+                #near_sdk::assert_one_yocto();
+                let account_id = #near_sdk::env::predecessor_account_id();
+
+                let available_before = <Self as Nep145Controller>::get_storage_balance(&account_id)
+                    .map_or(0, |balance| balance.available.0);
+
+                let balance = Nep145Controller::withdraw_from_storage_account(
+                    self,
+                    &account_id,
+                    amount.map(|amount| amount.0),
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+
+                let withdrawn = available_before - balance.available.0;
+
+                if withdrawn > 0 {
+                    #near_sdk::Promise::new(account_id).transfer(withdrawn);
+                }
+
+                balance
+            }
+
+            #[payable]
+            fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+                use #me::standard::nep145::Nep145Controller;
+
+                #near_sdk::assert_one_yocto();
+                let account_id = #near_sdk::env::predecessor_account_id();
+                let force = force.unwrap_or(false);
+
+                if <Self as Nep145Controller>::get_storage_balance(&account_id).is_none() {
+                    return false;
+                }
+
+                #before_unregister
+
+                let balance = Nep145Controller::unregister_storage_account(self, &account_id)
+                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+
+                if balance.total.0 > 0 {
+                    #near_sdk::Promise::new(account_id).transfer(balance.total.0);
+                }
+
+                true
+            }
+
+            fn storage_balance_of(
+                &self,
+                account_id: #near_sdk::AccountId,
+            ) -> Option<#me::standard::nep145::StorageBalance> {
+                <Self as #me::standard::nep145::Nep145Controller>::get_storage_balance(&account_id)
+            }
+
+            fn storage_balance_bounds(&self) -> #me::standard::nep145::StorageBalanceBounds {
+                <Self as #me::standard::nep145::Nep145Controller>::get_storage_balance_bounds()
+            }
+        }
+    })
+}
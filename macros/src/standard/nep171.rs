@@ -0,0 +1,157 @@
+use std::ops::Not;
+
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, Type};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep171), supports(struct_named))]
+pub struct Nep171Meta {
+    pub storage_key: Option<Expr>,
+    pub no_hooks: Flag,
+    pub extension: Option<Type>,
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep171Meta) -> Result<TokenStream, darling::Error> {
+    let Nep171Meta {
+        storage_key,
+        no_hooks,
+        extension,
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    let extension = extension
+        .map(|extension| quote! { #extension })
+        .unwrap_or_else(|| quote! { () });
+
+    let controller_hooks = no_hooks.is_present().not().then(|| {
+        quote! {
+            fn hook_transfer<E>(
+                contract: &mut Self,
+                transfer: &#me::standard::nep171::Nep171Transfer,
+                f: impl FnOnce(&mut Self) -> Result<(), E>,
+            ) -> Result<(), E> {
+                let hook_state = <Self as #me::standard::nep171::Nep171Hook::<_>>::before_nft_transfer(contract, transfer);
+                f(contract)?;
+                <Self as #me::standard::nep171::Nep171Hook::<_>>::after_nft_transfer(contract, transfer, hook_state);
+                Ok(())
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep171::Nep171ControllerInternal for #ident #ty #wher {
+            type Extension = #extension;
+
+            #root
+            #controller_hooks
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep171::Nep171 for #ident #ty #wher {
+            #[payable]
+            fn nft_transfer(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep171::TokenId,
+                approval_id: Option<u32>,
+                memo: Option<String>,
+            ) {
+                #near_sdk::assert_one_yocto();
+                let sender_id = #near_sdk::env::predecessor_account_id();
+
+                let transfer = #me::standard::nep171::Nep171Transfer {
+                    token_id,
+                    sender_id,
+                    receiver_id,
+                    approval_id,
+                    memo,
+                    msg: None,
+                };
+
+                #me::standard::nep171::Nep171Controller::transfer(self, &transfer)
+                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+            }
+
+            #[payable]
+            fn nft_transfer_call(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep171::TokenId,
+                approval_id: Option<u32>,
+                memo: Option<String>,
+                msg: String,
+            ) -> #near_sdk::PromiseOrValue<bool> {
+                #near_sdk::assert_one_yocto();
+                let sender_id = #near_sdk::env::predecessor_account_id();
+
+                let transfer = #me::standard::nep171::Nep171Transfer {
+                    token_id,
+                    sender_id,
+                    receiver_id,
+                    approval_id,
+                    memo,
+                    msg: Some(msg),
+                };
+
+                #me::standard::nep171::Nep171Controller::transfer_call(
+                    self,
+                    &transfer,
+                    #near_sdk::env::prepaid_gas(),
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()))
+                .into()
+            }
+
+            fn nft_token(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+            ) -> Option<#me::standard::nep171::Token> {
+                #me::standard::nep171::Nep171Controller::load_token(self, &token_id)
+            }
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep171::Nep171Resolver for #ident #ty #wher {
+            #[private]
+            fn nft_resolve_transfer(
+                &mut self,
+                previous_owner_id: #near_sdk::AccountId,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep171::TokenId,
+                approved_account_ids: Option<std::collections::HashMap<#near_sdk::AccountId, u32>>,
+            ) -> bool {
+                #me::standard::nep171::Nep171Controller::resolve_transfer(
+                    self,
+                    previous_owner_id,
+                    receiver_id,
+                    token_id,
+                    approved_account_ids,
+                )
+            }
+        }
+    })
+}
@@ -0,0 +1,129 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep178), supports(struct_named))]
+pub struct Nep178Meta {
+    pub storage_key: Option<Expr>,
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep178Meta) -> Result<TokenStream, darling::Error> {
+    let Nep178Meta {
+        storage_key,
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep178::Nep178ControllerInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep178::Nep178 for #ident #ty #wher {
+            #[payable]
+            fn nft_approve(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+                account_id: #near_sdk::AccountId,
+                msg: Option<String>,
+            ) -> Option<#near_sdk::Promise> {
+                #near_sdk::require!(
+                    #near_sdk::env::attached_deposit() > 0,
+                    "Requires attached deposit of at least 1 yoctoNEAR",
+                );
+
+                let owner_id = #near_sdk::env::predecessor_account_id();
+                let initial_storage_usage = #near_sdk::env::storage_usage();
+
+                let approval_id = #me::standard::nep178::Nep178Controller::approve(
+                    self,
+                    &token_id,
+                    &owner_id,
+                    &account_id,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+
+                #me::utils::apply_storage_fee_and_refund(initial_storage_usage, 0);
+
+                msg.map(|msg| {
+                    #me::standard::nep178::ext_nep178_receiver::ext(account_id)
+                        .nft_on_approve(token_id, owner_id, approval_id, msg)
+                })
+            }
+
+            #[payable]
+            fn nft_revoke(
+                &mut self,
+                token_id: #me::standard::nep171::TokenId,
+                account_id: #near_sdk::AccountId,
+            ) {
+                #near_sdk::assert_one_yocto();
+
+                let owner_id = #near_sdk::env::predecessor_account_id();
+                let initial_storage_usage = #near_sdk::env::storage_usage();
+
+                #me::standard::nep178::Nep178Controller::revoke(
+                    self,
+                    &token_id,
+                    &owner_id,
+                    &account_id,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+
+                #me::utils::refund_released_storage_fee(initial_storage_usage, owner_id);
+            }
+
+            #[payable]
+            fn nft_revoke_all(&mut self, token_id: #me::standard::nep171::TokenId) {
+                #near_sdk::assert_one_yocto();
+
+                let owner_id = #near_sdk::env::predecessor_account_id();
+                let initial_storage_usage = #near_sdk::env::storage_usage();
+
+                #me::standard::nep178::Nep178Controller::revoke_all(self, &token_id, &owner_id)
+                    .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+
+                #me::utils::refund_released_storage_fee(initial_storage_usage, owner_id);
+            }
+
+            fn nft_is_approved(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+                approved_account_id: #near_sdk::AccountId,
+                approval_id: Option<#me::standard::nep178::ApprovalId>,
+            ) -> bool {
+                match <Self as #me::standard::nep178::Nep178Controller>::approval_id_for(
+                    &token_id,
+                    &approved_account_id,
+                ) {
+                    Some(actual) => approval_id.map_or(true, |expected| expected == actual),
+                    None => false,
+                }
+            }
+        }
+    })
+}
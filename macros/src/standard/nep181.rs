@@ -0,0 +1,100 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep181), supports(struct_named))]
+pub struct Nep181Meta {
+    pub storage_key: Option<Expr>,
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep181Meta) -> Result<TokenStream, darling::Error> {
+    let Nep181Meta {
+        storage_key,
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep181::Nep181ControllerInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep181::Nep181 for #ident #ty #wher {
+            fn nft_total_supply(&self) -> #near_sdk::json_types::U128 {
+                u128::from(<Self as #me::standard::nep181::Nep181Controller>::total_supply()).into()
+            }
+
+            fn nft_tokens(
+                &self,
+                from_index: Option<#near_sdk::json_types::U128>,
+                limit: Option<u32>,
+            ) -> Vec<#me::standard::nep171::Token> {
+                let from_index = u64::try_from(from_index.map_or(0, |i| i.0)).unwrap_or(u64::MAX);
+
+                <Self as #me::standard::nep181::Nep181Controller>::tokens(
+                    from_index,
+                    limit.map(u64::from),
+                )
+                .into_iter()
+                .filter_map(|token_id| {
+                    #me::standard::nep171::Nep171Controller::load_token(self, &token_id)
+                })
+                .collect()
+            }
+
+            fn nft_supply_for_owner(
+                &self,
+                account_id: #near_sdk::AccountId,
+            ) -> #near_sdk::json_types::U128 {
+                u128::from(
+                    <Self as #me::standard::nep181::Nep181Controller>::supply_for_owner(&account_id),
+                )
+                .into()
+            }
+
+            fn nft_tokens_for_owner(
+                &self,
+                account_id: #near_sdk::AccountId,
+                from_index: Option<#near_sdk::json_types::U128>,
+                limit: Option<u32>,
+            ) -> Vec<#me::standard::nep171::Token> {
+                let from_index = u64::try_from(from_index.map_or(0, |i| i.0)).unwrap_or(u64::MAX);
+
+                <Self as #me::standard::nep181::Nep181Controller>::tokens_for_owner(
+                    &account_id,
+                    from_index,
+                    limit.map(u64::from),
+                )
+                .into_iter()
+                .filter_map(|token_id| {
+                    #me::standard::nep171::Nep171Controller::load_token(self, &token_id)
+                })
+                .collect()
+            }
+        }
+    })
+}
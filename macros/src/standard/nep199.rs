@@ -0,0 +1,104 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep199), supports(struct_named))]
+pub struct Nep199Meta {
+    pub storage_key: Option<Expr>,
+    pub max_len_payout: Option<Expr>,
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep199Meta) -> Result<TokenStream, darling::Error> {
+    let Nep199Meta {
+        storage_key,
+        max_len_payout,
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    let max_len_payout = max_len_payout.map(|max_len_payout| {
+        quote! {
+            fn max_len_payout() -> u32 {
+                #max_len_payout
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep199::Nep199ControllerInternal for #ident #ty #wher {
+            #root
+            #max_len_payout
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep199::Nep199 for #ident #ty #wher {
+            fn nft_payout(
+                &self,
+                token_id: #me::standard::nep171::TokenId,
+                balance: #near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> #me::standard::nep199::Payout {
+                <Self as #me::standard::nep199::Nep199Controller>::payout(
+                    &token_id,
+                    balance.into(),
+                    max_len_payout,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()))
+            }
+
+            #[payable]
+            fn nft_transfer_payout(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep171::TokenId,
+                approval_id: Option<u32>,
+                memo: Option<String>,
+                balance: #near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> #me::standard::nep199::Payout {
+                #near_sdk::assert_one_yocto();
+                let sender_id = #near_sdk::env::predecessor_account_id();
+
+                let transfer = #me::standard::nep171::Nep171Transfer {
+                    token_id,
+                    sender_id,
+                    receiver_id,
+                    approval_id,
+                    memo,
+                    msg: None,
+                };
+
+                #me::standard::nep199::Nep199Controller::transfer_payout(
+                    self,
+                    &transfer,
+                    balance.into(),
+                    max_len_payout,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()))
+            }
+        }
+    })
+}
@@ -0,0 +1,235 @@
+use std::ops::Not;
+
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep245), supports(struct_named))]
+pub struct Nep245Meta {
+    pub storage_key: Option<Expr>,
+    pub no_hooks: Flag,
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep245Meta) -> Result<TokenStream, darling::Error> {
+    let Nep245Meta {
+        storage_key,
+        no_hooks,
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    let controller_hooks = no_hooks.is_present().not().then(|| {
+        quote! {
+            fn hook_transfer<E>(
+                contract: &mut Self,
+                transfer: &#me::standard::nep245::Nep245Transfer,
+                f: impl FnOnce(&mut Self) -> Result<(), E>,
+            ) -> Result<(), E> {
+                let hook_state = <Self as #me::standard::nep245::Nep245Hook::<_>>::before_transfer(contract, transfer);
+                f(contract)?;
+                <Self as #me::standard::nep245::Nep245Hook::<_>>::after_transfer(contract, transfer, hook_state);
+                Ok(())
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep245::Nep245ControllerInternal for #ident #ty #wher {
+            #root
+            #controller_hooks
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep245::Nep245 for #ident #ty #wher {
+            #[payable]
+            fn mt_transfer(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep245::TokenId,
+                amount: #near_sdk::json_types::U128,
+                approval: Option<(#near_sdk::AccountId, u64)>,
+                memo: Option<String>,
+            ) {
+                <Self as #me::standard::nep245::Nep245>::mt_batch_transfer(
+                    self,
+                    receiver_id,
+                    vec![token_id],
+                    vec![amount],
+                    approval.map(|approval| vec![Some(approval)]),
+                    memo,
+                )
+            }
+
+            #[payable]
+            fn mt_batch_transfer(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_ids: Vec<#me::standard::nep245::TokenId>,
+                amounts: Vec<#near_sdk::json_types::U128>,
+                approvals: Option<Vec<Option<(#near_sdk::AccountId, u64)>>>,
+                memo: Option<String>,
+            ) {
+                #near_sdk::assert_one_yocto();
+                #near_sdk::require!(approvals.is_none(), "Approvals are not supported");
+                let sender_id = #near_sdk::env::predecessor_account_id();
+                let amounts: Vec<u128> = amounts.into_iter().map(|amount| amount.0).collect();
+
+                #me::standard::nep245::Nep245Controller::transfer(
+                    self,
+                    sender_id,
+                    receiver_id,
+                    token_ids,
+                    amounts,
+                    memo,
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()));
+            }
+
+            #[payable]
+            fn mt_transfer_call(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep245::TokenId,
+                amount: #near_sdk::json_types::U128,
+                approval: Option<(#near_sdk::AccountId, u64)>,
+                memo: Option<String>,
+                msg: String,
+            ) -> #near_sdk::Promise {
+                <Self as #me::standard::nep245::Nep245>::mt_batch_transfer_call(
+                    self,
+                    receiver_id,
+                    vec![token_id],
+                    vec![amount],
+                    approval.map(|approval| vec![Some(approval)]),
+                    memo,
+                    msg,
+                )
+            }
+
+            #[payable]
+            fn mt_batch_transfer_call(
+                &mut self,
+                receiver_id: #near_sdk::AccountId,
+                token_ids: Vec<#me::standard::nep245::TokenId>,
+                amounts: Vec<#near_sdk::json_types::U128>,
+                approvals: Option<Vec<Option<(#near_sdk::AccountId, u64)>>>,
+                memo: Option<String>,
+                msg: String,
+            ) -> #near_sdk::Promise {
+                #near_sdk::assert_one_yocto();
+                #near_sdk::require!(approvals.is_none(), "Approvals are not supported");
+                let sender_id = #near_sdk::env::predecessor_account_id();
+                let amounts: Vec<u128> = amounts.into_iter().map(|amount| amount.0).collect();
+
+                #me::standard::nep245::Nep245Controller::transfer_call(
+                    self,
+                    sender_id,
+                    receiver_id,
+                    token_ids,
+                    amounts,
+                    memo,
+                    msg,
+                    #near_sdk::env::prepaid_gas(),
+                )
+                .unwrap_or_else(|e| #near_sdk::env::panic_str(&e.to_string()))
+            }
+
+            fn mt_balance_of(
+                &self,
+                account_id: #near_sdk::AccountId,
+                token_id: #me::standard::nep245::TokenId,
+            ) -> #near_sdk::json_types::U128 {
+                <Self as #me::standard::nep245::Nep245Controller>::balance_of(&account_id, &token_id)
+                    .into()
+            }
+
+            fn mt_batch_balance_of(
+                &self,
+                account_id: #near_sdk::AccountId,
+                token_ids: Vec<#me::standard::nep245::TokenId>,
+            ) -> Vec<#near_sdk::json_types::U128> {
+                token_ids
+                    .iter()
+                    .map(|token_id| {
+                        <Self as #me::standard::nep245::Nep245Controller>::balance_of(
+                            &account_id,
+                            token_id,
+                        )
+                        .into()
+                    })
+                    .collect()
+            }
+
+            fn mt_supply(
+                &self,
+                token_id: #me::standard::nep245::TokenId,
+            ) -> Option<#near_sdk::json_types::U128> {
+                <Self as #me::standard::nep245::Nep245ControllerInternal>::slot_supply(&token_id)
+                    .read()
+                    .map(Into::into)
+            }
+
+            fn mt_batch_supply(
+                &self,
+                token_ids: Vec<#me::standard::nep245::TokenId>,
+            ) -> Vec<Option<#near_sdk::json_types::U128>> {
+                token_ids
+                    .iter()
+                    .map(|token_id| {
+                        <Self as #me::standard::nep245::Nep245ControllerInternal>::slot_supply(
+                            token_id,
+                        )
+                        .read()
+                        .map(Into::into)
+                    })
+                    .collect()
+            }
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep245::Nep245Resolver for #ident #ty #wher {
+            #[private]
+            fn mt_resolve_transfer(
+                &mut self,
+                previous_owner_ids: Vec<#near_sdk::AccountId>,
+                receiver_id: #near_sdk::AccountId,
+                token_ids: Vec<#me::standard::nep245::TokenId>,
+                amounts: Vec<#near_sdk::json_types::U128>,
+            ) -> Vec<#near_sdk::json_types::U128> {
+                #me::standard::nep245::Nep245Controller::resolve_transfer(
+                    self,
+                    previous_owner_ids,
+                    receiver_id,
+                    token_ids,
+                    amounts.into_iter().map(|amount| amount.0).collect(),
+                )
+                .into_iter()
+                .map(Into::into)
+                .collect()
+            }
+        }
+    })
+}
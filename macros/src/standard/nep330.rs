@@ -0,0 +1,78 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(nep330), supports(struct_named))]
+pub struct Nep330Meta {
+    pub version: Option<String>,
+    pub link: Option<String>,
+    pub storage_key: Option<Expr>,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: Nep330Meta) -> Result<TokenStream, darling::Error> {
+    let Nep330Meta {
+        version,
+        link,
+        storage_key,
+
+        generics,
+        ident,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    // Environment variables are read when the contract crate is compiled,
+    // since that is where the expanded code is compiled.
+    let version = version.map_or_else(
+        || quote! { option_env!("CARGO_PKG_VERSION") },
+        |version| quote! { Some(#version) },
+    );
+    let link = link.map_or_else(
+        || quote! { option_env!("CARGO_PKG_REPOSITORY") },
+        |link| quote! { Some(#link) },
+    );
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::root(#storage_key)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::standard::nep330::Nep330ControllerInternal for #ident #ty #wher {
+            fn source_metadata() -> #me::standard::nep330::ContractSourceMetadata {
+                let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+
+                #me::standard::nep330::ContractSourceMetadata {
+                    version: #version.and_then(non_empty),
+                    link: #link.and_then(non_empty),
+                }
+            }
+
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::standard::nep330::Nep330 for #ident #ty #wher {
+            fn contract_source_metadata(&self) -> #me::standard::nep330::ContractSourceMetadata {
+                <Self as #me::standard::nep330::Nep330Controller>::contract_source_metadata()
+            }
+        }
+    })
+}
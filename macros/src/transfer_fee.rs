@@ -0,0 +1,79 @@
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(transfer_fee), supports(struct_named))]
+pub struct TransferFeeMeta {
+    pub storage_key: Option<Expr>,
+    pub nep141_hook: Flag,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: TransferFeeMeta) -> Result<TokenStream, darling::Error> {
+    let TransferFeeMeta {
+        storage_key,
+        nep141_hook,
+        ident,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::new(#storage_key)
+            }
+        }
+    });
+
+    let nep141_hook = nep141_hook.is_present().then(|| {
+        quote! {
+            impl #imp #me::standard::nep141::Nep141Hook for #ident #ty #wher {
+                fn before_transfer(&mut self, transfer: &#me::standard::nep141::Nep141Transfer) {
+                    <Self as #me::transfer_fee::TransferFee>::before_nep141_transfer(transfer);
+                }
+
+                fn after_transfer(
+                    &mut self,
+                    transfer: &#me::standard::nep141::Nep141Transfer,
+                    _state: (),
+                ) {
+                    #me::transfer_fee::TransferFee::charge_transfer_fee(self, transfer);
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::transfer_fee::TransferFeeInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::transfer_fee::TransferFeeExternal for #ident #ty #wher {
+            fn fee_config(&self) -> Option<#me::transfer_fee::TransferFeeConfig> {
+                <Self as #me::transfer_fee::TransferFee>::transfer_fee()
+            }
+
+            fn fee_is_exempt(&self, account_id: #near_sdk::AccountId) -> bool {
+                <Self as #me::transfer_fee::TransferFee>::is_transfer_fee_exempt(&account_id)
+            }
+        }
+
+        #nep141_hook
+    })
+}
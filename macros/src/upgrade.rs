@@ -1,4 +1,4 @@
-use darling::{FromDeriveInput, FromMeta};
+use darling::{util::Flag, FromDeriveInput, FromMeta};
 use once_cell::sync::OnceCell;
 use proc_macro2::TokenStream;
 use quote::quote;
@@ -66,6 +66,7 @@ pub struct UpgradeMeta {
     pub migrate_method_name: Option<String>,
     pub migrate_method_args: Option<Expr>,
     pub migrate_minimum_gas: Option<Expr>,
+    pub nep330: Flag,
 
     pub generics: syn::Generics,
     pub ident: syn::Ident,
@@ -84,6 +85,7 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         migrate_method_name,
         migrate_method_args,
         migrate_minimum_gas,
+        nep330,
 
         ident,
         generics,
@@ -129,6 +131,12 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         }
     });
 
+    let record_upgrade = nep330.is_present().then(|| {
+        quote! {
+            #me::standard::nep330::Nep330Controller::record_upgrade(self);
+        }
+    });
+
     let (serializer_attribute, code_type, code_conversion) =
         match serializer.unwrap_or(Serializer::JsonBase64) {
             Serializer::Borsh => (
@@ -148,6 +156,7 @@ pub fn expand(meta: UpgradeMeta) -> Result<TokenStream, darling::Error> {
         impl #imp #ident #ty #wher {
             pub fn upgrade(&mut self, #serializer_attribute code: #code_type) {
                 #me::upgrade::serialized::UpgradeHook::on_upgrade(self);
+                #record_upgrade
                 #code_conversion
                 #me::upgrade::serialized::upgrade(
                     code,
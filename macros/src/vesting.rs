@@ -0,0 +1,96 @@
+use darling::{util::Flag, FromDeriveInput};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(vesting), supports(struct_named))]
+pub struct VestingMeta {
+    pub storage_key: Option<Expr>,
+    pub nep141_hook: Flag,
+
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: VestingMeta) -> Result<TokenStream, darling::Error> {
+    let VestingMeta {
+        storage_key,
+        nep141_hook,
+        ident,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    let root = storage_key.map(|storage_key| {
+        quote! {
+            fn root() -> #me::slot::Slot<()> {
+                #me::slot::Slot::new(#storage_key)
+            }
+        }
+    });
+
+    let nep141_hook = nep141_hook.is_present().then(|| {
+        quote! {
+            impl #imp #me::standard::nep141::Nep141Hook for #ident #ty #wher {
+                fn before_transfer(&mut self, transfer: &#me::standard::nep141::Nep141Transfer) {
+                    <Self as #me::vesting::Vesting>::before_nep141_transfer(transfer);
+                }
+
+                fn before_burn(&mut self, burn: &#me::standard::nep141::Nep141Burn) {
+                    <Self as #me::vesting::Vesting>::before_nep141_burn(burn);
+                }
+
+                fn refundable_amount(&self, refund: &#me::standard::nep141::Nep141Transfer) -> u128 {
+                    <Self as #me::vesting::Vesting>::refundable_nep141_amount(refund)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #imp #me::vesting::VestingInternal for #ident #ty #wher {
+            #root
+        }
+
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::vesting::VestingExternal for #ident #ty #wher {
+            fn vest_schedule_of(
+                &self,
+                account_id: #near_sdk::AccountId,
+            ) -> Option<#me::vesting::VestingSchedule> {
+                <Self as #me::vesting::Vesting>::vesting_schedule_of(&account_id)
+            }
+
+            fn vest_claimable_of(
+                &self,
+                account_id: #near_sdk::AccountId,
+            ) -> #near_sdk::json_types::U128 {
+                <Self as #me::vesting::Vesting>::claimable_of(&account_id).into()
+            }
+
+            fn vest_locked_of(
+                &self,
+                account_id: #near_sdk::AccountId,
+            ) -> #near_sdk::json_types::U128 {
+                <Self as #me::vesting::Vesting>::locked_of(&account_id).into()
+            }
+
+            fn vest_claim(&mut self) -> #near_sdk::json_types::U128 {
+                #me::vesting::Vesting::claim(self, #near_sdk::env::predecessor_account_id()).into()
+            }
+        }
+
+        #nep141_hook
+    })
+}
@@ -0,0 +1,46 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(wrapped_near), supports(struct_named))]
+pub struct WrappedNearMeta {
+    pub generics: syn::Generics,
+    pub ident: syn::Ident,
+
+    // crates
+    #[darling(rename = "crate", default = "crate::default_crate_name")]
+    pub me: syn::Path,
+    #[darling(default = "crate::default_near_sdk")]
+    pub near_sdk: syn::Path,
+}
+
+pub fn expand(meta: WrappedNearMeta) -> Result<TokenStream, darling::Error> {
+    let WrappedNearMeta {
+        ident,
+        generics,
+
+        me,
+        near_sdk,
+    } = meta;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[#near_sdk::near_bindgen]
+        impl #imp #me::wrapped_near::WrappedNearExternal for #ident #ty #wher {
+            #[payable]
+            fn near_deposit(&mut self) {
+                #me::wrapped_near::WrappedNear::deposit_near(self);
+            }
+
+            #[payable]
+            fn near_withdraw(
+                &mut self,
+                amount: #near_sdk::json_types::U128,
+            ) -> #near_sdk::Promise {
+                #me::wrapped_near::WrappedNear::withdraw_near(self, amount.into())
+            }
+        }
+    })
+}
@@ -1,21 +1,83 @@
 //! Queue and approve actions
+//!
+//! The [`ApprovalManager`] emits an [`ApprovalEvent`] when a request is
+//! created, approved, executed, or removed, or when an approval is revoked.
+//!
+//! Pending requests are tracked in an index that can be enumerated using
+//! [`ApprovalManager::list_requests`]. Requests created before the index was
+//! introduced are not listed until they are added to it using
+//! [`ApprovalManager::index_requests`], e.g. in a migration.
 
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     env, require, AccountId, BorshStorageKey,
 };
+use near_sdk_contract_tools_macros::event;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{slot::Slot, DefaultStorageKey};
+use crate::{slot::Slot, standard::nep297::Event, DefaultStorageKey};
 
 /// Error message emitted when the component is used before it is initialized
 pub const NOT_INITIALIZED: &str = "init must be called before use";
 /// Error message emitted when the init function is called multiple times
 pub const ALREADY_INITIALIZED: &str = "init can only be called once";
+/// Number of requests listed by the `multisig_list_requests` view of the
+/// multisig derive macros if no limit is given
+pub const DEFAULT_LIST_REQUESTS_LIMIT: u32 = 10;
+/// Maximum number of requests listed by the `multisig_list_requests` view of
+/// the multisig derive macros
+pub const MAX_LIST_REQUESTS_LIMIT: u32 = 100;
 
 pub mod native_transaction_action;
 pub mod simple_multisig;
+pub mod weighted_multisig;
+
+/// Events emitted over the lifecycle of an action request
+#[event(
+    standard = "x-appr",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum ApprovalEvent {
+    /// Emitted when an action request is created
+    Create {
+        /// ID of the new request
+        request_id: u32,
+        /// Account that created the request
+        account_id: AccountId,
+    },
+    /// Emitted when an action request is approved by an account
+    Approve {
+        /// ID of the approved request
+        request_id: u32,
+        /// Account that approved the request
+        account_id: AccountId,
+    },
+    /// Emitted when an account revokes its approval of an action request
+    Revoke {
+        /// ID of the request
+        request_id: u32,
+        /// Account that revoked its approval
+        account_id: AccountId,
+    },
+    /// Emitted when an action request is executed
+    Execute {
+        /// ID of the executed request
+        request_id: u32,
+        /// Account that executed the request
+        account_id: AccountId,
+    },
+    /// Emitted when an action request is removed without being executed
+    Remove {
+        /// ID of the removed request
+        request_id: u32,
+        /// Account that removed the request
+        account_id: AccountId,
+    },
+}
 
 /// Actions can be executed after they are approved
 pub trait Action<Cont: ?Sized> {
@@ -30,6 +92,8 @@ pub trait Action<Cont: ?Sized> {
 pub trait ApprovalConfiguration<A, S> {
     /// Errors when approving a request
     type ApprovalError;
+    /// Errors when revoking an approval
+    type RevocationError;
     /// Errors when removing a request
     type RemovalError;
     /// Errors when authorizing an account
@@ -59,6 +123,14 @@ pub trait ApprovalConfiguration<A, S> {
         account_id: AccountId,
         action_request: &mut ActionRequest<A, S>,
     ) -> Result<(), Self::ApprovalError>;
+
+    /// Modify action_request.approval_state in-place to withdraw the
+    /// approval of the account
+    fn try_revoke_with_authorized_account(
+        &self,
+        account_id: AccountId,
+        action_request: &mut ActionRequest<A, S>,
+    ) -> Result<(), Self::RevocationError>;
 }
 
 /// An action request is composed of an action that will be executed when the
@@ -77,6 +149,9 @@ enum ApprovalStorageKey {
     NextRequestId,
     Config,
     Request(u32),
+    RequestCount,
+    RequestIdAt(u32),
+    RequestIndex(u32),
 }
 
 /// The account is ineligile to perform an action for some reason
@@ -95,6 +170,17 @@ pub enum ApprovalError<AuthErr, AppErr> {
     ApprovalError(AppErr),
 }
 
+/// Top-level errors that may occur when attempting to revoke an approval
+#[derive(Error, Clone, Debug)]
+pub enum RevocationError<AuthErr, RevErr> {
+    /// The account is not allowed to act on requests
+    #[error(transparent)]
+    UnauthorizedAccount(#[from] UnauthorizedAccountError<AuthErr>),
+    /// The revocation function encountered another error
+    #[error("Revocation error: {0}")]
+    RevocationError(RevErr),
+}
+
 /// Errors that may occur when trying to execute a request
 #[derive(Error, Clone, Debug)]
 pub enum ExecutionError<AuthErr, ExecErr> {
@@ -152,6 +238,69 @@ where
     fn slot_request(request_id: u32) -> Slot<ActionRequest<A, S>> {
         Self::root().field(ApprovalStorageKey::Request(request_id))
     }
+
+    /// Number of pending action requests
+    fn slot_request_count() -> Slot<u32> {
+        Self::root().field(ApprovalStorageKey::RequestCount)
+    }
+
+    /// ID of the pending action request at the given position in the index
+    fn slot_request_id_at(index: u32) -> Slot<u32> {
+        Self::root().field(ApprovalStorageKey::RequestIdAt(index))
+    }
+
+    /// Position of the given pending action request in the index
+    fn slot_request_index(request_id: u32) -> Slot<u32> {
+        Self::root().field(ApprovalStorageKey::RequestIndex(request_id))
+    }
+}
+
+/// Appends a request ID to the index of pending requests.
+fn add_to_index<T, A, S, C>(request_id: u32)
+where
+    T: ApprovalManagerInternal<A, S, C> + ?Sized,
+    A: Action<T> + BorshSerialize + BorshDeserialize,
+    S: BorshSerialize + BorshDeserialize + Serialize,
+    C: ApprovalConfiguration<A, S> + BorshDeserialize + BorshSerialize,
+{
+    let mut count_slot = T::slot_request_count();
+    let count = count_slot.read().unwrap_or(0);
+
+    T::slot_request_id_at(count).write(&request_id);
+    T::slot_request_index(request_id).write(&count);
+    count_slot.write(&(count + 1));
+}
+
+/// Removes a request ID from the index of pending requests by swapping the
+/// last ID into its position.
+fn remove_from_index<T, A, S, C>(request_id: u32)
+where
+    T: ApprovalManagerInternal<A, S, C> + ?Sized,
+    A: Action<T> + BorshSerialize + BorshDeserialize,
+    S: BorshSerialize + BorshDeserialize + Serialize,
+    C: ApprovalConfiguration<A, S> + BorshDeserialize + BorshSerialize,
+{
+    let index = match T::slot_request_index(request_id).take() {
+        Some(index) => index,
+        None => return,
+    };
+
+    let mut count_slot = T::slot_request_count();
+    let last_index = count_slot.read().unwrap_or(1) - 1;
+    let last = T::slot_request_id_at(last_index).take();
+
+    if index != last_index {
+        if let Some(last) = last {
+            T::slot_request_id_at(index).write(&last);
+            T::slot_request_index(last).write(&index);
+        }
+    }
+
+    if last_index == 0 {
+        count_slot.remove();
+    } else {
+        count_slot.write(&last_index);
+    }
 }
 
 /// Collection of action requests that manages their approval state and
@@ -169,6 +318,23 @@ where
     /// Get a request by ID
     fn get_request(request_id: u32) -> Option<ActionRequest<A, S>>;
 
+    /// Lists up to `limit` pending requests with their IDs, starting at
+    /// position `from_index` in the index of pending requests. Removing a
+    /// request moves the last request into its position, so the order is
+    /// not guaranteed to follow request IDs.
+    fn list_requests(from_index: u32, limit: u32) -> Vec<(u32, ActionRequest<A, S>)>;
+
+    /// Number of pending requests
+    fn count_requests() -> u32;
+
+    /// Adds the pending requests with IDs in
+    /// `from_request_id..from_request_id + limit` that are missing from the
+    /// index of pending requests to it, returning the number of requests
+    /// added. Requests created before the index was introduced are missing
+    /// from it, so contracts upgrading from such a version should call this
+    /// in a migration (in batches, if there are many requests).
+    fn index_requests(&mut self, from_request_id: u32, limit: u32) -> u32;
+
     /// Must be called before using the Approval construct. Can only be called
     /// once.
     fn init(config: C);
@@ -198,6 +364,14 @@ where
         request_id: u32,
     ) -> Result<(), ApprovalError<C::AuthorizationError, C::ApprovalError>>;
 
+    /// Tries to withdraw the predecessor's approval of the action request
+    /// designated by the given request ID. Panics if the request ID does not
+    /// exist.
+    fn revoke_approval(
+        &mut self,
+        request_id: u32,
+    ) -> Result<(), RevocationError<C::AuthorizationError, C::RevocationError>>;
+
     /// Tries to remove the action request indicated by request_id.
     fn remove_request(
         &mut self,
@@ -221,6 +395,40 @@ where
         Self::slot_request(request_id).read()
     }
 
+    fn list_requests(from_index: u32, limit: u32) -> Vec<(u32, ActionRequest<A, S>)> {
+        let end = from_index.saturating_add(limit).min(Self::count_requests());
+
+        (from_index..end)
+            .filter_map(|index| Self::slot_request_id_at(index).read())
+            .filter_map(|request_id| {
+                Self::get_request(request_id).map(|request| (request_id, request))
+            })
+            .collect()
+    }
+
+    fn count_requests() -> u32 {
+        Self::slot_request_count().read().unwrap_or(0)
+    }
+
+    fn index_requests(&mut self, from_request_id: u32, limit: u32) -> u32 {
+        let end = from_request_id
+            .saturating_add(limit)
+            .min(Self::slot_next_request_id().read().unwrap_or(0));
+
+        let mut added = 0;
+
+        for request_id in from_request_id..end {
+            if Self::slot_request(request_id).exists()
+                && !Self::slot_request_index(request_id).exists()
+            {
+                add_to_index::<Self, A, S, C>(request_id);
+                added += 1;
+            }
+        }
+
+        added
+    }
+
     fn init(config: C) {
         require!(
             Self::slot_config().swap(&config).is_none(),
@@ -245,10 +453,17 @@ where
 
         config
             .is_account_authorized(&predecessor, &request)
-            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
 
         Self::slot_next_request_id().write(&(request_id + 1));
         Self::slot_request(request_id).write(&request);
+        add_to_index::<Self, A, S, C>(request_id);
+
+        ApprovalEvent::Create {
+            request_id,
+            account_id: predecessor,
+        }
+        .emit();
 
         Ok(request_id)
     }
@@ -269,10 +484,17 @@ where
 
         config
             .is_account_authorized(&predecessor, &request)
-            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
 
         let result = request.action.execute(self);
         request_slot.remove();
+        remove_from_index::<Self, A, S, C>(request_id);
+
+        ApprovalEvent::Execute {
+            request_id,
+            account_id: predecessor,
+        }
+        .emit();
 
         Ok(result)
     }
@@ -299,11 +521,46 @@ where
             .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
 
         config
-            .try_approve_with_authorized_account(predecessor, &mut request)
+            .try_approve_with_authorized_account(predecessor.clone(), &mut request)
             .map_err(ApprovalError::ApprovalError)?;
 
         request_slot.write(&request);
 
+        ApprovalEvent::Approve {
+            request_id,
+            account_id: predecessor,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn revoke_approval(
+        &mut self,
+        request_id: u32,
+    ) -> Result<(), RevocationError<C::AuthorizationError, C::RevocationError>> {
+        let mut request_slot = Self::slot_request(request_id);
+        let mut request = request_slot.read().unwrap();
+
+        let predecessor = env::predecessor_account_id();
+        let config = Self::get_config();
+
+        config
+            .is_account_authorized(&predecessor, &request)
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
+
+        config
+            .try_revoke_with_authorized_account(predecessor.clone(), &mut request)
+            .map_err(RevocationError::RevocationError)?;
+
+        request_slot.write(&request);
+
+        ApprovalEvent::Revoke {
+            request_id,
+            account_id: predecessor,
+        }
+        .emit();
+
         Ok(())
     }
 
@@ -323,9 +580,16 @@ where
 
         config
             .is_account_authorized(&predecessor, &request)
-            .map_err(|e| UnauthorizedAccountError(predecessor, e))?;
+            .map_err(|e| UnauthorizedAccountError(predecessor.clone(), e))?;
 
         request_slot.remove();
+        remove_from_index::<Self, A, S, C>(request_id);
+
+        ApprovalEvent::Remove {
+            request_id,
+            account_id: predecessor,
+        }
+        .emit();
 
         Ok(())
     }
@@ -336,7 +600,7 @@ mod tests {
     use near_sdk::{
         borsh::{self, BorshDeserialize, BorshSerialize},
         near_bindgen,
-        test_utils::VMContextBuilder,
+        test_utils::{get_logs, VMContextBuilder},
         testing_env, AccountId, BorshStorageKey,
     };
     use near_sdk_contract_tools_macros::Rbac;
@@ -411,6 +675,7 @@ mod tests {
 
     impl ApprovalConfiguration<MyAction, MultisigApprovalState> for MultisigConfig {
         type ApprovalError = String;
+        type RevocationError = String;
         type RemovalError = ();
         type AuthorizationError = String;
         type ExecutionEligibilityError = String;
@@ -471,6 +736,23 @@ mod tests {
 
             Ok(())
         }
+
+        fn try_revoke_with_authorized_account(
+            &self,
+            account_id: AccountId,
+            action_request: &mut ActionRequest<MyAction, MultisigApprovalState>,
+        ) -> Result<(), Self::RevocationError> {
+            let approved_by = &mut action_request.approval_state.approved_by;
+            let len = approved_by.len();
+
+            approved_by.retain(|a| a != &account_id);
+
+            if approved_by.len() == len {
+                Err("Not approved by account".to_string())
+            } else {
+                Ok(())
+            }
+        }
     }
 
     fn predecessor(account_id: &AccountId) {
@@ -606,4 +888,202 @@ mod tests {
 
         assert!(Contract::is_approved_for_execution(request_id).is_ok());
     }
+
+    #[test]
+    fn list_requests() {
+        let alice: AccountId = "alice".parse().unwrap();
+
+        let mut contract = Contract::new(1);
+
+        contract.add_role(alice.clone(), &Role::Multisig);
+
+        predecessor(&alice);
+
+        assert_eq!(Contract::count_requests(), 0);
+        assert!(Contract::list_requests(0, 10).is_empty());
+
+        let ids = [MyAction::SayHello, MyAction::SayGoodbye, MyAction::SayHello]
+            .map(|action| contract.create_request(action, Default::default()).unwrap());
+
+        assert_eq!(Contract::count_requests(), 3);
+
+        let list = |from_index, limit| {
+            Contract::list_requests(from_index, limit)
+                .into_iter()
+                .map(|(id, request)| (id, request.action))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            list(0, 10),
+            vec![
+                (ids[0], MyAction::SayHello),
+                (ids[1], MyAction::SayGoodbye),
+                (ids[2], MyAction::SayHello),
+            ],
+        );
+        assert_eq!(list(1, 1), vec![(ids[1], MyAction::SayGoodbye)]);
+        assert!(list(3, 10).is_empty());
+
+        contract.approve_request(ids[0]).unwrap();
+        contract.execute_request(ids[0]).unwrap();
+
+        assert_eq!(Contract::count_requests(), 2);
+        assert_eq!(
+            list(0, 10),
+            vec![(ids[2], MyAction::SayHello), (ids[1], MyAction::SayGoodbye)],
+        );
+
+        contract.remove_request(ids[1]).unwrap();
+
+        assert_eq!(Contract::count_requests(), 1);
+        assert_eq!(list(0, 10), vec![(ids[2], MyAction::SayHello)]);
+
+        contract.remove_request(ids[2]).unwrap();
+
+        assert_eq!(Contract::count_requests(), 0);
+        assert!(list(0, 10).is_empty());
+
+        let id = contract
+            .create_request(MyAction::SayGoodbye, Default::default())
+            .unwrap();
+
+        assert_eq!(list(0, 10), vec![(id, MyAction::SayGoodbye)]);
+    }
+
+    #[test]
+    fn index_requests() {
+        let alice: AccountId = "alice".parse().unwrap();
+
+        let mut contract = Contract::new(1);
+
+        contract.add_role(alice.clone(), &Role::Multisig);
+
+        predecessor(&alice);
+
+        let ids = [MyAction::SayHello, MyAction::SayGoodbye, MyAction::SayHello]
+            .map(|action| contract.create_request(action, Default::default()).unwrap());
+
+        contract.remove_request(ids[1]).unwrap();
+
+        // Simulate requests created before the index was introduced
+        fn clear_index<T>(request_ids: &[u32])
+        where
+            T: ApprovalManagerInternal<MyAction, MultisigApprovalState, MultisigConfig>,
+            MyAction: Action<T>,
+        {
+            for (index, request_id) in request_ids.iter().enumerate() {
+                T::slot_request_id_at(index as u32).remove();
+                T::slot_request_index(*request_id).remove();
+            }
+            T::slot_request_count().remove();
+        }
+
+        clear_index::<Contract>(&[ids[0], ids[2]]);
+
+        assert_eq!(Contract::count_requests(), 0);
+
+        assert_eq!(contract.index_requests(ids[0], 2), 1);
+        assert_eq!(contract.index_requests(ids[0], 100), 1);
+        // Indexed requests are not added again
+        assert_eq!(contract.index_requests(0, u32::MAX), 0);
+
+        assert_eq!(Contract::count_requests(), 2);
+        assert_eq!(
+            Contract::list_requests(0, 10)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            vec![ids[0], ids[2]],
+        );
+    }
+
+    #[test]
+    fn events() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob_acct".parse().unwrap();
+
+        let mut contract = Contract::new(1);
+
+        contract.add_role(alice.clone(), &Role::Multisig);
+        contract.add_role(bob.clone(), &Role::Multisig);
+
+        predecessor(&alice);
+        let first = contract
+            .create_request(MyAction::SayHello, Default::default())
+            .unwrap();
+        let second = contract
+            .create_request(MyAction::SayGoodbye, Default::default())
+            .unwrap();
+
+        predecessor(&bob);
+        contract.approve_request(first).unwrap();
+        contract.execute_request(first).unwrap();
+        contract.remove_request(second).unwrap();
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"x-appr","version":"1.0.0","event":"approve","data":{"request_id":0,"account_id":"bob_acct"}}"#,
+                r#"EVENT_JSON:{"standard":"x-appr","version":"1.0.0","event":"execute","data":{"request_id":0,"account_id":"bob_acct"}}"#,
+                r#"EVENT_JSON:{"standard":"x-appr","version":"1.0.0","event":"remove","data":{"request_id":1,"account_id":"bob_acct"}}"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn create_event() {
+        let alice: AccountId = "alice".parse().unwrap();
+
+        let mut contract = Contract::new(1);
+
+        contract.add_role(alice.clone(), &Role::Multisig);
+
+        predecessor(&alice);
+        contract
+            .create_request(MyAction::SayHello, Default::default())
+            .unwrap();
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"x-appr","version":"1.0.0","event":"create","data":{"request_id":0,"account_id":"alice"}}"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn revoke_approval() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob_acct".parse().unwrap();
+
+        let mut contract = Contract::new(2);
+
+        contract.add_role(alice.clone(), &Role::Multisig);
+        contract.add_role(bob.clone(), &Role::Multisig);
+
+        predecessor(&alice);
+        let request_id = contract
+            .create_request(MyAction::SayHello, Default::default())
+            .unwrap();
+        contract.approve_request(request_id).unwrap();
+
+        predecessor(&bob);
+        contract.approve_request(request_id).unwrap();
+
+        assert!(Contract::is_approved_for_execution(request_id).is_ok());
+
+        contract.revoke_approval(request_id).unwrap();
+
+        assert!(Contract::is_approved_for_execution(request_id).is_err());
+        assert!(contract.revoke_approval(request_id).is_err());
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"x-appr","version":"1.0.0","event":"approve","data":{"request_id":0,"account_id":"bob_acct"}}"#,
+                r#"EVENT_JSON:{"standard":"x-appr","version":"1.0.0","event":"revoke","data":{"request_id":0,"account_id":"bob_acct"}}"#,
+            ],
+        );
+    }
 }
@@ -1,5 +1,10 @@
 //! Simple multi-signature wallet component. Generic over approvable actions.
 //! Use with NativeTransactionAction for multisig over native transactions.
+//!
+//! Wrap the [`Configuration`] in [`Timelocked`] (with
+//! [`TimelockedApprovalState`]) to delay the execution of requests after they
+//! reach the approval threshold, so that users can react to malicious
+//! proposals.
 
 use std::marker::PhantomData;
 
@@ -57,6 +62,59 @@ impl<Au: AccountAuthorizer> Configuration<Au> {
                 < self.validity_period_nanoseconds
         }
     }
+
+    fn check_execution_eligibility(
+        &self,
+        approval_state: &ApprovalState,
+    ) -> Result<(), ExecutionEligibilityError> {
+        if !self.is_within_validity_period(approval_state) {
+            return Err(RequestExpiredError.into());
+        }
+
+        let current = approval_state.approved_by.len();
+        let required = self.threshold as usize;
+
+        if current < required {
+            return Err(ExecutionEligibilityError::InsufficientApprovals { current, required });
+        }
+
+        Ok(())
+    }
+
+    fn approve(
+        &self,
+        account_id: AccountId,
+        approval_state: &mut ApprovalState,
+    ) -> Result<(), ApprovalError> {
+        if !self.is_within_validity_period(approval_state) {
+            return Err(RequestExpiredError.into());
+        }
+
+        if approval_state.approved_by.contains(&account_id) {
+            return Err(ApprovalError::AlreadyApprovedByAccount);
+        }
+
+        approval_state.approved_by.push(account_id);
+
+        Ok(())
+    }
+
+    fn revoke(
+        &self,
+        account_id: AccountId,
+        approval_state: &mut ApprovalState,
+    ) -> Result<(), RevocationError> {
+        let approved_by = &mut approval_state.approved_by;
+
+        let index = approved_by
+            .iter()
+            .position(|a| a == &account_id)
+            .ok_or(RevocationError::NotApprovedByAccount)?;
+
+        approved_by.remove(index);
+
+        Ok(())
+    }
 }
 
 /// Approval state for simple multisig
@@ -89,6 +147,15 @@ impl ApprovalState {
 #[error("Validity period exceeded")]
 pub struct RequestExpiredError;
 
+/// A request that has reached the approval threshold may not be executed
+/// before its timelock has elapsed
+#[derive(Error, Clone, Debug)]
+#[error("Timelock not elapsed: request may be executed at {executable_at_nanoseconds}")]
+pub struct TimelockNotElapsedError {
+    /// Network timestamp after which the request may be executed
+    pub executable_at_nanoseconds: u64,
+}
+
 /// Why might a simple multisig approval attempt fail?
 #[derive(Error, Clone, Debug)]
 pub enum ApprovalError {
@@ -100,6 +167,14 @@ pub enum ApprovalError {
     RequestExpired(#[from] RequestExpiredError),
 }
 
+/// Why might a simple multisig revocation attempt fail?
+#[derive(Error, Clone, Debug)]
+pub enum RevocationError {
+    /// The account has not approved this action request
+    #[error("Not approved by this account")]
+    NotApprovedByAccount,
+}
+
 /// Errors when evaluating a request for execution
 #[derive(Error, Clone, Debug)]
 pub enum ExecutionEligibilityError {
@@ -114,6 +189,15 @@ pub enum ExecutionEligibilityError {
     /// The request has expired and cannot be approved or executed
     #[error(transparent)]
     RequestExpired(#[from] RequestExpiredError),
+    /// The request has reached the approval threshold, but the timelock has
+    /// not yet elapsed
+    #[error(transparent)]
+    TimelockNotElapsed(#[from] TimelockNotElapsedError),
+    /// The request meets the approval threshold without having reached it
+    /// through an approval (e.g. the threshold was lowered), so its timelock
+    /// has not started. The next approval starts it.
+    #[error("Timelock not started: the request must be approved to start its timelock")]
+    TimelockNotStarted,
 }
 
 /// What errors may occur when removing a request?
@@ -129,6 +213,7 @@ where
     Au: AccountAuthorizer,
 {
     type ApprovalError = ApprovalError;
+    type RevocationError = RevocationError;
     type RemovalError = RemovalError;
     type AuthorizationError = Au::AuthorizationError;
     type ExecutionEligibilityError = ExecutionEligibilityError;
@@ -137,15 +222,133 @@ where
         &self,
         action_request: &ActionRequest<Ac, ApprovalState>,
     ) -> Result<(), ExecutionEligibilityError> {
-        if !self.is_within_validity_period(&action_request.approval_state) {
-            return Err(RequestExpiredError.into());
+        self.check_execution_eligibility(&action_request.approval_state)
+    }
+
+    fn is_removable(
+        &self,
+        action_request: &ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::RemovalError> {
+        if self.is_within_validity_period(&action_request.approval_state) {
+            Err(RemovalError::RequestStillValid)
+        } else {
+            Ok(())
         }
+    }
 
-        let current = action_request.approval_state.approved_by.len();
-        let required = self.threshold as usize;
+    fn is_account_authorized(
+        &self,
+        account_id: &AccountId,
+        _action_request: &ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::AuthorizationError> {
+        Au::is_account_authorized(account_id)
+    }
 
-        if current < required {
-            return Err(ExecutionEligibilityError::InsufficientApprovals { current, required });
+    fn try_approve_with_authorized_account(
+        &self,
+        account_id: AccountId,
+        action_request: &mut ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::ApprovalError> {
+        self.approve(account_id, &mut action_request.approval_state)
+    }
+
+    fn try_revoke_with_authorized_account(
+        &self,
+        account_id: AccountId,
+        action_request: &mut ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::RevocationError> {
+        self.revoke(account_id, &mut action_request.approval_state)
+    }
+}
+
+/// Delays the execution of requests until a period has elapsed after they
+/// reached the approval threshold of the wrapped [`Configuration`]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+pub struct Timelocked<C> {
+    /// The wrapped approval scheme
+    pub configuration: C,
+    /// A request cannot be executed until this period has elapsed after it
+    /// reached the approval threshold.
+    /// 0 = no delay
+    pub timelock_nanoseconds: u64,
+}
+
+impl<C> Timelocked<C> {
+    /// Wrap an approval scheme with the given timelock
+    pub fn new(configuration: C, timelock_nanoseconds: u64) -> Self {
+        Self {
+            configuration,
+            timelock_nanoseconds,
+        }
+    }
+}
+
+/// Approval state for timelocked simple multisig
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
+pub struct TimelockedApprovalState {
+    /// Approvals of the request
+    pub approval_state: ApprovalState,
+    /// Network timestamp when the request most recently reached the approval
+    /// threshold, if it currently meets the threshold
+    pub threshold_reached_at_nanoseconds: Option<u64>,
+}
+
+impl Default for TimelockedApprovalState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimelockedApprovalState {
+    /// Creates a TimelockedApprovalState without approvals, created at the
+    /// current network timestamp. Its timelock starts when an approval
+    /// brings it to the approval threshold.
+    pub fn new() -> Self {
+        Self {
+            approval_state: ApprovalState::new(),
+            threshold_reached_at_nanoseconds: None,
+        }
+    }
+}
+
+impl<Au, Ac> ApprovalConfiguration<Ac, TimelockedApprovalState> for Timelocked<Configuration<Au>>
+where
+    Au: AccountAuthorizer,
+{
+    type ApprovalError = ApprovalError;
+    type RevocationError = RevocationError;
+    type RemovalError = RemovalError;
+    type AuthorizationError = Au::AuthorizationError;
+    type ExecutionEligibilityError = ExecutionEligibilityError;
+
+    fn is_approved_for_execution(
+        &self,
+        action_request: &ActionRequest<Ac, TimelockedApprovalState>,
+    ) -> Result<(), ExecutionEligibilityError> {
+        let TimelockedApprovalState {
+            approval_state,
+            threshold_reached_at_nanoseconds,
+        } = &action_request.approval_state;
+
+        self.configuration
+            .check_execution_eligibility(approval_state)?;
+
+        // A request that meets the threshold without any recorded time
+        // (e.g. after the threshold was lowered) has not started its timelock
+        let threshold_reached_at_nanoseconds = match threshold_reached_at_nanoseconds {
+            Some(threshold_reached_at_nanoseconds) => *threshold_reached_at_nanoseconds,
+            None if self.timelock_nanoseconds == 0 => return Ok(()),
+            None => return Err(ExecutionEligibilityError::TimelockNotStarted),
+        };
+
+        let executable_at_nanoseconds =
+            threshold_reached_at_nanoseconds.saturating_add(self.timelock_nanoseconds);
+
+        if env::block_timestamp() < executable_at_nanoseconds {
+            return Err(TimelockNotElapsedError {
+                executable_at_nanoseconds,
+            }
+            .into());
         }
 
         Ok(())
@@ -153,9 +356,12 @@ where
 
     fn is_removable(
         &self,
-        action_request: &ActionRequest<Ac, ApprovalState>,
+        action_request: &ActionRequest<Ac, TimelockedApprovalState>,
     ) -> Result<(), Self::RemovalError> {
-        if self.is_within_validity_period(&action_request.approval_state) {
+        if self
+            .configuration
+            .is_within_validity_period(&action_request.approval_state.approval_state)
+        {
             Err(RemovalError::RequestStillValid)
         } else {
             Ok(())
@@ -165,7 +371,7 @@ where
     fn is_account_authorized(
         &self,
         account_id: &AccountId,
-        _action_request: &ActionRequest<Ac, ApprovalState>,
+        _action_request: &ActionRequest<Ac, TimelockedApprovalState>,
     ) -> Result<(), Self::AuthorizationError> {
         Au::is_account_authorized(account_id)
     }
@@ -173,21 +379,36 @@ where
     fn try_approve_with_authorized_account(
         &self,
         account_id: AccountId,
-        action_request: &mut ActionRequest<Ac, ApprovalState>,
+        action_request: &mut ActionRequest<Ac, TimelockedApprovalState>,
     ) -> Result<(), Self::ApprovalError> {
-        if !self.is_within_validity_period(&action_request.approval_state) {
-            return Err(RequestExpiredError.into());
-        }
+        let state = &mut action_request.approval_state;
 
-        if action_request
-            .approval_state
-            .approved_by
-            .contains(&account_id)
+        self.configuration
+            .approve(account_id, &mut state.approval_state)?;
+
+        if state.threshold_reached_at_nanoseconds.is_none()
+            && state.approval_state.approved_by.len() >= self.configuration.threshold as usize
         {
-            return Err(ApprovalError::AlreadyApprovedByAccount);
+            state.threshold_reached_at_nanoseconds = Some(env::block_timestamp());
         }
 
-        action_request.approval_state.approved_by.push(account_id);
+        Ok(())
+    }
+
+    fn try_revoke_with_authorized_account(
+        &self,
+        account_id: AccountId,
+        action_request: &mut ActionRequest<Ac, TimelockedApprovalState>,
+    ) -> Result<(), Self::RevocationError> {
+        let state = &mut action_request.approval_state;
+
+        self.configuration
+            .revoke(account_id, &mut state.approval_state)?;
+
+        // The timelock restarts if the threshold is reached again
+        if state.approval_state.approved_by.len() < self.configuration.threshold as usize {
+            state.threshold_reached_at_nanoseconds = None;
+        }
 
         Ok(())
     }
@@ -305,6 +526,10 @@ mod tests {
         pub fn remove(&mut self, request_id: u32) {
             self.remove_request(request_id).unwrap()
         }
+
+        pub fn revoke(&mut self, request_id: u32) {
+            self.revoke_approval(request_id).unwrap()
+        }
     }
 
     fn predecessor(account_id: &AccountId) {
@@ -434,4 +659,254 @@ mod tests {
 
         contract.remove(request_id);
     }
+
+    #[test]
+    fn successful_revocation() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob_acct".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        for account_id in [&alice, &bob, &charlie] {
+            predecessor(account_id);
+            contract.obtain_multisig_permission();
+        }
+
+        predecessor(&alice);
+        let request_id = contract.create(true);
+        contract.approve(request_id);
+
+        predecessor(&bob);
+        contract.approve(request_id);
+
+        assert!(Contract::is_approved_for_execution(request_id).is_ok());
+
+        contract.revoke(request_id);
+
+        assert!(Contract::is_approved_for_execution(request_id).is_err());
+        assert_eq!(
+            Contract::get_request(request_id)
+                .unwrap()
+                .approval_state
+                .approved_by,
+            vec![alice],
+        );
+
+        predecessor(&charlie);
+        contract.approve(request_id);
+
+        assert_eq!(contract.execute(request_id), "hello");
+    }
+
+    #[test]
+    #[should_panic = "NotApprovedByAccount"]
+    fn unsuccessful_revocation_not_approved() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob_acct".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        for account_id in [&alice, &bob] {
+            predecessor(account_id);
+            contract.obtain_multisig_permission();
+        }
+
+        predecessor(&alice);
+        let request_id = contract.create(true);
+        contract.approve(request_id);
+
+        predecessor(&bob);
+        contract.revoke(request_id);
+    }
+
+    #[test]
+    fn storage_layout() {
+        let config = Configuration::<Contract>::new(2, 5);
+        let mut expected_config = vec![2];
+        expected_config.extend(5u64.to_le_bytes());
+        assert_eq!(config.try_to_vec().unwrap(), expected_config);
+
+        let state = ApprovalState {
+            approved_by: vec!["alice".parse().unwrap()],
+            created_at_nanoseconds: 7,
+        };
+        let mut expected_state = 1u32.to_le_bytes().to_vec();
+        expected_state.extend(5u32.to_le_bytes());
+        expected_state.extend(b"alice");
+        expected_state.extend(7u64.to_le_bytes());
+        assert_eq!(state.try_to_vec().unwrap(), expected_state);
+    }
+
+    mod timelocked {
+        use super::*;
+        use crate::approval::simple_multisig::{
+            ExecutionEligibilityError, TimelockNotElapsedError, Timelocked, TimelockedApprovalState,
+        };
+
+        struct TimelockedContract {}
+
+        impl
+            ApprovalManagerInternal<
+                Action,
+                TimelockedApprovalState,
+                Timelocked<Configuration<Self>>,
+            > for TimelockedContract
+        {
+            fn root() -> Slot<()> {
+                Slot::new(b"t")
+            }
+        }
+
+        impl crate::approval::Action<TimelockedContract> for Action {
+            type Output = &'static str;
+
+            fn execute(self, _contract: &mut TimelockedContract) -> Self::Output {
+                match self {
+                    Self::SayHello => "hello",
+                    Self::SayGoodbye => "goodbye",
+                }
+            }
+        }
+
+        impl AccountAuthorizer for TimelockedContract {
+            type AuthorizationError = MissingRole;
+
+            fn is_account_authorized(_account_id: &AccountId) -> Result<(), MissingRole> {
+                Ok(())
+            }
+        }
+
+        fn at_time(account_id: &AccountId, block_timestamp: u64) {
+            let mut context = VMContextBuilder::new();
+            context
+                .predecessor_account_id(account_id.clone())
+                .block_timestamp(block_timestamp);
+            testing_env!(context.build());
+        }
+
+        fn setup() -> (TimelockedContract, u32, AccountId) {
+            let alice: AccountId = "alice".parse().unwrap();
+            let bob: AccountId = "bob_acct".parse().unwrap();
+
+            at_time(&bob, 1000);
+            <TimelockedContract as ApprovalManager<_, _, _>>::init(Timelocked::new(
+                Configuration::new(2, 0),
+                100,
+            ));
+            let mut contract = TimelockedContract {};
+
+            let request_id = contract
+                .create_request(Action::SayHello, TimelockedApprovalState::new())
+                .unwrap();
+            contract.approve_request(request_id).unwrap();
+
+            at_time(&alice, 1050);
+            contract.approve_request(request_id).unwrap();
+
+            (contract, request_id, alice)
+        }
+
+        #[test]
+        fn timelock_elapsed() {
+            let (mut contract, request_id, alice) = setup();
+
+            assert_eq!(
+                TimelockedContract::get_request(request_id)
+                    .unwrap()
+                    .approval_state
+                    .threshold_reached_at_nanoseconds,
+                Some(1050),
+            );
+
+            at_time(&alice, 1149);
+            assert!(matches!(
+                TimelockedContract::is_approved_for_execution(request_id),
+                Err(ExecutionEligibilityError::TimelockNotElapsed(
+                    TimelockNotElapsedError {
+                        executable_at_nanoseconds: 1150,
+                    }
+                )),
+            ));
+
+            at_time(&alice, 1150);
+            assert!(TimelockedContract::is_approved_for_execution(request_id).is_ok());
+            assert_eq!(contract.execute_request(request_id).unwrap(), "hello");
+        }
+
+        #[test]
+        #[should_panic = "TimelockNotElapsed"]
+        fn timelock_not_elapsed() {
+            let (mut contract, request_id, alice) = setup();
+
+            at_time(&alice, 1100);
+            contract.execute_request(request_id).unwrap();
+        }
+
+        #[test]
+        fn timelock_restarts_after_revocation() {
+            let (mut contract, request_id, alice) = setup();
+
+            at_time(&alice, 1100);
+            contract.revoke_approval(request_id).unwrap();
+
+            assert!(TimelockedContract::get_request(request_id)
+                .unwrap()
+                .approval_state
+                .threshold_reached_at_nanoseconds
+                .is_none());
+
+            at_time(&alice, 1200);
+            contract.approve_request(request_id).unwrap();
+
+            assert!(TimelockedContract::is_approved_for_execution(request_id).is_err());
+
+            at_time(&alice, 1300);
+            assert_eq!(contract.execute_request(request_id).unwrap(), "hello");
+        }
+
+        #[test]
+        fn timelock_starts_after_threshold_lowered() {
+            let bob: AccountId = "bob_acct".parse().unwrap();
+            let alice: AccountId = "alice".parse().unwrap();
+
+            at_time(&bob, 1000);
+            <TimelockedContract as ApprovalManager<_, _, _>>::init(Timelocked::new(
+                Configuration::new(2, 0),
+                100,
+            ));
+            let mut contract = TimelockedContract {};
+
+            let request_id = contract
+                .create_request(Action::SayHello, TimelockedApprovalState::new())
+                .unwrap();
+            contract.approve_request(request_id).unwrap();
+
+            // The single approval now meets the threshold, but never reached it
+            TimelockedContract::slot_config()
+                .write(&Timelocked::new(Configuration::new(1, 0), 100));
+
+            at_time(&bob, 2000);
+            assert!(matches!(
+                TimelockedContract::is_approved_for_execution(request_id),
+                Err(ExecutionEligibilityError::TimelockNotStarted),
+            ));
+
+            at_time(&alice, 2000);
+            contract.approve_request(request_id).unwrap();
+
+            at_time(&alice, 2099);
+            assert!(matches!(
+                TimelockedContract::is_approved_for_execution(request_id),
+                Err(ExecutionEligibilityError::TimelockNotElapsed(
+                    TimelockNotElapsedError {
+                        executable_at_nanoseconds: 2100,
+                    }
+                )),
+            ));
+
+            at_time(&alice, 2100);
+            assert_eq!(contract.execute_request(request_id).unwrap(), "hello");
+        }
+    }
 }
@@ -0,0 +1,488 @@
+//! Weighted multi-signature wallet component. Generic over approvable actions.
+//! Each approving account contributes its weight towards a threshold, rather
+//! than every approval counting equally as in
+//! [simple_multisig](super::simple_multisig).
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (ERR) Only accounts that have a weight (see: [`AccountWeighter`]) may
+//!   approve requests.
+//! * The weight of an approval is fixed when it is submitted. Changing the
+//!   weight of an account does not change the weight of its existing
+//!   approvals, so a request may remain approved for execution after the
+//!   weights of its approvers are lowered. The account may revoke and
+//!   resubmit its approval to apply its current weight.
+
+use std::marker::PhantomData;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    simple_multisig::RequestExpiredError, ActionRequest, ApprovalConfiguration,
+    UnauthorizedAccountError,
+};
+
+/// Determine the weight of accounts that are eligible to submit approvals to
+/// an [ApprovalManager](super::ApprovalManager)
+pub trait AccountWeighter {
+    /// Why can this account not be authorized?
+    type AuthorizationError;
+
+    /// Determines the weight an account contributes when it submits an
+    /// approval, failing if the account is not allowed to submit approvals
+    fn account_weight(account_id: &AccountId) -> Result<u64, Self::AuthorizationError>;
+}
+
+/// Weighted approval scheme: the weights of approving accounts must add up to
+/// the threshold
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+pub struct Configuration<Au: AccountWeighter> {
+    /// How much accumulated weight is required?
+    pub threshold: u64,
+    /// A request cannot be executed, and can be deleted by any
+    /// approval-eligible member after this period has elapsed.
+    /// 0 = perpetual validity, no deletion
+    pub validity_period_nanoseconds: u64,
+    #[borsh_skip]
+    #[serde(skip)]
+    _weighter: PhantomData<Au>,
+}
+
+impl<Au: AccountWeighter> Configuration<Au> {
+    /// Create an approval scheme with the given weight threshold
+    pub fn new(threshold: u64, validity_period_nanoseconds: u64) -> Self {
+        Self {
+            threshold,
+            validity_period_nanoseconds,
+            _weighter: PhantomData,
+        }
+    }
+
+    /// Is the given approval state still considered valid?
+    pub fn is_within_validity_period(&self, approval_state: &ApprovalState) -> bool {
+        if self.validity_period_nanoseconds == 0 {
+            true
+        } else {
+            env::block_timestamp()
+                .checked_sub(approval_state.created_at_nanoseconds)
+                .unwrap() // inconsistent state if a request timestamp is in the future
+                < self.validity_period_nanoseconds
+        }
+    }
+}
+
+/// An approval submitted by an account
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Approval {
+    /// Approving account
+    pub account_id: AccountId,
+    /// Weight of the account at the time of approval
+    pub weight: u64,
+}
+
+/// Approval state for weighted multisig
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
+pub struct ApprovalState {
+    /// List of approvals submitted thus far
+    pub approvals: Vec<Approval>,
+    /// Sum of the weights of all approvals
+    pub weight: u64,
+    /// Network timestamp when the request was created
+    pub created_at_nanoseconds: u64,
+}
+
+impl Default for ApprovalState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApprovalState {
+    /// Creates an ApprovalState with the current network timestamp
+    pub fn new() -> Self {
+        Self {
+            approvals: Vec::new(),
+            weight: 0,
+            created_at_nanoseconds: env::block_timestamp(),
+        }
+    }
+}
+
+/// Why might a weighted multisig approval attempt fail?
+#[derive(Error, Clone, Debug)]
+pub enum ApprovalError<AuthErr> {
+    /// The account has already approved this action request
+    #[error("Already approved by this account")]
+    AlreadyApprovedByAccount,
+    /// The request has expired and cannot be approved or executed
+    #[error(transparent)]
+    RequestExpired(#[from] RequestExpiredError),
+    /// The weight of the account could not be determined
+    #[error(transparent)]
+    UnauthorizedAccount(#[from] UnauthorizedAccountError<AuthErr>),
+}
+
+/// Why might a weighted multisig revocation attempt fail?
+#[derive(Error, Clone, Debug)]
+pub enum RevocationError {
+    /// The account has not approved this action request
+    #[error("Not approved by this account")]
+    NotApprovedByAccount,
+}
+
+/// Errors when evaluating a request for execution
+#[derive(Error, Clone, Debug)]
+pub enum ExecutionEligibilityError {
+    /// The request does not have enough accumulated weight
+    #[error("Insufficient approval weight on request: required {required} but only has {current}")]
+    InsufficientWeight {
+        /// Current accumulated weight
+        current: u64,
+        /// Required weight
+        required: u64,
+    },
+    /// The request has expired and cannot be approved or executed
+    #[error(transparent)]
+    RequestExpired(#[from] RequestExpiredError),
+}
+
+/// What errors may occur when removing a request?
+#[derive(Error, Clone, Debug)]
+pub enum RemovalError {
+    /// Requests may not be removed while they are still valid
+    #[error("Removal prohibited before expiration")]
+    RequestStillValid,
+}
+
+impl<Au, Ac> ApprovalConfiguration<Ac, ApprovalState> for Configuration<Au>
+where
+    Au: AccountWeighter,
+{
+    type ApprovalError = ApprovalError<Au::AuthorizationError>;
+    type RevocationError = RevocationError;
+    type RemovalError = RemovalError;
+    type AuthorizationError = Au::AuthorizationError;
+    type ExecutionEligibilityError = ExecutionEligibilityError;
+
+    fn is_approved_for_execution(
+        &self,
+        action_request: &ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), ExecutionEligibilityError> {
+        if !self.is_within_validity_period(&action_request.approval_state) {
+            return Err(RequestExpiredError.into());
+        }
+
+        let current = action_request.approval_state.weight;
+        let required = self.threshold;
+
+        if current < required {
+            return Err(ExecutionEligibilityError::InsufficientWeight { current, required });
+        }
+
+        Ok(())
+    }
+
+    fn is_removable(
+        &self,
+        action_request: &ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::RemovalError> {
+        if self.is_within_validity_period(&action_request.approval_state) {
+            Err(RemovalError::RequestStillValid)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_account_authorized(
+        &self,
+        account_id: &AccountId,
+        _action_request: &ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::AuthorizationError> {
+        Au::account_weight(account_id).map(|_| ())
+    }
+
+    fn try_approve_with_authorized_account(
+        &self,
+        account_id: AccountId,
+        action_request: &mut ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::ApprovalError> {
+        if !self.is_within_validity_period(&action_request.approval_state) {
+            return Err(ApprovalError::RequestExpired(RequestExpiredError));
+        }
+
+        let approval_state = &mut action_request.approval_state;
+
+        if approval_state
+            .approvals
+            .iter()
+            .any(|approval| approval.account_id == account_id)
+        {
+            return Err(ApprovalError::AlreadyApprovedByAccount);
+        }
+
+        // The account has already been authorized, so this only fails if
+        // the weighter is inconsistent
+        let weight = Au::account_weight(&account_id)
+            .map_err(|e| UnauthorizedAccountError(account_id.clone(), e))?;
+
+        approval_state.weight = approval_state.weight.saturating_add(weight);
+        approval_state
+            .approvals
+            .push(Approval { account_id, weight });
+
+        Ok(())
+    }
+
+    fn try_revoke_with_authorized_account(
+        &self,
+        account_id: AccountId,
+        action_request: &mut ActionRequest<Ac, ApprovalState>,
+    ) -> Result<(), Self::RevocationError> {
+        let approval_state = &mut action_request.approval_state;
+
+        let index = approval_state
+            .approvals
+            .iter()
+            .position(|approval| approval.account_id == account_id)
+            .ok_or(RevocationError::NotApprovedByAccount)?;
+
+        let approval = approval_state.approvals.remove(index);
+        approval_state.weight = approval_state.weight.saturating_sub(approval.weight);
+
+        Ok(())
+    }
+}
+
+/// Types used by near-sdk-contract-tools-macros
+pub mod macro_types {
+    use thiserror::Error;
+
+    /// Account that attempted an action has no approval weight
+    #[derive(Error, Clone, Debug)]
+    #[error("Account has no approval weight")]
+    pub struct NoWeight;
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        borsh::{self, BorshDeserialize, BorshSerialize},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId,
+    };
+    use thiserror::Error;
+
+    use crate::{
+        approval::{
+            weighted_multisig::{AccountWeighter, ApprovalError, ApprovalState, Configuration},
+            ApprovalConfiguration, ApprovalManager, ApprovalManagerInternal,
+        },
+        slot::Slot,
+    };
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    enum Action {
+        SayHello,
+    }
+
+    impl crate::approval::Action<Contract> for Action {
+        type Output = &'static str;
+
+        fn execute(self, _contract: &mut Contract) -> Self::Output {
+            match self {
+                Self::SayHello => "hello",
+            }
+        }
+    }
+
+    #[derive(Debug, BorshSerialize, BorshDeserialize)]
+    #[near_bindgen]
+    struct Contract {}
+
+    impl ApprovalManagerInternal<Action, ApprovalState, Configuration<Self>> for Contract {
+        fn root() -> Slot<()> {
+            Slot::new(b"w")
+        }
+    }
+
+    #[derive(Error, Clone, Debug)]
+    #[error("Not a signer")]
+    struct NotASigner;
+
+    impl AccountWeighter for Contract {
+        type AuthorizationError = NotASigner;
+
+        fn account_weight(account_id: &AccountId) -> Result<u64, NotASigner> {
+            match account_id.as_str() {
+                "alice" => Ok(3),
+                "bob_acct" => Ok(2),
+                "charlie" => Ok(1),
+                _ => Err(NotASigner),
+            }
+        }
+    }
+
+    #[near_bindgen]
+    impl Contract {
+        #[init]
+        pub fn new() -> Self {
+            <Self as ApprovalManager<_, _, _>>::init(Configuration::new(4, 10000));
+            Self {}
+        }
+
+        pub fn create(&mut self) -> u32 {
+            self.create_request(Action::SayHello, ApprovalState::new())
+                .unwrap()
+        }
+
+        pub fn approve(&mut self, request_id: u32) {
+            self.approve_request(request_id).unwrap();
+        }
+
+        pub fn revoke(&mut self, request_id: u32) {
+            self.revoke_approval(request_id).unwrap();
+        }
+
+        pub fn execute(&mut self, request_id: u32) -> &'static str {
+            self.execute_request(request_id).unwrap()
+        }
+    }
+
+    fn predecessor(account_id: &AccountId) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(account_id.clone());
+        testing_env!(context.build());
+    }
+
+    fn weight_of(request_id: u32) -> u64 {
+        Contract::get_request(request_id)
+            .unwrap()
+            .approval_state
+            .weight
+    }
+
+    #[test]
+    fn successful_approval() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob_acct".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        predecessor(&charlie);
+        let request_id = contract.create();
+        contract.approve(request_id);
+
+        predecessor(&bob);
+        contract.approve(request_id);
+
+        assert_eq!(weight_of(request_id), 3);
+        assert!(Contract::is_approved_for_execution(request_id).is_err());
+
+        predecessor(&alice);
+        contract.approve(request_id);
+
+        assert_eq!(weight_of(request_id), 6);
+        assert!(Contract::is_approved_for_execution(request_id).is_ok());
+
+        assert_eq!(contract.execute(request_id), "hello");
+    }
+
+    #[test]
+    fn successful_revocation() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let charlie: AccountId = "charlie".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        predecessor(&alice);
+        let request_id = contract.create();
+        contract.approve(request_id);
+
+        predecessor(&charlie);
+        contract.approve(request_id);
+
+        assert!(Contract::is_approved_for_execution(request_id).is_ok());
+
+        predecessor(&alice);
+        contract.revoke(request_id);
+
+        assert_eq!(weight_of(request_id), 1);
+        assert!(Contract::is_approved_for_execution(request_id).is_err());
+    }
+
+    #[test]
+    #[should_panic = "InsufficientWeight"]
+    fn no_execution_before_approval() {
+        let alice: AccountId = "alice".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        predecessor(&alice);
+        let request_id = contract.create();
+        contract.approve(request_id);
+
+        contract.execute(request_id);
+    }
+
+    #[test]
+    #[should_panic = "AlreadyApprovedByAccount"]
+    fn duplicate_approval() {
+        let alice: AccountId = "alice".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        predecessor(&alice);
+        let request_id = contract.create();
+        contract.approve(request_id);
+        contract.approve(request_id);
+    }
+
+    #[test]
+    #[should_panic = "UnauthorizedAccount"]
+    fn unauthorized_approval() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let dave: AccountId = "dave".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        predecessor(&alice);
+        let request_id = contract.create();
+
+        predecessor(&dave);
+        contract.approve(request_id);
+    }
+
+    #[test]
+    fn approval_without_weight() {
+        let alice: AccountId = "alice".parse().unwrap();
+        let dave: AccountId = "dave".parse().unwrap();
+
+        let mut contract = Contract::new();
+
+        predecessor(&alice);
+        let request_id = contract.create();
+        let mut request = Contract::get_request(request_id).unwrap();
+
+        // The weight is determined again when the approval is recorded
+        let result = <Configuration<Contract> as ApprovalConfiguration<Action, _>>::try_approve_with_authorized_account(
+            &Contract::get_config(),
+            dave,
+            &mut request,
+        );
+
+        assert!(matches!(result, Err(ApprovalError::UnauthorizedAccount(_))));
+        assert!(request.approval_state.approvals.is_empty());
+        assert_eq!(request.approval_state.weight, 0);
+    }
+}
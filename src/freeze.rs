@@ -0,0 +1,228 @@
+//! Freeze pattern implements methods to freeze, unfreeze and check the status
+//! of individual accounts.
+//!
+//! [`Freeze`] implements methods to freeze and unfreeze accounts. When the
+//! methods are called the account's status changes and the respective event
+//! is emitted. Accounts are unfrozen by default. [`FreezeExternal`] exposes an
+//! external function to check the status of an account.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::Freeze) derives a
+//! default implementation for both these traits. Use
+//! `#[freeze(nep141_hook)]` to also derive a [`Nep141Hook`] implementation
+//! that rejects transfers, mints, and burns involving frozen accounts, as
+//! well as transfer call refunds from or to frozen accounts. To combine this
+//! with other hooks, implement [`Nep141Hook`] instead and call
+//! [`Freeze::before_nep141_transfer`], [`Freeze::before_nep141_mint`],
+//! [`Freeze::before_nep141_burn`], and [`Freeze::refundable_nep141_amount`]
+//! from it (see: [`Nep141Hook`]). Transfer call refunds do not execute the
+//! transfer hooks, so without [`Freeze::refundable_nep141_amount`], frozen
+//! accounts may send and receive refunds.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * Initial state is unfrozen for all accounts.
+//! * (UB) The freeze root storage slot is not used or modified. The default key is `~f`.
+//! * (ERR) Only an unfrozen account can be frozen using [`Freeze::freeze`].
+//! * (ERR) Only a frozen account can be unfrozen using [`Freeze::unfreeze`].
+//! * (ERR) [`Freeze::require_frozen`] may only be called with a frozen account.
+//! * (ERR) [`Freeze::require_not_frozen`] may only be called with an unfrozen account.
+//!
+//! [`Nep141Hook`]: crate::standard::nep141::Nep141Hook
+#![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    env, ext_contract, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+
+use crate::{
+    slot::Slot,
+    standard::{
+        nep141::{Nep141Burn, Nep141Mint, Nep141Transfer},
+        nep297::Event,
+    },
+    DefaultStorageKey,
+};
+
+/// Events emitted when the freeze state of an account is changed
+#[event(
+    standard = "x-frz",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum FreezeEvent {
+    /// Emitted when an account is frozen
+    Freeze {
+        /// The frozen account
+        account_id: AccountId,
+    },
+    /// Emitted when an account is unfrozen
+    Unfreeze {
+        /// The unfrozen account
+        account_id: AccountId,
+    },
+}
+
+#[derive(BorshSerialize, BorshStorageKey, Debug, Clone)]
+enum StorageKey {
+    Frozen(AccountId),
+}
+
+/// Internal functions for [`Freeze`]. Using these methods may result in unexpected behavior.
+pub trait FreezeInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Freeze)
+    }
+
+    /// Storage slot for the freeze state of an account
+    fn slot_frozen(account_id: &AccountId) -> Slot<bool> {
+        Self::root().field(StorageKey::Frozen(account_id.clone()))
+    }
+}
+
+/// Contract private-only interactions for freezing accounts.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{near_bindgen, AccountId};
+/// use near_sdk_contract_tools::{freeze::Freeze, owner::Owner, Freeze, Owner};
+///
+/// #[derive(Owner, Freeze)]
+/// #[near_bindgen]
+/// struct Contract {
+///     // ...
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn freeze_account(&mut self, account_id: AccountId) {
+///         Self::require_owner();
+///         self.freeze(account_id);
+///     }
+///
+///     pub fn unfreeze_account(&mut self, account_id: AccountId) {
+///         Self::require_owner();
+///         self.unfreeze(account_id);
+///     }
+///
+///     pub fn only_when_not_frozen(&self) {
+///         Self::require_not_frozen(&near_sdk::env::predecessor_account_id());
+///     }
+/// }
+/// ```
+pub trait Freeze {
+    /// Force the freeze state of an account in a particular direction.
+    /// Does not emit events or check the current freeze state.
+    fn set_is_frozen(&mut self, account_id: &AccountId, is_frozen: bool);
+
+    /// Returns `true` if the account is frozen, `false` otherwise
+    fn is_frozen(account_id: &AccountId) -> bool;
+
+    /// Freezes the account if it is currently unfrozen, panics otherwise.
+    /// Emits a `FreezeEvent::Freeze` event.
+    fn freeze(&mut self, account_id: AccountId);
+
+    /// Unfreezes the account if it is currently frozen, panics otherwise.
+    /// Emits a `FreezeEvent::Unfreeze` event.
+    fn unfreeze(&mut self, account_id: AccountId);
+
+    /// Rejects if the account is not frozen.
+    fn require_frozen(account_id: &AccountId);
+
+    /// Rejects if the account is frozen.
+    fn require_not_frozen(account_id: &AccountId);
+
+    /// Rejects an NEP-141 transfer from or to a frozen account. Call from
+    /// [`Nep141Hook::before_transfer`](crate::standard::nep141::Nep141Hook::before_transfer).
+    fn before_nep141_transfer(transfer: &Nep141Transfer);
+
+    /// Rejects an NEP-141 mint to a frozen account. Call from
+    /// [`Nep141Hook::before_mint`](crate::standard::nep141::Nep141Hook::before_mint).
+    fn before_nep141_mint(mint: &Nep141Mint);
+
+    /// Rejects an NEP-141 burn from a frozen account. Call from
+    /// [`Nep141Hook::before_burn`](crate::standard::nep141::Nep141Hook::before_burn).
+    fn before_nep141_burn(burn: &Nep141Burn);
+
+    /// Rejects an NEP-141 transfer call refund from or to a frozen account
+    /// by limiting it to 0 tokens. Call from
+    /// [`Nep141Hook::refundable_amount`](crate::standard::nep141::Nep141Hook::refundable_amount).
+    fn refundable_nep141_amount(refund: &Nep141Transfer) -> u128;
+}
+
+impl<T: FreezeInternal> Freeze for T {
+    fn set_is_frozen(&mut self, account_id: &AccountId, is_frozen: bool) {
+        let mut slot = Self::slot_frozen(account_id);
+
+        if is_frozen {
+            slot.write(&true);
+        } else {
+            slot.remove();
+        }
+    }
+
+    fn is_frozen(account_id: &AccountId) -> bool {
+        Self::slot_frozen(account_id).read().unwrap_or(false)
+    }
+
+    fn freeze(&mut self, account_id: AccountId) {
+        Self::require_not_frozen(&account_id);
+        self.set_is_frozen(&account_id, true);
+        FreezeEvent::Freeze { account_id }.emit();
+    }
+
+    fn unfreeze(&mut self, account_id: AccountId) {
+        Self::require_frozen(&account_id);
+        self.set_is_frozen(&account_id, false);
+        FreezeEvent::Unfreeze { account_id }.emit();
+    }
+
+    fn require_frozen(account_id: &AccountId) {
+        if !Self::is_frozen(account_id) {
+            env::panic_str(&format!("Account `{account_id}` is not frozen"));
+        }
+    }
+
+    fn require_not_frozen(account_id: &AccountId) {
+        if Self::is_frozen(account_id) {
+            env::panic_str(&format!("Account `{account_id}` is frozen"));
+        }
+    }
+
+    fn before_nep141_transfer(transfer: &Nep141Transfer) {
+        Self::require_not_frozen(&transfer.sender_id);
+        Self::require_not_frozen(&transfer.receiver_id);
+    }
+
+    fn before_nep141_mint(mint: &Nep141Mint) {
+        Self::require_not_frozen(&mint.account_id);
+    }
+
+    fn before_nep141_burn(burn: &Nep141Burn) {
+        Self::require_not_frozen(&burn.account_id);
+    }
+
+    fn refundable_nep141_amount(refund: &Nep141Transfer) -> u128 {
+        if Self::is_frozen(&refund.sender_id) || Self::is_frozen(&refund.receiver_id) {
+            0
+        } else {
+            refund.amount
+        }
+    }
+}
+
+/// External (public) methods for [`Freeze`]
+#[ext_contract(ext_freeze)]
+pub trait FreezeExternal {
+    /// Returns `true` if the account is frozen, `false` otherwise
+    fn frz_is_frozen(&self, account_id: AccountId) -> bool;
+}
@@ -5,24 +5,57 @@
 pub enum DefaultStorageKey {
     /// Default storage key for [`approval::ApprovalManager::root`]
     ApprovalManager,
+    /// Default storage key for [`freeze::FreezeInternal::root`]
+    Freeze,
     /// Default storage key for [`standard::nep141::Nep141Controller::root`]
     Nep141,
+    /// Default storage key for [`standard::nep145::Nep145ControllerInternal::root`]
+    Nep145,
+    /// Default storage key for [`standard::nep171::Nep171ControllerInternal::root`]
+    Nep171,
+    /// Default storage key for [`standard::nep177::Nep177ControllerInternal::root`]
+    Nep177,
+    /// Default storage key for [`standard::nep178::Nep178ControllerInternal::root`]
+    Nep178,
+    /// Default storage key for [`standard::nep181::Nep181ControllerInternal::root`]
+    Nep181,
+    /// Default storage key for [`standard::nep199::Nep199ControllerInternal::root`]
+    Nep199,
+    /// Default storage key for [`standard::nep245::Nep245ControllerInternal::root`]
+    Nep245,
+    /// Default storage key for [`standard::nep330::Nep330ControllerInternal::root`]
+    Nep330,
     /// Default storage key for [`owner::Owner::root`]
     Owner,
     /// Default storage key for [`pause::Pause::root`]
     Pause,
     /// Default storage key for [`rbac::Rbac::root`]
     Rbac,
+    /// Default storage key for [`transfer_fee::TransferFeeInternal::root`]
+    TransferFee,
+    /// Default storage key for [`vesting::VestingInternal::root`]
+    Vesting,
 }
 
 impl IntoStorageKey for DefaultStorageKey {
     fn into_storage_key(self) -> Vec<u8> {
         match self {
             DefaultStorageKey::ApprovalManager => b"~am".to_vec(),
+            DefaultStorageKey::Freeze => b"~f".to_vec(),
             DefaultStorageKey::Nep141 => b"~$141".to_vec(),
+            DefaultStorageKey::Nep145 => b"~$145".to_vec(),
+            DefaultStorageKey::Nep171 => b"~$171".to_vec(),
+            DefaultStorageKey::Nep177 => b"~$177".to_vec(),
+            DefaultStorageKey::Nep178 => b"~$178".to_vec(),
+            DefaultStorageKey::Nep181 => b"~$181".to_vec(),
+            DefaultStorageKey::Nep199 => b"~$199".to_vec(),
+            DefaultStorageKey::Nep245 => b"~$245".to_vec(),
+            DefaultStorageKey::Nep330 => b"~$330".to_vec(),
             DefaultStorageKey::Owner => b"~o".to_vec(),
             DefaultStorageKey::Pause => b"~p".to_vec(),
             DefaultStorageKey::Rbac => b"~r".to_vec(),
+            DefaultStorageKey::TransferFee => b"~tf".to_vec(),
+            DefaultStorageKey::Vesting => b"~v".to_vec(),
         }
     }
 }
@@ -30,13 +63,17 @@ impl IntoStorageKey for DefaultStorageKey {
 pub mod standard;
 
 pub mod approval;
+pub mod freeze;
 pub mod migrate;
 pub mod owner;
 pub mod pause;
 pub mod rbac;
 pub mod slot;
+pub mod transfer_fee;
 pub mod upgrade;
 pub mod utils;
+pub mod vesting;
+pub mod wrapped_near;
 
 use near_sdk::IntoStorageKey;
 pub use near_sdk_contract_tools_macros::*;
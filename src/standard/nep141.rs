@@ -2,24 +2,35 @@
 //! <https://github.com/near/NEPs/blob/master/neps/nep-0141.md>
 #![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
 
+use std::collections::HashMap;
+
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     env, ext_contract,
-    json_types::U128,
-    require, AccountId, BorshStorageKey, Gas, Promise, PromiseOrValue, PromiseResult,
+    json_types::{U128, U64},
+    AccountId, BorshStorageKey, Gas, Promise, PromiseOrValue, PromiseResult,
 };
 use near_sdk_contract_tools_macros::event;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::{slot::Slot, standard::nep297::*, DefaultStorageKey};
+use crate::{
+    slot::Slot,
+    standard::{
+        nep145::{longest_account_id, record_storage_usage},
+        nep297::*,
+    },
+    DefaultStorageKey,
+};
 
-/// Gas value required for ft_resolve_transfer calls
+/// Default gas value required for ft_resolve_transfer calls (see:
+/// [`Nep141ControllerInternal::gas_for_resolve_transfer`])
 pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
-/// Gas value required for ft_transfer_call calls (includes gas for )
+/// Default gas value required for ft_transfer_call calls, including gas for
+/// ft_resolve_transfer (see:
+/// [`Nep141ControllerInternal::gas_for_ft_transfer_call`])
 pub const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
 
-const MORE_GAS_FAIL_MESSAGE: &str = "More gas is required";
-
 /// NEP-141 standard events for minting, burning, and transferring tokens
 #[event(
     crate = "crate",
@@ -43,6 +54,22 @@ pub enum Nep141Event {
     FtBurn(Vec<event::FtBurnData>),
 }
 
+/// Events for the optional NEP-141 allowance extension (see:
+/// [`Nep141AllowanceController`])
+#[event(
+    crate = "crate",
+    macros = "crate",
+    serde = "serde",
+    standard = "nep141-allowance",
+    version = "1.0.0"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep141AllowanceEvent {
+    /// Allowance change event. Emitted when an owner sets the amount of its
+    /// tokens that a spender may transfer.
+    FtApprove(Vec<event::FtApproveData>),
+}
+
 pub mod event {
     use near_sdk::{json_types::U128, AccountId};
     use serde::Serialize;
@@ -85,10 +112,27 @@ pub mod event {
         pub memo: Option<String>,
     }
 
+    /// Individual allowance change metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct FtApproveData {
+        /// Account ID of the token owner
+        pub owner_id: AccountId,
+        /// Account ID of the spender
+        pub spender_id: AccountId,
+        /// New allowance of the spender
+        pub amount: U128,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
     #[cfg(test)]
     mod tests {
 
-        use super::{super::Nep141Event, *};
+        use super::{
+            super::{Nep141AllowanceEvent, Nep141Event},
+            *,
+        };
         use crate::standard::nep297::Event;
 
         #[test]
@@ -138,6 +182,20 @@ pub mod event {
                 r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"foundation.near","amount":"100"}]}"#,
             );
         }
+
+        #[test]
+        fn approve() {
+            assert_eq!(
+                Nep141AllowanceEvent::FtApprove(vec![FtApproveData {
+                    owner_id: "owner.near".parse().unwrap(),
+                    spender_id: "spender.near".parse().unwrap(),
+                    amount: 250u128.into(),
+                    memo: None,
+                }])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep141-allowance","version":"1.0.0","event":"ft_approve","data":[{"owner_id":"owner.near","spender_id":"spender.near","amount":"250"}]}"#,
+            );
+        }
     }
 }
 
@@ -145,6 +203,11 @@ pub mod event {
 enum StorageKey {
     TotalSupply,
     Account(AccountId),
+    Allowance(AccountId, AccountId),
+    BalanceCheckpointCount(AccountId),
+    BalanceCheckpoint(AccountId, u32),
+    TotalSupplyCheckpointCount,
+    TotalSupplyCheckpoint(u32),
 }
 
 /// Contracts may implement this trait to inject code into NEP-141 functions.
@@ -152,6 +215,57 @@ enum StorageKey {
 /// `T` is an optional value for passing state between different lifecycle
 /// hooks. This may be useful for charging callers for storage usage, for
 /// example.
+///
+/// # Combining hooks
+///
+/// A contract can only implement this trait once, so the `nep141_hook`
+/// options of the `Freeze`, `Vesting`, and `TransferFee` derive macros
+/// cannot be combined. Instead, call their hook functions from a single
+/// implementation:
+///
+/// ```
+/// use near_sdk::near_bindgen;
+/// use near_sdk_contract_tools::{
+///     freeze::Freeze,
+///     standard::nep141::{Nep141Burn, Nep141Hook, Nep141Mint, Nep141Transfer},
+///     transfer_fee::TransferFee,
+///     vesting::Vesting,
+///     Freeze, FungibleToken, TransferFee, Vesting,
+/// };
+///
+/// #[derive(Freeze, FungibleToken, TransferFee, Vesting)]
+/// #[fungible_token(name = "Token", symbol = "TKN", decimals = 18)]
+/// #[near_bindgen]
+/// struct Contract {}
+///
+/// impl Nep141Hook for Contract {
+///     fn before_transfer(&mut self, transfer: &Nep141Transfer) {
+///         <Self as Freeze>::before_nep141_transfer(transfer);
+///         <Self as Vesting>::before_nep141_transfer(transfer);
+///         <Self as TransferFee>::before_nep141_transfer(transfer);
+///     }
+///
+///     fn after_transfer(&mut self, transfer: &Nep141Transfer, _state: ()) {
+///         self.charge_transfer_fee(transfer);
+///     }
+///
+///     fn before_mint(&mut self, mint: &Nep141Mint) {
+///         <Self as Freeze>::before_nep141_mint(mint);
+///     }
+///
+///     fn before_burn(&mut self, burn: &Nep141Burn) {
+///         <Self as Freeze>::before_nep141_burn(burn);
+///         <Self as Vesting>::before_nep141_burn(burn);
+///     }
+///
+///     fn refundable_amount(&self, refund: &Nep141Transfer) -> u128 {
+///         std::cmp::min(
+///             <Self as Freeze>::refundable_nep141_amount(refund),
+///             <Self as Vesting>::refundable_nep141_amount(refund),
+///         )
+///     }
+/// }
+/// ```
 pub trait Nep141Hook<T: Default = ()> {
     /// Executed before a token transfer is conducted
     ///
@@ -165,6 +279,43 @@ pub trait Nep141Hook<T: Default = ()> {
     ///
     /// Receives the state value returned by `before_transfer`.
     fn after_transfer(&mut self, _transfer: &Nep141Transfer, _state: T) {}
+
+    /// Executed before tokens are minted
+    ///
+    /// May return an optional state value which will be passed along to the
+    /// following `after_mint`.
+    fn before_mint(&mut self, _mint: &Nep141Mint) -> T {
+        Default::default()
+    }
+
+    /// Executed after tokens are minted
+    ///
+    /// Receives the state value returned by `before_mint`.
+    fn after_mint(&mut self, _mint: &Nep141Mint, _state: T) {}
+
+    /// Executed before tokens are burned
+    ///
+    /// May return an optional state value which will be passed along to the
+    /// following `after_burn`.
+    fn before_burn(&mut self, _burn: &Nep141Burn) -> T {
+        Default::default()
+    }
+
+    /// Executed after tokens are burned
+    ///
+    /// Receives the state value returned by `before_burn`.
+    fn after_burn(&mut self, _burn: &Nep141Burn, _state: T) {}
+
+    /// Executed when resolving a transfer call, before the unused tokens are
+    /// refunded (see: [`Nep141Controller::resolve_transfer`])
+    ///
+    /// `refund` moves the tokens from the receiver of the transfer call back
+    /// to its sender. Returns how many of them may be refunded. Refunds do
+    /// not execute the transfer hooks, so this should limit them the same
+    /// way `before_transfer` limits transfers, without failing.
+    fn refundable_amount(&self, refund: &Nep141Transfer) -> u128 {
+        refund.amount
+    }
 }
 
 /// Transfer metadata generic over both types of transfer (`ft_transfer` and
@@ -179,10 +330,34 @@ pub struct Nep141Transfer {
     pub amount: u128,
     /// Optional memo string
     pub memo: Option<String>,
-    /// Message passed to contract located at `receiver_id`
+    /// Message passed to contract located at `receiver_id`. `Some` if and
+    /// only if the transfer is performed by a transfer call
+    /// (`ft_transfer_call`), including in transfer hooks.
     pub msg: Option<String>,
 }
 
+/// Mint metadata
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Nep141Mint {
+    /// Account ID of the recipient of the minted tokens
+    pub account_id: AccountId,
+    /// Minted amount
+    pub amount: u128,
+    /// Optional memo string
+    pub memo: Option<String>,
+}
+
+/// Burn metadata
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Nep141Burn {
+    /// Account ID from which the tokens are burned
+    pub account_id: AccountId,
+    /// Burned amount
+    pub amount: u128,
+    /// Optional memo string
+    pub memo: Option<String>,
+}
+
 impl Nep141Transfer {
     /// Returns `true` if this transfer comes from a `ft_transfer_call`
     /// call, `false` otherwise
@@ -191,6 +366,27 @@ impl Nep141Transfer {
     }
 }
 
+/// A value recorded at a block height (see: [`Nep141SnapshotController`])
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Checkpoint {
+    /// Block height at which the value was recorded
+    pub block_height: u64,
+    /// The value at the end of the block
+    pub value: u128,
+}
+
+/// Worst-case storage usage of a new balance checkpoint record (see:
+/// [`Nep141ControllerInternal::slot_balance_checkpoint`])
+pub fn checkpoint_storage_usage<T: Nep141ControllerInternal + ?Sized>() -> u64 {
+    record_storage_usage(
+        &T::slot_balance_checkpoint(&longest_account_id(), u32::MAX),
+        &Checkpoint {
+            block_height: 0,
+            value: 0,
+        },
+    )
+}
+
 /// Internal functions for [`Nep141Controller`]. Using these methods may result in unexpected behavior.
 pub trait Nep141ControllerInternal {
     /// Root storage slot
@@ -207,9 +403,524 @@ pub trait Nep141ControllerInternal {
     fn slot_total_supply() -> Slot<u128> {
         Self::root().field(StorageKey::TotalSupply)
     }
+
+    /// Slot for the amount of `owner_id`'s tokens that `spender_id` may
+    /// transfer
+    fn slot_allowance(owner_id: &AccountId, spender_id: &AccountId) -> Slot<u128> {
+        Self::root().field(StorageKey::Allowance(owner_id.clone(), spender_id.clone()))
+    }
+
+    /// Returns `true` if balance and total supply checkpoints should be
+    /// recorded on every change (see: [`Nep141SnapshotController`]). By
+    /// default, no checkpoints are recorded. Checkpoints are not paid for by
+    /// the controller, so callers should charge for the storage they write
+    /// (e.g. using [`crate::standard::nep145::charge_storage_fee`]).
+    fn record_checkpoints() -> bool {
+        false
+    }
+
+    /// Slot for the number of balance checkpoints of an account
+    fn slot_balance_checkpoint_count(account_id: &AccountId) -> Slot<u32> {
+        Self::root().field(StorageKey::BalanceCheckpointCount(account_id.clone()))
+    }
+
+    /// Slot for the balance checkpoint of an account at `index`
+    fn slot_balance_checkpoint(account_id: &AccountId, index: u32) -> Slot<Checkpoint> {
+        Self::root().field(StorageKey::BalanceCheckpoint(account_id.clone(), index))
+    }
+
+    /// Slot for the number of total supply checkpoints
+    fn slot_total_supply_checkpoint_count() -> Slot<u32> {
+        Self::root().field(StorageKey::TotalSupplyCheckpointCount)
+    }
+
+    /// Slot for the total supply checkpoint at `index`
+    fn slot_total_supply_checkpoint(index: u32) -> Slot<Checkpoint> {
+        Self::root().field(StorageKey::TotalSupplyCheckpoint(index))
+    }
+
+    /// Gas attached to the `ft_resolve_transfer` callback of a transfer call.
+    fn gas_for_resolve_transfer() -> Gas {
+        GAS_FOR_RESOLVE_TRANSFER
+    }
+
+    /// Gas reserved by a transfer call for its own execution and the
+    /// `ft_resolve_transfer` callback, so it must be at least
+    /// [`Nep141ControllerInternal::gas_for_resolve_transfer`]. The rest of
+    /// the gas allowance is attached to the `ft_on_transfer` call on the
+    /// receiver, which also receives all of the gas left unused by the
+    /// transfer call.
+    fn gas_for_ft_transfer_call() -> Gas {
+        GAS_FOR_FT_TRANSFER_CALL
+    }
+
+    /// Returns `true` if `account_id` may hold a balance. Deposits and
+    /// transfers to accounts that may not hold a balance fail. By default,
+    /// all accounts may hold a balance.
+    fn is_account_registered(_account_id: &AccountId) -> bool {
+        true
+    }
+
+    /// Maximum total supply. Deposits (e.g. mints) that would raise the
+    /// total supply above it fail. By default, there is no maximum.
+    fn max_supply() -> Option<u128> {
+        None
+    }
+
+    /// Performs a transfer (`f`), executing any transfer hooks around it. By
+    /// default, no hooks are executed. The derive macro wires this up to the
+    /// [`Nep141Hook`] transfer hooks unless `no_hooks` is specified. If `f`
+    /// fails, the `after_transfer` hook is not executed.
+    fn hook_transfer<E>(
+        contract: &mut Self,
+        _transfer: &Nep141Transfer,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        f(contract)
+    }
+
+    /// Performs a mint (`f`), executing any mint hooks around it. By default,
+    /// no hooks are executed. The derive macro wires this up to the
+    /// [`Nep141Hook`] mint hooks unless `no_hooks` is specified. If `f`
+    /// fails, the `after_mint` hook is not executed.
+    fn hook_mint<E>(
+        contract: &mut Self,
+        _mint: &Nep141Mint,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        f(contract)
+    }
+
+    /// Returns how many of the tokens of `refund` may be refunded when
+    /// resolving a transfer call (see:
+    /// [`Nep141Controller::resolve_transfer`]). By default, all of them may
+    /// be refunded. The derive macro wires this up to
+    /// [`Nep141Hook::refundable_amount`] unless `no_hooks` is specified.
+    fn refundable_amount(_contract: &Self, refund: &Nep141Transfer) -> u128 {
+        refund.amount
+    }
+
+    /// Performs a burn (`f`), executing any burn hooks around it. By default,
+    /// no hooks are executed. The derive macro wires this up to the
+    /// [`Nep141Hook`] burn hooks unless `no_hooks` is specified. If `f`
+    /// fails, the `after_burn` hook is not executed.
+    fn hook_burn<E>(
+        contract: &mut Self,
+        _burn: &Nep141Burn,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        f(contract)
+    }
+}
+
+pub mod error {
+    use near_sdk::{AccountId, Gas};
+    use thiserror::Error;
+
+    pub use crate::standard::nep145::error::AccountNotRegisteredError;
+
+    /// Occurs when an account's balance is less than the amount to be
+    /// removed from it
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Balance underflow: account `{account_id}` has a balance of {balance}, which is less than {amount}")]
+    pub struct BalanceUnderflowError {
+        /// The account in question
+        pub account_id: AccountId,
+        /// The account's balance
+        pub balance: u128,
+        /// The amount to be removed
+        pub amount: u128,
+    }
+
+    /// Occurs when an account's balance plus the amount to be added to it
+    /// does not fit in a `u128`
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Balance overflow: account `{account_id}` has a balance of {balance}, to which {amount} cannot be added")]
+    pub struct BalanceOverflowError {
+        /// The account in question
+        pub account_id: AccountId,
+        /// The account's balance
+        pub balance: u128,
+        /// The amount to be added
+        pub amount: u128,
+    }
+
+    /// Occurs when the total supply is less than the amount to be burned
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Total supply underflow: total supply of {total_supply} is less than {amount}")]
+    pub struct TotalSupplyUnderflowError {
+        /// The total supply
+        pub total_supply: u128,
+        /// The amount to be burned
+        pub amount: u128,
+    }
+
+    /// Occurs when the total supply plus the amount to be minted does not
+    /// fit in a `u128`
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Total supply overflow: {amount} cannot be added to total supply of {total_supply}")]
+    pub struct TotalSupplyOverflowError {
+        /// The total supply
+        pub total_supply: u128,
+        /// The amount to be minted
+        pub amount: u128,
+    }
+
+    /// Occurs when the total supply plus the amount to be minted exceeds the
+    /// maximum supply
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Maximum supply exceeded: {amount} cannot be added to total supply of {total_supply} without exceeding the maximum supply of {max_supply}")]
+    pub struct MaxSupplyExceededError {
+        /// The maximum supply
+        pub max_supply: u128,
+        /// The total supply
+        pub total_supply: u128,
+        /// The amount to be minted
+        pub amount: u128,
+    }
+
+    /// Occurs when a transfer call is not allowed enough gas
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("More gas is required: {} gas is required, but only {} gas was allowed", .required.0, .gas_allowance.0)]
+    pub struct InsufficientGasError {
+        /// The minimum required gas
+        pub required: Gas,
+        /// The gas allowed for the call
+        pub gas_allowance: Gas,
+    }
+
+    /// Occurs when the gas reserved for a transfer call does not cover the
+    /// gas for its callback
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Invalid gas configuration: {} gas is reserved for a transfer call, which is less than the {} gas for resolving it", .gas_for_ft_transfer_call.0, .gas_for_resolve_transfer.0)]
+    pub struct InvalidGasConfigurationError {
+        /// The gas reserved for a transfer call
+        pub gas_for_ft_transfer_call: Gas,
+        /// The gas for resolving a transfer call
+        pub gas_for_resolve_transfer: Gas,
+    }
+
+    /// Occurs when a spender's allowance is less than the amount to be
+    /// transferred
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Insufficient allowance: `{spender_id}` may transfer {allowance} of `{owner_id}`'s tokens, which is less than {amount}")]
+    pub struct InsufficientAllowanceError {
+        /// The owner of the tokens
+        pub owner_id: AccountId,
+        /// The spender
+        pub spender_id: AccountId,
+        /// The spender's allowance
+        pub allowance: u128,
+        /// The amount to be transferred
+        pub amount: u128,
+    }
+}
+
+use error::*;
+
+/// Errors that may occur when removing tokens from an account (e.g. burning)
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum WithdrawError {
+    /// The account's balance is too small
+    #[error(transparent)]
+    BalanceUnderflow(#[from] BalanceUnderflowError),
+    /// The total supply is too small
+    #[error(transparent)]
+    TotalSupplyUnderflow(#[from] TotalSupplyUnderflowError),
+}
+
+/// Errors that may occur when adding tokens to an account (e.g. minting)
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DepositError {
+    /// The account is not registered
+    #[error(transparent)]
+    AccountNotRegistered(#[from] AccountNotRegisteredError),
+    /// The account's balance is too large
+    #[error(transparent)]
+    BalanceOverflow(#[from] BalanceOverflowError),
+    /// The total supply is too large
+    #[error(transparent)]
+    TotalSupplyOverflow(#[from] TotalSupplyOverflowError),
+    /// The total supply would exceed the maximum supply
+    #[error(transparent)]
+    MaxSupplyExceeded(#[from] MaxSupplyExceededError),
+}
+
+/// Errors that may occur when transferring tokens between accounts
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TransferError {
+    /// The receiver is not registered
+    #[error(transparent)]
+    AccountNotRegistered(#[from] AccountNotRegisteredError),
+    /// The sender's balance is too small
+    #[error(transparent)]
+    BalanceUnderflow(#[from] BalanceUnderflowError),
+    /// The receiver's balance is too large
+    #[error(transparent)]
+    BalanceOverflow(#[from] BalanceOverflowError),
+}
+
+/// Errors that may occur when initiating a transfer call
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TransferCallError {
+    /// Not enough gas was allowed
+    #[error(transparent)]
+    InsufficientGas(#[from] InsufficientGasError),
+    /// The gas for resolving transfer calls exceeds the gas reserved for
+    /// them
+    #[error(transparent)]
+    InvalidGasConfiguration(#[from] InvalidGasConfigurationError),
+    /// The transfer failed
+    #[error(transparent)]
+    Transfer(#[from] TransferError),
+}
+
+/// Errors that may occur when transferring tokens on behalf of their owner
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TransferFromError {
+    /// The spender's allowance is too small
+    #[error(transparent)]
+    InsufficientAllowance(#[from] InsufficientAllowanceError),
+    /// The transfer failed
+    #[error(transparent)]
+    Transfer(#[from] TransferError),
+}
+
+fn check_registered<T: Nep141ControllerInternal + ?Sized>(
+    account_id: &AccountId,
+) -> Result<(), AccountNotRegisteredError> {
+    if T::is_account_registered(account_id) {
+        Ok(())
+    } else {
+        Err(AccountNotRegisteredError {
+            account_id: account_id.clone(),
+        })
+    }
+}
+
+fn write_balance<T: Nep141ControllerInternal + ?Sized>(account_id: &AccountId, balance: u128) {
+    T::slot_account(account_id).write(&balance);
+
+    if T::record_checkpoints() {
+        push_checkpoint(
+            T::slot_balance_checkpoint_count(account_id),
+            |index| T::slot_balance_checkpoint(account_id, index),
+            balance,
+        );
+    }
+}
+
+fn write_total_supply<T: Nep141ControllerInternal + ?Sized>(total_supply: u128) {
+    T::slot_total_supply().write(&total_supply);
+
+    if T::record_checkpoints() {
+        push_checkpoint(
+            T::slot_total_supply_checkpoint_count(),
+            T::slot_total_supply_checkpoint,
+            total_supply,
+        );
+    }
+}
+
+/// Moves `amount` tokens between accounts without checking whether the
+/// receiver is registered.
+fn move_balance<T: Nep141ControllerInternal>(
+    sender_account_id: &AccountId,
+    receiver_account_id: &AccountId,
+    amount: u128,
+) -> Result<(), TransferError> {
+    let sender_balance = T::balance_of(sender_account_id);
+    let sender_balance =
+        sender_balance
+            .checked_sub(amount)
+            .ok_or_else(|| BalanceUnderflowError {
+                account_id: sender_account_id.clone(),
+                balance: sender_balance,
+                amount,
+            })?;
+
+    let receiver_balance = T::balance_of(receiver_account_id);
+    let receiver_balance =
+        receiver_balance
+            .checked_add(amount)
+            .ok_or_else(|| BalanceOverflowError {
+                account_id: receiver_account_id.clone(),
+                balance: receiver_balance,
+                amount,
+            })?;
+
+    write_balance::<T>(sender_account_id, sender_balance);
+    write_balance::<T>(receiver_account_id, receiver_balance);
+
+    Ok(())
+}
+
+/// Records `value` for the current block, overwriting the last checkpoint if
+/// it was recorded in the same block.
+fn push_checkpoint(
+    mut slot_count: Slot<u32>,
+    slot_checkpoint: impl Fn(u32) -> Slot<Checkpoint>,
+    value: u128,
+) {
+    let block_height = env::block_height();
+    let count = slot_count.read().unwrap_or(0);
+
+    let checkpoint = Checkpoint {
+        block_height,
+        value,
+    };
+
+    if let Some(mut last) = count.checked_sub(1).map(&slot_checkpoint) {
+        if last
+            .read()
+            .map_or(false, |c| c.block_height == block_height)
+        {
+            last.write(&checkpoint);
+            return;
+        }
+    }
+
+    slot_checkpoint(count).write(&checkpoint);
+    slot_count.write(&(count + 1));
+}
+
+/// Binary search for the value of the last checkpoint recorded at or before
+/// `block_height`. Returns 0 if there is no such checkpoint.
+fn find_checkpoint(
+    slot_count: Slot<u32>,
+    slot_checkpoint: impl Fn(u32) -> Slot<Checkpoint>,
+    block_height: u64,
+) -> u128 {
+    let read = |index| {
+        slot_checkpoint(index)
+            .read()
+            .unwrap_or_else(|| env::panic_str("Missing checkpoint"))
+    };
+
+    // Index of the first checkpoint after `block_height`
+    let mut low = 0;
+    let mut high = slot_count.read().unwrap_or(0);
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if read(mid).block_height <= block_height {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low.checked_sub(1).map_or(0, |index| read(index).value)
+}
+
+/// Checks that all of `transfers` would succeed if applied in order, without
+/// changing any balances.
+fn validate_batch<T: Nep141ControllerInternal>(
+    transfers: &[Nep141Transfer],
+) -> Result<(), TransferError> {
+    let mut balances = HashMap::<&AccountId, u128>::new();
+
+    for transfer in transfers {
+        check_registered::<T>(&transfer.receiver_id)?;
+
+        let sender_balance = *balances
+            .entry(&transfer.sender_id)
+            .or_insert_with(|| T::balance_of(&transfer.sender_id));
+        let sender_balance =
+            sender_balance
+                .checked_sub(transfer.amount)
+                .ok_or_else(|| BalanceUnderflowError {
+                    account_id: transfer.sender_id.clone(),
+                    balance: sender_balance,
+                    amount: transfer.amount,
+                })?;
+        balances.insert(&transfer.sender_id, sender_balance);
+
+        let receiver_balance = *balances
+            .entry(&transfer.receiver_id)
+            .or_insert_with(|| T::balance_of(&transfer.receiver_id));
+        let receiver_balance = receiver_balance
+            .checked_add(transfer.amount)
+            .ok_or_else(|| BalanceOverflowError {
+                account_id: transfer.receiver_id.clone(),
+                balance: receiver_balance,
+                amount: transfer.amount,
+            })?;
+        balances.insert(&transfer.receiver_id, receiver_balance);
+    }
+
+    Ok(())
+}
+
+/// Performs the `remaining` transfers of a batch, nesting the hooks of each
+/// transfer inside those of the previous one. Once all transfers are
+/// performed, a single event is emitted for the whole `batch`, before the
+/// `after_transfer` hooks.
+fn hooked_transfer_batch<T: Nep141ControllerInternal>(
+    contract: &mut T,
+    batch: &[Nep141Transfer],
+    remaining: &[Nep141Transfer],
+) -> Result<(), TransferError> {
+    let (transfer, remaining) = match remaining.split_first() {
+        Some(next) => next,
+        None => {
+            Nep141Event::FtTransfer(
+                batch
+                    .iter()
+                    .map(|transfer| event::FtTransferData {
+                        old_owner_id: transfer.sender_id.clone(),
+                        new_owner_id: transfer.receiver_id.clone(),
+                        amount: transfer.amount.into(),
+                        memo: transfer.memo.clone(),
+                    })
+                    .collect(),
+            )
+            .emit();
+
+            return Ok(());
+        }
+    };
+
+    T::hook_transfer(contract, transfer, |contract| {
+        contract.try_transfer_unchecked(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            transfer.amount,
+        )?;
+
+        hooked_transfer_batch(contract, batch, remaining)
+    })
+}
+
+/// Performs `transfer` with event emission, executing transfer hooks around
+/// it.
+fn hooked_transfer<T: Nep141ControllerInternal>(
+    contract: &mut T,
+    transfer: Nep141Transfer,
+) -> Result<(), TransferError> {
+    T::hook_transfer(contract, &transfer, |contract| {
+        contract.try_transfer_unchecked(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            transfer.amount,
+        )?;
+
+        Nep141Event::FtTransfer(vec![event::FtTransferData {
+            old_owner_id: transfer.sender_id.clone(),
+            new_owner_id: transfer.receiver_id.clone(),
+            amount: transfer.amount.into(),
+            memo: transfer.memo.clone(),
+        }])
+        .emit();
+
+        Ok(())
+    })
 }
 
 /// Non-public implementations of functions for managing a fungible token.
+///
+/// Functions prefixed with `try_` return an error instead of panicking. The
+/// other functions panic with the error message.
 pub trait Nep141Controller {
     /// Get the balance of an account. Returns 0 if the account does not exist.
     fn balance_of(account_id: &AccountId) -> u128;
@@ -217,32 +928,65 @@ pub trait Nep141Controller {
     /// Get the total circulating supply of the token.
     fn total_supply() -> u128;
 
+    /// Removes tokens from an account and decreases total supply. No event
+    /// emission. Fails if the current balance of `account_id` is less than
+    /// `amount` or if `total_supply` is less than `amount`.
+    fn try_withdraw_unchecked(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), WithdrawError>;
+
     /// Removes tokens from an account and decreases total supply. No event
     /// emission.
     ///
     /// # Panics
     ///
-    /// Panics if the current balance of `account_id` is less than `amount` or
-    /// if `total_supply` is less than `amount`.
+    /// See: `Nep141Controller::try_withdraw_unchecked`
     fn withdraw_unchecked(&mut self, account_id: &AccountId, amount: u128);
 
+    /// Increases the token balance of an account. Updates total supply. No
+    /// event emission. Fails if the balance of `account_id` plus `amount`
+    /// >= `u128::MAX`, if the total supply plus `amount` >= `u128::MAX` or
+    /// exceeds the maximum supply (see
+    /// [`Nep141ControllerInternal::max_supply`]), or if `account_id` is not
+    /// registered (see
+    /// [`Nep141ControllerInternal::is_account_registered`]).
+    fn try_deposit_unchecked(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), DepositError>;
+
     /// Increases the token balance of an account. Updates total supply. No
     /// event emission,
     ///
     /// # Panics
     ///
-    /// Panics if the balance of `account_id` plus `amount` >= `u128::MAX`, or
-    /// if the total supply plus `amount` >= `u128::MAX`.
+    /// See: `Nep141Controller::try_deposit_unchecked`
     fn deposit_unchecked(&mut self, account_id: &AccountId, amount: u128);
 
+    /// Decreases the balance of `sender_account_id` by `amount` and increases
+    /// the balance of `receiver_account_id` by the same. No change to total
+    /// supply. No event emission. Fails if the balance of
+    /// `sender_account_id` < `amount`, if the balance of
+    /// `receiver_account_id` plus `amount` >= `u128::MAX`, or if
+    /// `receiver_account_id` is not registered (see
+    /// [`Nep141ControllerInternal::is_account_registered`]).
+    fn try_transfer_unchecked(
+        &mut self,
+        sender_account_id: &AccountId,
+        receiver_account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), TransferError>;
+
     /// Decreases the balance of `sender_account_id` by `amount` and increases
     /// the balance of `receiver_account_id` by the same. No change to total
     /// supply. No event emission.
     ///
     /// # Panics
     ///
-    /// Panics if the balance of `sender_account_id` < `amount` or if the
-    /// balance of `receiver_account_id` plus `amount` >= `u128::MAX`.
+    /// See: `Nep141Controller::try_transfer_unchecked`
     fn transfer_unchecked(
         &mut self,
         sender_account_id: &AccountId,
@@ -250,11 +994,26 @@ pub trait Nep141Controller {
         amount: u128,
     );
 
-    /// Performs an NEP-141 token transfer, with event emission.
+    /// Performs an NEP-141 token transfer, with event emission. Executes
+    /// transfer hooks (see: [`Nep141ControllerInternal::hook_transfer`]). Use
+    /// `Nep141Controller::try_transfer_unchecked` to move tokens without
+    /// executing hooks.
+    ///
+    /// See: `Nep141Controller::try_transfer_unchecked`
+    fn try_transfer(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), TransferError>;
+
+    /// Performs an NEP-141 token transfer, with event emission. Executes
+    /// transfer hooks (see: [`Nep141ControllerInternal::hook_transfer`]).
     ///
     /// # Panics
     ///
-    /// See: `Nep141Controller::transfer_unchecked`
+    /// See: `Nep141Controller::try_transfer`
     fn transfer(
         &mut self,
         sender_account_id: AccountId,
@@ -263,27 +1022,95 @@ pub trait Nep141Controller {
         memo: Option<String>,
     );
 
-    /// Performs an NEP-141 token mint, with event emission.
+    /// Performs many NEP-141 token transfers, emitting a single
+    /// `ft_transfer` event for the whole batch. Executes transfer hooks for
+    /// each item (see: [`Nep141ControllerInternal::hook_transfer`]). The
+    /// `after_transfer` hooks are executed after the event, in reverse
+    /// order, so that any events they emit (e.g. transfer fees) follow it. No
+    /// event is emitted if `transfers` is empty.
+    ///
+    /// The whole batch is validated before any balance is changed, so a
+    /// batch that would fail (e.g. because a sender's balance runs out
+    /// partway through) has no effect. Transfers are then applied in order.
+    /// If one still fails, e.g. because a hook changed a balance, the
+    /// transfers before it are not reverted and no event is emitted, so the
+    /// caller should panic to roll back the batch.
+    ///
+    /// See: `Nep141Controller::try_transfer_unchecked`
+    fn try_transfer_batch(&mut self, transfers: Vec<Nep141Transfer>) -> Result<(), TransferError>;
+
+    /// Performs many NEP-141 token transfers, emitting a single
+    /// `ft_transfer` event for the whole batch.
+    ///
+    /// # Panics
+    ///
+    /// See: `Nep141Controller::try_transfer_batch`
+    fn transfer_batch(&mut self, transfers: Vec<Nep141Transfer>);
+
+    /// Performs an NEP-141 token mint, with event emission. Executes mint
+    /// hooks (see: [`Nep141ControllerInternal::hook_mint`]).
+    ///
+    /// See: `Nep141Controller::try_deposit_unchecked`
+    fn try_mint(
+        &mut self,
+        account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), DepositError>;
+
+    /// Performs an NEP-141 token mint, with event emission. Executes mint
+    /// hooks (see: [`Nep141ControllerInternal::hook_mint`]).
     ///
     /// # Panics
     ///
-    /// See: `Nep141Controller::deposit_unchecked`
+    /// See: `Nep141Controller::try_mint`
     fn mint(&mut self, account_id: AccountId, amount: u128, memo: Option<String>);
 
-    /// Performs an NEP-141 token burn, with event emission.
+    /// Performs an NEP-141 token burn, with event emission. Executes burn
+    /// hooks (see: [`Nep141ControllerInternal::hook_burn`]).
+    ///
+    /// See: `Nep141Controller::try_withdraw_unchecked`
+    fn try_burn(
+        &mut self,
+        account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), WithdrawError>;
+
+    /// Performs an NEP-141 token burn, with event emission. Executes burn
+    /// hooks (see: [`Nep141ControllerInternal::hook_burn`]).
     ///
     /// # Panics
     ///
-    /// See: `Nep141Controller::withdraw_unchecked`
+    /// See: `Nep141Controller::try_burn`
     fn burn(&mut self, account_id: AccountId, amount: u128, memo: Option<String>);
 
+    /// Performs an NEP-141 token transfer call, with event emission. Executes
+    /// transfer hooks, passing `msg` along with the transfer. Fails if
+    /// `gas_allowance` is less than
+    /// [`Nep141ControllerInternal::gas_for_ft_transfer_call`], or if that is
+    /// less than [`Nep141ControllerInternal::gas_for_resolve_transfer`].
+    ///
+    /// The receiver's `ft_on_transfer` call is attached `gas_allowance`
+    /// minus [`Nep141ControllerInternal::gas_for_ft_transfer_call`], plus all
+    /// of the gas left unused by the transfer call.
+    ///
+    /// See also: `Nep141Controller::try_transfer`
+    fn try_transfer_call(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+        msg: String,
+        gas_allowance: Gas,
+    ) -> Result<Promise, TransferCallError>;
+
     /// Performs an NEP-141 token transfer call, with event emission.
     ///
     /// # Panics
     ///
-    /// Panics if `gas_allowance` < `GAS_FOR_FT_TRANSFER_CALL`.
-    ///
-    /// See also: `Nep141Controller::transfer`
+    /// See: `Nep141Controller::try_transfer_call`
     fn transfer_call(
         &mut self,
         sender_account_id: AccountId,
@@ -294,7 +1121,22 @@ pub trait Nep141Controller {
         gas_allowance: Gas,
     ) -> Promise;
 
-    /// Resolves an NEP-141 `ft_transfer_call` promise chain.
+    /// Resolves an NEP-141 `ft_transfer_call` promise chain, following the
+    /// reference implementation:
+    ///
+    /// - The unused amount returned by `ft_on_transfer` (the full amount if
+    ///   the call failed or returned an invalid value) is refunded from the
+    ///   receiver to the sender, up to the receiver's current balance and to
+    ///   [`Nep141ControllerInternal::refundable_amount`]. The refund does not
+    ///   execute transfer hooks, and it is made even if the
+    ///   sender is no longer registered (see
+    ///   [`Nep141ControllerInternal::is_account_registered`]).
+    /// - If the receiver is no longer registered, the unused amount is
+    ///   burned from the receiver instead, with memo `"refund"`. The burn
+    ///   executes burn hooks (see: `Nep141Controller::burn`).
+    ///
+    /// Returns the amount of tokens used, i.e. `amount` minus the amount
+    /// refunded to the sender. Burned refunds count as used.
     fn resolve_transfer(
         &mut self,
         sender_id: AccountId,
@@ -312,40 +1154,100 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
         Self::slot_total_supply().read().unwrap_or(0)
     }
 
-    fn withdraw_unchecked(&mut self, account_id: &AccountId, amount: u128) {
+    fn try_withdraw_unchecked(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), WithdrawError> {
         if amount != 0 {
             let balance = Self::balance_of(account_id);
-            if let Some(balance) = balance.checked_sub(amount) {
-                Self::slot_account(account_id).write(&balance);
-            } else {
-                env::panic_str("Balance underflow");
-            }
+            let balance = balance
+                .checked_sub(amount)
+                .ok_or_else(|| BalanceUnderflowError {
+                    account_id: account_id.clone(),
+                    balance,
+                    amount,
+                })?;
 
             let total_supply = Self::total_supply();
-            if let Some(total_supply) = total_supply.checked_sub(amount) {
-                Self::slot_total_supply().write(&total_supply);
-            } else {
-                env::panic_str("Total supply underflow");
-            }
+            let total_supply =
+                total_supply
+                    .checked_sub(amount)
+                    .ok_or(TotalSupplyUnderflowError {
+                        total_supply,
+                        amount,
+                    })?;
+
+            write_balance::<Self>(account_id, balance);
+            write_total_supply::<Self>(total_supply);
         }
+
+        Ok(())
     }
 
-    fn deposit_unchecked(&mut self, account_id: &AccountId, amount: u128) {
+    fn withdraw_unchecked(&mut self, account_id: &AccountId, amount: u128) {
+        self.try_withdraw_unchecked(account_id, amount)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    fn try_deposit_unchecked(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), DepositError> {
+        check_registered::<Self>(account_id)?;
+
         if amount != 0 {
             let balance = Self::balance_of(account_id);
-            if let Some(balance) = balance.checked_add(amount) {
-                Self::slot_account(account_id).write(&balance);
-            } else {
-                env::panic_str("Balance overflow");
-            }
+            let balance = balance
+                .checked_add(amount)
+                .ok_or_else(|| BalanceOverflowError {
+                    account_id: account_id.clone(),
+                    balance,
+                    amount,
+                })?;
 
             let total_supply = Self::total_supply();
-            if let Some(total_supply) = total_supply.checked_add(amount) {
-                Self::slot_total_supply().write(&total_supply);
-            } else {
-                env::panic_str("Total supply overflow");
+            let new_total_supply =
+                total_supply
+                    .checked_add(amount)
+                    .ok_or(TotalSupplyOverflowError {
+                        total_supply,
+                        amount,
+                    })?;
+
+            if let Some(max_supply) = Self::max_supply() {
+                if new_total_supply > max_supply {
+                    return Err(MaxSupplyExceededError {
+                        max_supply,
+                        total_supply,
+                        amount,
+                    }
+                    .into());
+                }
             }
+
+            write_balance::<Self>(account_id, balance);
+            write_total_supply::<Self>(new_total_supply);
         }
+
+        Ok(())
+    }
+
+    fn deposit_unchecked(&mut self, account_id: &AccountId, amount: u128) {
+        self.try_deposit_unchecked(account_id, amount)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    fn try_transfer_unchecked(
+        &mut self,
+        sender_account_id: &AccountId,
+        receiver_account_id: &AccountId,
+        amount: u128,
+    ) -> Result<(), TransferError> {
+        check_registered::<Self>(receiver_account_id)?;
+
+        move_balance::<Self>(sender_account_id, receiver_account_id, amount)
     }
 
     fn transfer_unchecked(
@@ -354,19 +1256,27 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
         receiver_account_id: &AccountId,
         amount: u128,
     ) {
-        let sender_balance = Self::balance_of(sender_account_id);
-
-        if let Some(sender_balance) = sender_balance.checked_sub(amount) {
-            let receiver_balance = Self::balance_of(receiver_account_id);
-            if let Some(receiver_balance) = receiver_balance.checked_add(amount) {
-                Self::slot_account(sender_account_id).write(&sender_balance);
-                Self::slot_account(receiver_account_id).write(&receiver_balance);
-            } else {
-                env::panic_str("Receiver balance overflow");
-            }
-        } else {
-            env::panic_str("Sender balance underflow");
-        }
+        self.try_transfer_unchecked(sender_account_id, receiver_account_id, amount)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    fn try_transfer(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), TransferError> {
+        hooked_transfer(
+            self,
+            Nep141Transfer {
+                sender_id: sender_account_id,
+                receiver_id: receiver_account_id,
+                amount,
+                memo,
+                msg: None,
+            },
+        )
     }
 
     fn transfer(
@@ -376,40 +1286,88 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
         amount: u128,
         memo: Option<String>,
     ) {
-        self.transfer_unchecked(&sender_account_id, &receiver_account_id, amount);
+        self.try_transfer(sender_account_id, receiver_account_id, amount, memo)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
 
-        Nep141Event::FtTransfer(vec![event::FtTransferData {
-            old_owner_id: sender_account_id,
-            new_owner_id: receiver_account_id,
-            amount: amount.into(),
+    fn try_transfer_batch(&mut self, transfers: Vec<Nep141Transfer>) -> Result<(), TransferError> {
+        validate_batch::<Self>(&transfers)?;
+
+        if transfers.is_empty() {
+            return Ok(());
+        }
+
+        hooked_transfer_batch(self, &transfers, &transfers)
+    }
+
+    fn transfer_batch(&mut self, transfers: Vec<Nep141Transfer>) {
+        self.try_transfer_batch(transfers)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
+
+    fn try_mint(
+        &mut self,
+        account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), DepositError> {
+        let mint = Nep141Mint {
+            account_id,
+            amount,
             memo,
-        }])
-        .emit();
+        };
+
+        Self::hook_mint(self, &mint, |contract| {
+            contract.try_deposit_unchecked(&mint.account_id, mint.amount)?;
+
+            Nep141Event::FtMint(vec![event::FtMintData {
+                owner_id: mint.account_id.clone(),
+                amount: mint.amount.into(),
+                memo: mint.memo.clone(),
+            }])
+            .emit();
+
+            Ok(())
+        })
     }
 
     fn mint(&mut self, account_id: AccountId, amount: u128, memo: Option<String>) {
-        self.deposit_unchecked(&account_id, amount);
+        self.try_mint(account_id, amount, memo)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+    }
 
-        Nep141Event::FtMint(vec![event::FtMintData {
-            owner_id: account_id,
-            amount: amount.into(),
+    fn try_burn(
+        &mut self,
+        account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), WithdrawError> {
+        let burn = Nep141Burn {
+            account_id,
+            amount,
             memo,
-        }])
-        .emit();
+        };
+
+        Self::hook_burn(self, &burn, |contract| {
+            contract.try_withdraw_unchecked(&burn.account_id, burn.amount)?;
+
+            Nep141Event::FtBurn(vec![event::FtBurnData {
+                owner_id: burn.account_id.clone(),
+                amount: burn.amount.into(),
+                memo: burn.memo.clone(),
+            }])
+            .emit();
+
+            Ok(())
+        })
     }
 
     fn burn(&mut self, account_id: AccountId, amount: u128, memo: Option<String>) {
-        self.withdraw_unchecked(&account_id, amount);
-
-        Nep141Event::FtBurn(vec![event::FtBurnData {
-            owner_id: account_id,
-            amount: amount.into(),
-            memo,
-        }])
-        .emit();
+        self.try_burn(account_id, amount, memo)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
     }
 
-    fn transfer_call(
+    fn try_transfer_call(
         &mut self,
         sender_account_id: AccountId,
         receiver_account_id: AccountId,
@@ -417,33 +1375,72 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
         memo: Option<String>,
         msg: String,
         gas_allowance: Gas,
-    ) -> Promise {
-        require!(
-            gas_allowance >= GAS_FOR_FT_TRANSFER_CALL,
-            MORE_GAS_FAIL_MESSAGE,
-        );
+    ) -> Result<Promise, TransferCallError> {
+        let gas_for_ft_transfer_call = Self::gas_for_ft_transfer_call();
+        let gas_for_resolve_transfer = Self::gas_for_resolve_transfer();
 
-        self.transfer(
-            sender_account_id.clone(),
-            receiver_account_id.clone(),
-            amount,
-            memo,
-        );
+        if gas_for_ft_transfer_call < gas_for_resolve_transfer {
+            return Err(InvalidGasConfigurationError {
+                gas_for_ft_transfer_call,
+                gas_for_resolve_transfer,
+            }
+            .into());
+        }
+
+        if gas_allowance < gas_for_ft_transfer_call {
+            return Err(InsufficientGasError {
+                required: gas_for_ft_transfer_call,
+                gas_allowance,
+            }
+            .into());
+        }
 
-        let receiver_gas = gas_allowance
-            .0
-            .checked_sub(GAS_FOR_FT_TRANSFER_CALL.0) // TODO: Double-check this math. Should this be GAS_FOR_RESOLVE_TRANSFER? If not, this checked_sub call is superfluous given the require!() at the top of this function.
-            .unwrap_or_else(|| env::panic_str("Prepaid gas overflow"));
+        hooked_transfer(
+            self,
+            Nep141Transfer {
+                sender_id: sender_account_id.clone(),
+                receiver_id: receiver_account_id.clone(),
+                amount,
+                memo,
+                msg: Some(msg.clone()),
+            },
+        )?;
 
-        // Initiating receiver's call and the callback
-        ext_nep141_receiver::ext(receiver_account_id.clone())
-            .with_static_gas(receiver_gas.into())
+        // The rest of the reserved gas is left for this call's own execution
+        let receiver_gas = gas_allowance - gas_for_ft_transfer_call;
+
+        // Initiating receiver's call and the callback. All unused gas goes to
+        // the receiver's call.
+        Ok(ext_nep141_receiver::ext(receiver_account_id.clone())
+            .with_static_gas(receiver_gas)
+            .with_unused_gas_weight(1)
             .ft_on_transfer(sender_account_id.clone(), amount.into(), msg)
             .then(
                 ext_nep141_resolver::ext(env::current_account_id())
-                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .with_static_gas(gas_for_resolve_transfer)
+                    .with_unused_gas_weight(0)
                     .ft_resolve_transfer(sender_account_id, receiver_account_id, amount.into()),
-            )
+            ))
+    }
+
+    fn transfer_call(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+        msg: String,
+        gas_allowance: Gas,
+    ) -> Promise {
+        self.try_transfer_call(
+            sender_account_id,
+            receiver_account_id,
+            amount,
+            memo,
+            msg,
+            gas_allowance,
+        )
+        .unwrap_or_else(|e| env::panic_str(&e.to_string()))
     }
 
     fn resolve_transfer(
@@ -466,21 +1463,172 @@ impl<T: Nep141ControllerInternal> Nep141Controller for T {
             PromiseResult::Failed => amount,
         };
 
-        let refunded_amount = if unused_amount > 0 {
-            let receiver_balance = Self::balance_of(&receiver_id);
-            if receiver_balance > 0 {
-                let refund_amount = std::cmp::min(receiver_balance, unused_amount);
-                self.transfer(receiver_id, sender_id, refund_amount, None);
-                refund_amount
-            } else {
-                0
-            }
-        } else {
-            0
+        let refund = Nep141Transfer {
+            sender_id: receiver_id.clone(),
+            receiver_id: sender_id.clone(),
+            amount: std::cmp::min(Self::balance_of(&receiver_id), unused_amount),
+            memo: None,
+            msg: None,
         };
+        let refund_amount = std::cmp::min(refund.amount, Self::refundable_amount(self, &refund));
+
+        if refund_amount == 0 {
+            return amount;
+        }
+
+        if Self::is_account_registered(&receiver_id) {
+            move_balance::<Self>(&receiver_id, &sender_id, refund_amount)
+                .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+
+            Nep141Event::FtTransfer(vec![event::FtTransferData {
+                old_owner_id: receiver_id,
+                new_owner_id: sender_id,
+                amount: refund_amount.into(),
+                memo: None,
+            }])
+            .emit();
+
+            // Used amount
+            amount - refund_amount
+        } else {
+            // The receiver's account was deleted, so the refund is burned
+            self.burn(receiver_id, refund_amount, Some("refund".to_string()));
+
+            amount
+        }
+    }
+}
+
+/// Non-public implementations of functions for the optional NEP-141
+/// allowance extension, which allows an owner to authorize another account (a
+/// spender) to transfer up to a certain amount of the owner's tokens.
+pub trait Nep141AllowanceController {
+    /// Get the amount of `owner_id`'s tokens that `spender_id` may transfer.
+    fn allowance(owner_id: &AccountId, spender_id: &AccountId) -> u128;
+
+    /// Sets the amount of `owner_id`'s tokens that `spender_id` may transfer,
+    /// replacing any previous allowance. No event emission.
+    fn set_allowance_unchecked(
+        &mut self,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+        amount: u128,
+    );
+
+    /// Sets the amount of `owner_id`'s tokens that `spender_id` may transfer,
+    /// with event emission.
+    fn approve(
+        &mut self,
+        owner_id: AccountId,
+        spender_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    );
+
+    /// Transfers `amount` of `owner_id`'s tokens on behalf of `spender_id`,
+    /// decreasing the allowance by the same, with event emission. Fails if
+    /// the allowance of `spender_id` < `amount`.
+    ///
+    /// See also: `Nep141Controller::try_transfer`
+    fn try_transfer_from(
+        &mut self,
+        spender_id: &AccountId,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), TransferFromError>;
+
+    /// Transfers `amount` of `owner_id`'s tokens on behalf of `spender_id`,
+    /// decreasing the allowance by the same, with event emission.
+    ///
+    /// # Panics
+    ///
+    /// See: `Nep141AllowanceController::try_transfer_from`
+    fn transfer_from(
+        &mut self,
+        spender_id: &AccountId,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    );
+}
+
+impl<T: Nep141ControllerInternal> Nep141AllowanceController for T {
+    fn allowance(owner_id: &AccountId, spender_id: &AccountId) -> u128 {
+        Self::slot_allowance(owner_id, spender_id)
+            .read()
+            .unwrap_or(0)
+    }
+
+    fn set_allowance_unchecked(
+        &mut self,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+        amount: u128,
+    ) {
+        let mut slot = Self::slot_allowance(owner_id, spender_id);
+        if amount == 0 {
+            slot.remove();
+        } else {
+            slot.write(&amount);
+        }
+    }
+
+    fn approve(
+        &mut self,
+        owner_id: AccountId,
+        spender_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) {
+        self.set_allowance_unchecked(&owner_id, &spender_id, amount);
+
+        Nep141AllowanceEvent::FtApprove(vec![event::FtApproveData {
+            owner_id,
+            spender_id,
+            amount: amount.into(),
+            memo,
+        }])
+        .emit();
+    }
+
+    fn try_transfer_from(
+        &mut self,
+        spender_id: &AccountId,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), TransferFromError> {
+        let allowance = Self::allowance(&owner_id, spender_id);
+        let remaining_allowance =
+            allowance
+                .checked_sub(amount)
+                .ok_or_else(|| InsufficientAllowanceError {
+                    owner_id: owner_id.clone(),
+                    spender_id: spender_id.clone(),
+                    allowance,
+                    amount,
+                })?;
+
+        self.try_transfer(owner_id.clone(), receiver_id, amount, memo)?;
+        self.set_allowance_unchecked(&owner_id, spender_id, remaining_allowance);
 
-        // Used amount
-        amount - refunded_amount
+        Ok(())
+    }
+
+    fn transfer_from(
+        &mut self,
+        spender_id: &AccountId,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) {
+        self.try_transfer_from(spender_id, owner_id, receiver_id, amount, memo)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
     }
 }
 
@@ -504,7 +1652,8 @@ pub trait Nep141Receiver {
 #[ext_contract(ext_nep141_resolver)]
 pub trait Nep141Resolver {
     /// Callback, last in `ft_transfer_call` promise chain. Returns the amount
-    /// of tokens refunded to the original sender.
+    /// of tokens used by the receiver, i.e. not refunded to the original
+    /// sender.
     fn ft_resolve_transfer(
         &mut self,
         sender_id: AccountId,
@@ -536,3 +1685,271 @@ pub trait Nep141 {
     /// Returns the amount of tokens controlled by `account_id`
     fn ft_balance_of(&self, account_id: AccountId) -> U128;
 }
+
+/// Externally-accessible interface for the optional NEP-141 allowance
+/// extension.
+#[ext_contract(ext_nep141_allowance)]
+pub trait Nep141Allowance {
+    /// Sets the amount of the predecessor's tokens that `spender_id` may
+    /// transfer, replacing any previous allowance. The attached deposit must
+    /// cover the storage fee of a new allowance; the remainder is refunded.
+    /// The storage fee is refunded when the allowance is set to zero or used
+    /// up.
+    fn ft_approve(&mut self, spender_id: AccountId, amount: U128, memo: Option<String>);
+
+    /// Returns the amount of `owner_id`'s tokens that `spender_id` may
+    /// transfer
+    fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128;
+
+    /// Transfers tokens from `owner_id` to `receiver_id` on behalf of the
+    /// predecessor, decreasing the predecessor's allowance.
+    fn ft_transfer_from(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    );
+}
+
+/// Historical balance lookups for voting-weight tokens and the like.
+/// Lookups are only accurate for blocks after checkpoint recording was
+/// enabled (see: [`Nep141ControllerInternal::record_checkpoints`]).
+pub trait Nep141SnapshotController {
+    /// Balance of `account_id` at the end of block `block_height`.
+    fn balance_of_at(account_id: &AccountId, block_height: u64) -> u128;
+
+    /// Total supply at the end of block `block_height`.
+    fn total_supply_at(block_height: u64) -> u128;
+}
+
+impl<T: Nep141ControllerInternal> Nep141SnapshotController for T {
+    fn balance_of_at(account_id: &AccountId, block_height: u64) -> u128 {
+        find_checkpoint(
+            Self::slot_balance_checkpoint_count(account_id),
+            |index| Self::slot_balance_checkpoint(account_id, index),
+            block_height,
+        )
+    }
+
+    fn total_supply_at(block_height: u64) -> u128 {
+        find_checkpoint(
+            Self::slot_total_supply_checkpoint_count(),
+            Self::slot_total_supply_checkpoint,
+            block_height,
+        )
+    }
+}
+
+/// Optional batch transfer extension to NEP-141
+#[ext_contract(ext_nep141_transfer_batch)]
+pub trait Nep141TransferBatch {
+    /// Transfers `amounts[i]` tokens from the predecessor to
+    /// `receiver_ids[i]` for each `i`. Emits a single `ft_transfer` event.
+    fn ft_transfer_batch(
+        &mut self,
+        receiver_ids: Vec<AccountId>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
+    );
+}
+
+/// Optional snapshot extension to NEP-141
+#[ext_contract(ext_nep141_snapshot)]
+pub trait Nep141Snapshot {
+    /// Balance of `account_id` at the end of block `block_height`.
+    fn ft_balance_of_at(&self, account_id: AccountId, block_height: U64) -> U128;
+
+    /// Total supply at the end of block `block_height`.
+    fn ft_total_supply_at(&self, block_height: U64) -> U128;
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{
+        test_utils::{get_logs, VMContextBuilder},
+        testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig,
+    };
+
+    use super::*;
+
+    struct Contract;
+
+    impl Nep141ControllerInternal for Contract {
+        fn is_account_registered(account_id: &AccountId) -> bool {
+            account_id.as_str() != "deleted"
+        }
+
+        // Resolving a transfer call must not fail because of a hook
+        fn hook_transfer<E>(
+            _contract: &mut Self,
+            _transfer: &Nep141Transfer,
+            _f: impl FnOnce(&mut Self) -> Result<(), E>,
+        ) -> Result<(), E> {
+            panic!("Transfer hook executed");
+        }
+
+        fn hook_burn<E>(
+            contract: &mut Self,
+            _burn: &Nep141Burn,
+            f: impl FnOnce(&mut Self) -> Result<(), E>,
+        ) -> Result<(), E> {
+            env::log_str("burn hook");
+            f(contract)
+        }
+    }
+
+    fn promise_result(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn setup(sender: &str, receiver_balance: u128) -> (Contract, AccountId, AccountId) {
+        setup_accounts(sender, "receiver", receiver_balance)
+    }
+
+    fn setup_accounts(
+        sender: &str,
+        receiver: &str,
+        receiver_balance: u128,
+    ) -> (Contract, AccountId, AccountId) {
+        testing_env!(VMContextBuilder::new().build());
+
+        let mut contract = Contract;
+        let sender: AccountId = sender.parse().unwrap();
+        let receiver: AccountId = receiver.parse().unwrap();
+
+        // Written directly, since the receiver may not be registered
+        write_balance::<Contract>(&receiver, receiver_balance);
+        write_total_supply::<Contract>(receiver_balance);
+
+        (contract, sender, receiver)
+    }
+
+    #[test]
+    fn resolve_transfer_partial_refund() {
+        let (mut contract, sender, receiver) = setup("sender", 100);
+
+        promise_result(PromiseResult::Successful(br#""30""#.to_vec()));
+
+        assert_eq!(
+            contract.resolve_transfer(sender.clone(), receiver.clone(), 100),
+            70,
+        );
+        assert_eq!(Contract::balance_of(&sender), 30);
+        assert_eq!(Contract::balance_of(&receiver), 70);
+        assert_eq!(Contract::total_supply(), 100);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"receiver","new_owner_id":"sender","amount":"30"}]}"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn resolve_transfer_unused_amount_is_capped() {
+        let (mut contract, sender, receiver) = setup("sender", 100);
+
+        promise_result(PromiseResult::Successful(br#""1000""#.to_vec()));
+
+        assert_eq!(contract.resolve_transfer(sender.clone(), receiver, 100), 0);
+        assert_eq!(Contract::balance_of(&sender), 100);
+    }
+
+    fn assert_full_refund(result: PromiseResult) {
+        let (mut contract, sender, receiver) = setup("sender", 100);
+
+        promise_result(result);
+
+        assert_eq!(
+            contract.resolve_transfer(sender.clone(), receiver.clone(), 100),
+            0,
+        );
+        assert_eq!(Contract::balance_of(&sender), 100);
+        assert_eq!(Contract::balance_of(&receiver), 0);
+    }
+
+    #[test]
+    fn resolve_transfer_failed() {
+        assert_full_refund(PromiseResult::Failed);
+    }
+
+    #[test]
+    fn resolve_transfer_invalid_result() {
+        assert_full_refund(PromiseResult::Successful(b"not json".to_vec()));
+    }
+
+    #[test]
+    fn resolve_transfer_receiver_spent_tokens() {
+        // Receiver only holds 40 of the 100 transferred tokens
+        let (mut contract, sender, receiver) = setup("sender", 40);
+
+        promise_result(PromiseResult::Failed);
+
+        assert_eq!(
+            contract.resolve_transfer(sender.clone(), receiver.clone(), 100),
+            60,
+        );
+        assert_eq!(Contract::balance_of(&sender), 40);
+        assert_eq!(Contract::balance_of(&receiver), 0);
+    }
+
+    #[test]
+    fn resolve_transfer_receiver_spent_all_tokens() {
+        let (mut contract, sender, receiver) = setup("sender", 0);
+
+        promise_result(PromiseResult::Failed);
+
+        assert_eq!(contract.resolve_transfer(sender, receiver, 100), 100);
+        assert!(get_logs().is_empty());
+    }
+
+    #[test]
+    fn resolve_transfer_sender_deleted() {
+        let (mut contract, sender, receiver) = setup("deleted", 100);
+
+        promise_result(PromiseResult::Successful(br#""30""#.to_vec()));
+
+        assert_eq!(
+            contract.resolve_transfer(sender.clone(), receiver.clone(), 100),
+            70,
+        );
+        assert_eq!(Contract::balance_of(&sender), 30);
+        assert_eq!(Contract::balance_of(&receiver), 70);
+        assert_eq!(Contract::total_supply(), 100);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"receiver","new_owner_id":"deleted","amount":"30"}]}"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn resolve_transfer_receiver_deleted() {
+        let (mut contract, sender, receiver) = setup_accounts("sender", "deleted", 100);
+
+        promise_result(PromiseResult::Successful(br#""30""#.to_vec()));
+
+        assert_eq!(
+            contract.resolve_transfer(sender.clone(), receiver.clone(), 100),
+            100,
+        );
+        assert_eq!(Contract::balance_of(&sender), 0);
+        assert_eq!(Contract::balance_of(&receiver), 70);
+        assert_eq!(Contract::total_supply(), 70);
+        assert_eq!(
+            get_logs(),
+            vec![
+                "burn hook",
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"deleted","amount":"30","memo":"refund"}]}"#,
+            ],
+        );
+    }
+}
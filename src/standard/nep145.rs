@@ -0,0 +1,373 @@
+//! NEP-145 storage management implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0145.md>
+//!
+//! Accounts register with the contract by depositing at least the minimum
+//! storage balance ([`StorageBalanceBounds::min`]), which is locked for as
+//! long as the account is registered. Any amount deposited above the minimum
+//! is available for withdrawal.
+//!
+//! Unless other bounds are set, the minimum storage balance covers the
+//! storage fee of the records written when an account registers (see
+//! [`Nep145ControllerInternal::storage_usage_per_account`]), so that
+//! registration is never free.
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, ext_contract,
+    json_types::U128,
+    AccountId, BorshStorageKey,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{slot::Slot, DefaultStorageKey};
+
+/// Storage balance of an account
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StorageBalance {
+    /// Total amount deposited by the account
+    pub total: U128,
+    /// Amount available for withdrawal
+    pub available: U128,
+}
+
+/// Minimum and maximum storage balance of an account
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StorageBalanceBounds {
+    /// Amount required to register an account
+    pub min: U128,
+    /// Maximum amount an account may deposit, if any
+    pub max: Option<U128>,
+}
+
+pub mod error {
+    use near_sdk::AccountId;
+    use thiserror::Error;
+
+    /// Occurs when registering an account with less than the minimum storage
+    /// balance
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Deposit of {amount} yoctoNEAR does not meet the minimum storage balance of {min} yoctoNEAR")]
+    pub struct MinimumBalanceUnmetError {
+        /// The minimum storage balance
+        pub min: u128,
+        /// The amount deposited
+        pub amount: u128,
+    }
+
+    /// Occurs when an operation requires an account to be registered
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Account `{account_id}` is not registered")]
+    pub struct AccountNotRegisteredError {
+        /// The account that is not registered
+        pub account_id: AccountId,
+    }
+
+    /// Occurs when withdrawing more than the available storage balance
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Account `{account_id}` has {available} yoctoNEAR of storage balance available, but {amount} yoctoNEAR was requested")]
+    pub struct InsufficientBalanceError {
+        /// The account in question
+        pub account_id: AccountId,
+        /// The available storage balance
+        pub available: u128,
+        /// The requested amount
+        pub amount: u128,
+    }
+}
+
+use error::*;
+
+/// Errors that may occur when depositing to a storage account
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum StorageDepositError {
+    /// The deposit does not cover the minimum storage balance
+    #[error(transparent)]
+    MinimumBalanceUnmet(#[from] MinimumBalanceUnmetError),
+}
+
+/// Errors that may occur when withdrawing from a storage account
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum StorageWithdrawError {
+    /// The account is not registered
+    #[error(transparent)]
+    AccountNotRegistered(#[from] AccountNotRegisteredError),
+    /// The account does not have enough available storage balance
+    #[error(transparent)]
+    InsufficientBalance(#[from] InsufficientBalanceError),
+}
+
+/// Errors that may occur when unregistering a storage account
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum StorageUnregisterError {
+    /// The account is not registered
+    #[error(transparent)]
+    AccountNotRegistered(#[from] AccountNotRegisteredError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    BalanceBounds,
+    Account(&'a AccountId),
+}
+
+/// Number of bytes the protocol charges for each storage record in addition
+/// to its key and value (`num_extra_bytes_record`)
+pub const STORAGE_BYTES_PER_RECORD: u64 = 40;
+
+/// The longest valid account ID (64 characters), used to compute the
+/// worst-case storage usage of per-account records
+pub fn longest_account_id() -> AccountId {
+    "a".repeat(64).parse().unwrap()
+}
+
+/// Storage usage of writing `value` to `slot`, without writing it
+pub fn record_storage_usage<T: BorshSerialize>(slot: &Slot<T>, value: &T) -> u64 {
+    let value_len = value.try_to_vec().unwrap().len() as u64;
+    slot.key.len() as u64 + value_len + STORAGE_BYTES_PER_RECORD
+}
+
+/// Charges the storage fee for the storage used since `initial_storage_usage`,
+/// plus `additional_storage_usage`, to the available storage balance of
+/// `account_id`. Fails if the account is not registered or if its available
+/// storage balance is too small.
+pub fn charge_storage_fee<T: Nep145Controller + ?Sized>(
+    contract: &mut T,
+    account_id: &AccountId,
+    initial_storage_usage: u64,
+    additional_storage_usage: u64,
+) -> Result<(), StorageWithdrawError> {
+    let storage_usage =
+        env::storage_usage().saturating_sub(initial_storage_usage) + additional_storage_usage;
+
+    if storage_usage > 0 {
+        contract.withdraw_from_storage_account(
+            account_id,
+            Some(u128::from(storage_usage) * env::storage_byte_cost()),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Internal functions for [`Nep145Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep145ControllerInternal {
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep145)
+    }
+
+    /// Slot for the storage balance bounds
+    fn slot_storage_balance_bounds() -> Slot<StorageBalanceBounds> {
+        Self::root().field(StorageKey::BalanceBounds)
+    }
+
+    /// Slot for the storage balance of an account. An account is registered
+    /// if and only if this slot is populated.
+    fn slot_storage_balance(account_id: &AccountId) -> Slot<StorageBalance> {
+        Self::root().field(StorageKey::Account(account_id))
+    }
+
+    /// Storage usage of the records written when an account registers,
+    /// computed for the longest possible account ID. The default minimum
+    /// storage balance covers the storage fee for this usage. Override to
+    /// include other per-account records.
+    fn storage_usage_per_account() -> u64 {
+        record_storage_usage(
+            &Self::slot_storage_balance(&longest_account_id()),
+            &StorageBalance {
+                total: 0.into(),
+                available: 0.into(),
+            },
+        )
+    }
+}
+
+/// Non-public implementations of functions for managing storage balances.
+pub trait Nep145Controller {
+    /// Returns the storage balance bounds. Defaults to a minimum of the
+    /// storage fee for [`Nep145ControllerInternal::storage_usage_per_account`]
+    /// and no maximum.
+    fn get_storage_balance_bounds() -> StorageBalanceBounds;
+
+    /// Sets the storage balance bounds. Does not affect accounts that are
+    /// already registered. `max`, if specified, should not be less than
+    /// `min`.
+    fn set_storage_balance_bounds(&mut self, bounds: &StorageBalanceBounds);
+
+    /// Returns the storage balance of an account, or `None` if the account is
+    /// not registered.
+    fn get_storage_balance(account_id: &AccountId) -> Option<StorageBalance>;
+
+    /// Deposits `amount` to the storage balance of an account, registering
+    /// the account if necessary. If `registration_only` is `true`, deposits
+    /// only the amount required to register the account.
+    ///
+    /// Never deposits more than the maximum storage balance. Returns the new
+    /// storage balance of the account and the portion of `amount` that was
+    /// not deposited, which should be refunded.
+    fn deposit_to_storage_account(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+        registration_only: bool,
+    ) -> Result<(StorageBalance, u128), StorageDepositError>;
+
+    /// Withdraws `amount` (default: all available) from the storage balance
+    /// of an account. Returns the new storage balance of the account.
+    fn withdraw_from_storage_account(
+        &mut self,
+        account_id: &AccountId,
+        amount: Option<u128>,
+    ) -> Result<StorageBalance, StorageWithdrawError>;
+
+    /// Unregisters an account, returning its final storage balance. The
+    /// entire balance (including the locked minimum) should be refunded.
+    fn unregister_storage_account(
+        &mut self,
+        account_id: &AccountId,
+    ) -> Result<StorageBalance, StorageUnregisterError>;
+}
+
+impl<T: Nep145ControllerInternal> Nep145Controller for T {
+    fn get_storage_balance_bounds() -> StorageBalanceBounds {
+        Self::slot_storage_balance_bounds()
+            .read()
+            .unwrap_or_else(|| StorageBalanceBounds {
+                min: (u128::from(Self::storage_usage_per_account()) * env::storage_byte_cost())
+                    .into(),
+                max: None,
+            })
+    }
+
+    fn set_storage_balance_bounds(&mut self, bounds: &StorageBalanceBounds) {
+        Self::slot_storage_balance_bounds().write(bounds);
+    }
+
+    fn get_storage_balance(account_id: &AccountId) -> Option<StorageBalance> {
+        Self::slot_storage_balance(account_id).read()
+    }
+
+    fn deposit_to_storage_account(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+        registration_only: bool,
+    ) -> Result<(StorageBalance, u128), StorageDepositError> {
+        let bounds = Self::get_storage_balance_bounds();
+        let min = bounds.min.0;
+
+        let (balance, deposit, is_registration) = match Self::get_storage_balance(account_id) {
+            Some(balance) if registration_only => (balance, 0, false),
+            Some(balance) => (balance, amount, false),
+            None => {
+                if amount < min {
+                    return Err(MinimumBalanceUnmetError { min, amount }.into());
+                }
+
+                let deposit = if registration_only { min } else { amount };
+
+                let balance = StorageBalance {
+                    total: 0.into(),
+                    available: 0.into(),
+                };
+
+                (balance, deposit, true)
+            }
+        };
+
+        let deposit = bounds.max.map_or(deposit, |max| {
+            u128::min(deposit, max.0.saturating_sub(balance.total.0))
+        });
+
+        let total = balance.total.0 + deposit;
+        let available = if is_registration {
+            total.saturating_sub(min)
+        } else {
+            balance.available.0 + deposit
+        };
+
+        let balance = StorageBalance {
+            total: total.into(),
+            available: available.into(),
+        };
+
+        Self::slot_storage_balance(account_id).write(&balance);
+
+        Ok((balance, amount - deposit))
+    }
+
+    fn withdraw_from_storage_account(
+        &mut self,
+        account_id: &AccountId,
+        amount: Option<u128>,
+    ) -> Result<StorageBalance, StorageWithdrawError> {
+        let balance =
+            Self::get_storage_balance(account_id).ok_or_else(|| AccountNotRegisteredError {
+                account_id: account_id.clone(),
+            })?;
+
+        let amount = amount.unwrap_or(balance.available.0);
+
+        if amount > balance.available.0 {
+            return Err(InsufficientBalanceError {
+                account_id: account_id.clone(),
+                available: balance.available.0,
+                amount,
+            }
+            .into());
+        }
+
+        let balance = StorageBalance {
+            total: (balance.total.0 - amount).into(),
+            available: (balance.available.0 - amount).into(),
+        };
+
+        Self::slot_storage_balance(account_id).write(&balance);
+
+        Ok(balance)
+    }
+
+    fn unregister_storage_account(
+        &mut self,
+        account_id: &AccountId,
+    ) -> Result<StorageBalance, StorageUnregisterError> {
+        Self::slot_storage_balance(account_id)
+            .take()
+            .ok_or_else(|| {
+                AccountNotRegisteredError {
+                    account_id: account_id.clone(),
+                }
+                .into()
+            })
+    }
+}
+
+/// Externally-accessible NEP-145-compatible storage management interface.
+#[ext_contract(ext_nep145)]
+pub trait Nep145 {
+    /// Deposits the attached deposit to the storage balance of `account_id`
+    /// (default: the predecessor), registering the account if necessary. If
+    /// `registration_only` is `true`, only the amount required for
+    /// registration is deposited. Unused funds are refunded.
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance;
+
+    /// Withdraws `amount` (default: all available) from the predecessor's
+    /// storage balance.
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+
+    /// Unregisters the predecessor and refunds its entire storage balance.
+    /// Returns `false` if the predecessor was not registered.
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool;
+
+    /// Returns the storage balance of `account_id`, if it is registered.
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+
+    /// Returns the minimum and maximum storage balance of an account.
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+}
@@ -0,0 +1,876 @@
+//! NEP-171 non-fungible token core implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0171.md>
+//!
+//! Other non-fungible token standards (approval management, enumeration,
+//! etc.) participate in the core's token lifecycle through the
+//! [`Nep171Extension`] trait, which is selected by
+//! [`Nep171ControllerInternal::Extension`].
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, ext_contract, AccountId, BorshStorageKey, Gas, Promise, PromiseOrValue, PromiseResult,
+};
+use near_sdk_contract_tools_macros::event;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{slot::Slot, standard::nep297::*, DefaultStorageKey};
+
+/// Type of a non-fungible token ID
+pub type TokenId = String;
+
+/// Gas value required for nft_resolve_transfer calls
+pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+/// Gas value required for nft_transfer_call calls (includes gas for
+/// nft_resolve_transfer)
+pub const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+/// NEP-171 standard events for minting, burning, and transferring tokens
+#[event(
+    crate = "crate",
+    macros = "crate",
+    serde = "serde",
+    standard = "nep171",
+    version = "1.0.0"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep171Event {
+    /// Token mint event. Emitted when tokens are created.
+    NftMint(Vec<event::NftMintData>),
+
+    /// Token transfer event. Emitted when tokens are transferred between two
+    /// accounts.
+    NftTransfer(Vec<event::NftTransferData>),
+
+    /// Token burn event. Emitted when tokens are deleted.
+    NftBurn(Vec<event::NftBurnData>),
+}
+
+pub mod event {
+    use near_sdk::AccountId;
+    use serde::Serialize;
+
+    use super::TokenId;
+
+    /// Individual mint metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct NftMintData {
+        /// The account to which the new tokens were minted
+        pub owner_id: AccountId,
+        /// IDs of the minted tokens
+        pub token_ids: Vec<TokenId>,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
+    /// Individual transfer metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct NftTransferData {
+        /// Approved account that performed the transfer on behalf of the
+        /// owner, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub authorized_id: Option<AccountId>,
+        /// Account ID of the previous owner
+        pub old_owner_id: AccountId,
+        /// Account ID of the new owner
+        pub new_owner_id: AccountId,
+        /// IDs of the transferred tokens
+        pub token_ids: Vec<TokenId>,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
+    /// Individual burn metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct NftBurnData {
+        /// Account ID of the owner of the burned tokens
+        pub owner_id: AccountId,
+        /// Approved account that performed the burn on behalf of the owner,
+        /// if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub authorized_id: Option<AccountId>,
+        /// IDs of the burned tokens
+        pub token_ids: Vec<TokenId>,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{super::Nep171Event, *};
+        use crate::standard::nep297::Event;
+
+        #[test]
+        fn mint() {
+            assert_eq!(
+                Nep171Event::NftMint(vec![NftMintData {
+                    owner_id: "foundation.near".parse().unwrap(),
+                    token_ids: vec!["aurora".to_string(), "proximitylabs".to_string()],
+                    memo: None,
+                }])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"foundation.near","token_ids":["aurora","proximitylabs"]}]}"#,
+            );
+        }
+
+        #[test]
+        fn transfer() {
+            assert_eq!(
+                Nep171Event::NftTransfer(vec![
+                    NftTransferData {
+                        authorized_id: None,
+                        old_owner_id: "from.near".parse().unwrap(),
+                        new_owner_id: "to.near".parse().unwrap(),
+                        token_ids: vec!["meme".to_string()],
+                        memo: Some("have fun!".to_string()),
+                    },
+                    NftTransferData {
+                        authorized_id: Some("market.near".parse().unwrap()),
+                        old_owner_id: "user1.near".parse().unwrap(),
+                        new_owner_id: "user2.near".parse().unwrap(),
+                        token_ids: vec!["token".to_string()],
+                        memo: None,
+                    },
+                ])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"from.near","new_owner_id":"to.near","token_ids":["meme"],"memo":"have fun!"},{"authorized_id":"market.near","old_owner_id":"user1.near","new_owner_id":"user2.near","token_ids":["token"]}]}"#,
+            );
+        }
+
+        #[test]
+        fn burn() {
+            assert_eq!(
+                Nep171Event::NftBurn(vec![NftBurnData {
+                    owner_id: "foundation.near".parse().unwrap(),
+                    authorized_id: None,
+                    token_ids: vec!["aurora".to_string(), "proximitylabs".to_string()],
+                    memo: None,
+                }])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":[{"owner_id":"foundation.near","token_ids":["aurora","proximitylabs"]}]}"#,
+            );
+        }
+    }
+}
+
+pub mod error {
+    use near_sdk::{AccountId, Gas};
+    use thiserror::Error;
+
+    use super::TokenId;
+
+    /// Occurs when attempting to create a token that already exists
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Token `{token_id}` already exists")]
+    pub struct TokenAlreadyExistsError {
+        /// The ID of the token that already exists
+        pub token_id: TokenId,
+    }
+
+    /// Occurs when performing an operation on a token that does not exist
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Token `{token_id}` does not exist")]
+    pub struct TokenDoesNotExistError {
+        /// The ID of the token that does not exist
+        pub token_id: TokenId,
+    }
+
+    /// Occurs when a token is not owned by the account that was expected to
+    /// own it
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error(
+        "Token `{token_id}` is owned by `{owner_id}` instead of expected `{expected_owner_id}`"
+    )]
+    pub struct TokenNotOwnedByExpectedOwnerError {
+        /// The ID of the token in question
+        pub token_id: TokenId,
+        /// The account that actually owns the token
+        pub owner_id: AccountId,
+        /// The account that was expected to own the token
+        pub expected_owner_id: AccountId,
+    }
+
+    /// Occurs when a token would be transferred to the account that already
+    /// owns it
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Receiver `{owner_id}` already owns token `{token_id}`")]
+    pub struct TokenReceiverIsCurrentOwnerError {
+        /// The ID of the token in question
+        pub token_id: TokenId,
+        /// The current owner of the token
+        pub owner_id: AccountId,
+    }
+
+    /// Occurs when an account that is neither the owner nor approved by the
+    /// owner attempts to transfer a token
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Sender `{sender_id}` does not have permission to transfer token `{token_id}`")]
+    pub struct SenderNotApprovedError {
+        /// The ID of the token in question
+        pub token_id: TokenId,
+        /// The account that attempted the transfer
+        pub sender_id: AccountId,
+    }
+
+    /// Occurs when a transfer call is not allowed enough gas
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("More gas is required: {} gas is required, but only {} gas was allowed", .required.0, .gas_allowance.0)]
+    pub struct InsufficientGasError {
+        /// The minimum required gas
+        pub required: Gas,
+        /// The gas allowed for the call
+        pub gas_allowance: Gas,
+    }
+}
+
+use error::*;
+
+/// Errors that may occur when transferring a token
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep171TransferError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The sender may not transfer the token
+    #[error(transparent)]
+    SenderNotApproved(#[from] SenderNotApprovedError),
+    /// The receiver already owns the token
+    #[error(transparent)]
+    TokenReceiverIsCurrentOwner(#[from] TokenReceiverIsCurrentOwnerError),
+}
+
+/// Errors that may occur when performing a transfer call
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep171TransferCallError {
+    /// Not enough gas was allowed
+    #[error(transparent)]
+    InsufficientGas(#[from] InsufficientGasError),
+    /// The transfer failed
+    #[error(transparent)]
+    Transfer(#[from] Nep171TransferError),
+}
+
+/// Errors that may occur when minting tokens
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep171MintError {
+    /// A token with the same ID already exists
+    #[error(transparent)]
+    TokenAlreadyExists(#[from] TokenAlreadyExistsError),
+}
+
+/// Errors that may occur when burning tokens
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep171BurnError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The token is not owned by the account that is burning it
+    #[error(transparent)]
+    TokenNotOwnedByExpectedOwner(#[from] TokenNotOwnedByExpectedOwnerError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    TokenOwner(&'a str),
+}
+
+/// Contracts may implement this trait to inject code into NEP-171 functions.
+///
+/// `T` is an optional value for passing state between different lifecycle
+/// hooks. This may be useful for charging callers for storage usage, for
+/// example.
+pub trait Nep171Hook<T: Default = ()> {
+    /// Executed before a token transfer is conducted
+    ///
+    /// May return an optional state value which will be passed along to the
+    /// following `after_nft_transfer`.
+    fn before_nft_transfer(&mut self, _transfer: &Nep171Transfer) -> T {
+        Default::default()
+    }
+
+    /// Executed after a token transfer is conducted
+    ///
+    /// Receives the state value returned by `before_nft_transfer`.
+    fn after_nft_transfer(&mut self, _transfer: &Nep171Transfer, _state: T) {}
+}
+
+/// Lets other non-fungible token standards observe and authorize the token
+/// operations performed by a [`Nep171Controller`].
+///
+/// Multiple extensions may be combined by using a tuple, e.g. `(A, B)`.
+pub trait Nep171Extension<C: ?Sized> {
+    /// Called when `transfer.sender_id` is not the current owner of the
+    /// token. Returning `Ok(())` allows the transfer to proceed. The default
+    /// implementation rejects all such transfers.
+    fn authorize_transfer(
+        _contract: &C,
+        transfer: &Nep171Transfer,
+        _owner_id: &AccountId,
+    ) -> Result<(), Nep171TransferError> {
+        Err(SenderNotApprovedError {
+            token_id: transfer.token_id.clone(),
+            sender_id: transfer.sender_id.clone(),
+        }
+        .into())
+    }
+
+    /// Executed after a token has been transferred from `owner_id`.
+    fn on_transfer(_contract: &mut C, _transfer: &Nep171Transfer, _owner_id: &AccountId) {}
+
+    /// Executed after tokens have been minted to `owner_id`.
+    fn on_mint(_contract: &mut C, _token_ids: &[TokenId], _owner_id: &AccountId) {}
+
+    /// Executed after tokens owned by `owner_id` have been burned.
+    fn on_burn(_contract: &mut C, _token_ids: &[TokenId], _owner_id: &AccountId) {}
+
+    /// Approvals to restore if a token transferred by `nft_transfer_call` is
+    /// returned to its previous owner. Read before the transfer is performed.
+    fn approved_account_ids(_contract: &C, _token_id: &TokenId) -> Option<HashMap<AccountId, u32>> {
+        None
+    }
+
+    /// Executed after a token transferred by `nft_transfer_call` has been
+    /// returned to `previous_owner_id`. Receives the approvals returned by
+    /// [`Nep171Extension::approved_account_ids`] before the transfer.
+    fn on_transfer_reverted(
+        _contract: &mut C,
+        _token_id: &TokenId,
+        _previous_owner_id: &AccountId,
+        _approved_account_ids: Option<&HashMap<AccountId, u32>>,
+    ) {
+    }
+
+    /// Adds extension-specific fields to the JSON representation of a token
+    /// (see [`Token`]).
+    fn load_token_metadata(
+        _contract: &C,
+        _token_id: &TokenId,
+        _metadata: &mut HashMap<String, serde_json::Value>,
+    ) {
+    }
+}
+
+impl<C: ?Sized> Nep171Extension<C> for () {}
+
+macro_rules! impl_nep171_extension_for_tuple {
+    ($($t:ident),+) => {
+        impl<C: ?Sized, $($t: Nep171Extension<C>),+> Nep171Extension<C> for ($($t,)+) {
+            fn authorize_transfer(
+                contract: &C,
+                transfer: &Nep171Transfer,
+                owner_id: &AccountId,
+            ) -> Result<(), Nep171TransferError> {
+                let mut result = Err(SenderNotApprovedError {
+                    token_id: transfer.token_id.clone(),
+                    sender_id: transfer.sender_id.clone(),
+                }
+                .into());
+                $(
+                    if result.is_err() {
+                        result = $t::authorize_transfer(contract, transfer, owner_id);
+                    }
+                )+
+                result
+            }
+
+            fn on_transfer(contract: &mut C, transfer: &Nep171Transfer, owner_id: &AccountId) {
+                $($t::on_transfer(contract, transfer, owner_id);)+
+            }
+
+            fn on_mint(contract: &mut C, token_ids: &[TokenId], owner_id: &AccountId) {
+                $($t::on_mint(contract, token_ids, owner_id);)+
+            }
+
+            fn on_burn(contract: &mut C, token_ids: &[TokenId], owner_id: &AccountId) {
+                $($t::on_burn(contract, token_ids, owner_id);)+
+            }
+
+            fn approved_account_ids(
+                contract: &C,
+                token_id: &TokenId,
+            ) -> Option<HashMap<AccountId, u32>> {
+                None$(.or_else(|| $t::approved_account_ids(contract, token_id)))+
+            }
+
+            fn on_transfer_reverted(
+                contract: &mut C,
+                token_id: &TokenId,
+                previous_owner_id: &AccountId,
+                approved_account_ids: Option<&HashMap<AccountId, u32>>,
+            ) {
+                $($t::on_transfer_reverted(contract, token_id, previous_owner_id, approved_account_ids);)+
+            }
+
+            fn load_token_metadata(
+                contract: &C,
+                token_id: &TokenId,
+                metadata: &mut HashMap<String, serde_json::Value>,
+            ) {
+                $($t::load_token_metadata(contract, token_id, metadata);)+
+            }
+        }
+    };
+}
+
+impl_nep171_extension_for_tuple!(A);
+impl_nep171_extension_for_tuple!(A, B);
+impl_nep171_extension_for_tuple!(A, B, C2);
+impl_nep171_extension_for_tuple!(A, B, C2, D);
+impl_nep171_extension_for_tuple!(A, B, C2, D, E);
+
+/// Transfer metadata generic over both types of transfer (`nft_transfer` and
+/// `nft_transfer_call`).
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Nep171Transfer {
+    /// ID of the token being transferred
+    pub token_id: TokenId,
+    /// Account that initiated the transfer (owner or approved account)
+    pub sender_id: AccountId,
+    /// Receiver's account ID
+    pub receiver_id: AccountId,
+    /// Approval ID provided by an approved sender
+    pub approval_id: Option<u32>,
+    /// Optional memo string
+    pub memo: Option<String>,
+    /// Message passed to contract located at `receiver_id`
+    pub msg: Option<String>,
+}
+
+impl Nep171Transfer {
+    /// Returns `true` if this transfer comes from a `nft_transfer_call`
+    /// call, `false` otherwise
+    pub fn is_transfer_call(&self) -> bool {
+        self.msg.is_some()
+    }
+}
+
+/// NEP-171-compatible token representation. Extensions (e.g. metadata,
+/// approvals) add their fields to `extensions_metadata`, which is flattened
+/// into the token's JSON representation.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Token {
+    /// Token ID
+    pub token_id: TokenId,
+    /// Current owner of the token
+    pub owner_id: AccountId,
+    /// Fields added by extensions
+    #[serde(flatten)]
+    pub extensions_metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Internal functions for [`Nep171Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep171ControllerInternal {
+    /// Extensions that participate in token transfers, mints, and burns.
+    /// Use `()` if there are none.
+    type Extension: Nep171Extension<Self>;
+
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep171)
+    }
+
+    /// Slot for the owner of a token. A token exists if and only if this
+    /// slot is populated.
+    fn slot_token_owner(token_id: &TokenId) -> Slot<AccountId> {
+        Self::root().field(StorageKey::TokenOwner(token_id))
+    }
+
+    /// Performs a transfer (`f`), executing any transfer hooks around it. By
+    /// default, no hooks are executed. The derive macro wires this up to the
+    /// [`Nep171Hook`] transfer hooks unless `no_hooks` is specified. If `f`
+    /// fails, the `after_nft_transfer` hook is not executed.
+    fn hook_transfer<E>(
+        contract: &mut Self,
+        _transfer: &Nep171Transfer,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        f(contract)
+    }
+}
+
+/// Performs a transfer, with event emission, without executing the transfer
+/// hooks
+fn unhooked_transfer<T: Nep171ControllerInternal>(
+    contract: &mut T,
+    transfer: &Nep171Transfer,
+) -> Result<(), Nep171TransferError> {
+    let owner_id = contract.check_transfer(transfer)?;
+
+    contract.transfer_unchecked(
+        std::slice::from_ref(&transfer.token_id),
+        &transfer.receiver_id,
+    );
+
+    T::Extension::on_transfer(contract, transfer, &owner_id);
+
+    let authorized_id = (transfer.sender_id != owner_id).then(|| transfer.sender_id.clone());
+
+    Nep171Event::NftTransfer(vec![event::NftTransferData {
+        authorized_id,
+        old_owner_id: owner_id,
+        new_owner_id: transfer.receiver_id.clone(),
+        token_ids: vec![transfer.token_id.clone()],
+        memo: transfer.memo.clone(),
+    }])
+    .emit();
+
+    Ok(())
+}
+
+/// Non-public implementations of functions for managing non-fungible tokens.
+pub trait Nep171Controller {
+    /// Returns the owner of a token, or `None` if the token does not exist.
+    fn token_owner(token_id: &TokenId) -> Option<AccountId>;
+
+    /// Loads a token and the metadata contributed by extensions.
+    fn load_token(&self, token_id: &TokenId) -> Option<Token>;
+
+    /// Checks whether a transfer is allowed to proceed. Returns the current
+    /// owner of the token if it is.
+    fn check_transfer(&self, transfer: &Nep171Transfer) -> Result<AccountId, Nep171TransferError>;
+
+    /// Sets the owner of the tokens to `receiver_id`. No checks, no event
+    /// emission, and extensions are not notified.
+    fn transfer_unchecked(&mut self, token_ids: &[TokenId], receiver_id: &AccountId);
+
+    /// Performs an NEP-171 token transfer, with event emission. Executes
+    /// transfer hooks (see: [`Nep171ControllerInternal::hook_transfer`]).
+    fn transfer(&mut self, transfer: &Nep171Transfer) -> Result<(), Nep171TransferError>;
+
+    /// Creates tokens owned by `owner_id`. No checks, no event emission, and
+    /// extensions are not notified.
+    fn mint_unchecked(&mut self, token_ids: &[TokenId], owner_id: &AccountId);
+
+    /// Performs an NEP-171 token mint, with event emission. Fails if any of
+    /// the tokens already exist.
+    fn mint(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        owner_id: AccountId,
+        memo: Option<String>,
+    ) -> Result<(), Nep171MintError>;
+
+    /// Deletes tokens. No checks, no event emission, and extensions are not
+    /// notified.
+    fn burn_unchecked(&mut self, token_ids: &[TokenId]);
+
+    /// Performs an NEP-171 token burn, with event emission. Fails if any of
+    /// the tokens do not exist or are not owned by `owner_id`.
+    fn burn(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        owner_id: AccountId,
+        memo: Option<String>,
+    ) -> Result<(), Nep171BurnError>;
+
+    /// Performs an NEP-171 token transfer call, with event emission. Fails
+    /// if `gas_allowance` < `GAS_FOR_NFT_TRANSFER_CALL`. Executes transfer
+    /// hooks around the transfer, before the receiver is called.
+    ///
+    /// See also: `Nep171Controller::transfer`
+    fn transfer_call(
+        &mut self,
+        transfer: &Nep171Transfer,
+        gas_allowance: Gas,
+    ) -> Result<Promise, Nep171TransferCallError>;
+
+    /// Resolves an NEP-171 `nft_transfer_call` promise chain. Returns `true`
+    /// if the token was successfully transferred to `receiver_id`.
+    ///
+    /// If the token is returned, `approved_account_ids` are passed along to
+    /// [`Nep171Extension::on_transfer_reverted`] so that they may be restored.
+    /// Returning the token does not execute the transfer hooks.
+    fn resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u32>>,
+    ) -> bool;
+}
+
+impl<T: Nep171ControllerInternal> Nep171Controller for T {
+    fn token_owner(token_id: &TokenId) -> Option<AccountId> {
+        Self::slot_token_owner(token_id).read()
+    }
+
+    fn load_token(&self, token_id: &TokenId) -> Option<Token> {
+        let owner_id = Self::token_owner(token_id)?;
+        let mut extensions_metadata = HashMap::new();
+        T::Extension::load_token_metadata(self, token_id, &mut extensions_metadata);
+
+        Some(Token {
+            token_id: token_id.clone(),
+            owner_id,
+            extensions_metadata,
+        })
+    }
+
+    fn check_transfer(&self, transfer: &Nep171Transfer) -> Result<AccountId, Nep171TransferError> {
+        let owner_id =
+            Self::token_owner(&transfer.token_id).ok_or_else(|| TokenDoesNotExistError {
+                token_id: transfer.token_id.clone(),
+            })?;
+
+        if transfer.receiver_id == owner_id {
+            return Err(TokenReceiverIsCurrentOwnerError {
+                token_id: transfer.token_id.clone(),
+                owner_id,
+            }
+            .into());
+        }
+
+        if transfer.sender_id != owner_id {
+            T::Extension::authorize_transfer(self, transfer, &owner_id)?;
+        }
+
+        Ok(owner_id)
+    }
+
+    fn transfer_unchecked(&mut self, token_ids: &[TokenId], receiver_id: &AccountId) {
+        for token_id in token_ids {
+            Self::slot_token_owner(token_id).write(receiver_id);
+        }
+    }
+
+    fn transfer(&mut self, transfer: &Nep171Transfer) -> Result<(), Nep171TransferError> {
+        Self::hook_transfer(self, transfer, |contract| {
+            unhooked_transfer(contract, transfer)
+        })
+    }
+
+    fn mint_unchecked(&mut self, token_ids: &[TokenId], owner_id: &AccountId) {
+        for token_id in token_ids {
+            Self::slot_token_owner(token_id).write(owner_id);
+        }
+    }
+
+    fn mint(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        owner_id: AccountId,
+        memo: Option<String>,
+    ) -> Result<(), Nep171MintError> {
+        for token_id in &token_ids {
+            if Self::slot_token_owner(token_id).exists() {
+                return Err(TokenAlreadyExistsError {
+                    token_id: token_id.clone(),
+                }
+                .into());
+            }
+        }
+
+        self.mint_unchecked(&token_ids, &owner_id);
+
+        T::Extension::on_mint(self, &token_ids, &owner_id);
+
+        Nep171Event::NftMint(vec![event::NftMintData {
+            owner_id,
+            token_ids,
+            memo,
+        }])
+        .emit();
+
+        Ok(())
+    }
+
+    fn burn_unchecked(&mut self, token_ids: &[TokenId]) {
+        for token_id in token_ids {
+            Self::slot_token_owner(token_id).remove();
+        }
+    }
+
+    fn burn(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        owner_id: AccountId,
+        memo: Option<String>,
+    ) -> Result<(), Nep171BurnError> {
+        for token_id in &token_ids {
+            let actual_owner_id =
+                Self::token_owner(token_id).ok_or_else(|| TokenDoesNotExistError {
+                    token_id: token_id.clone(),
+                })?;
+
+            if actual_owner_id != owner_id {
+                return Err(TokenNotOwnedByExpectedOwnerError {
+                    token_id: token_id.clone(),
+                    owner_id: actual_owner_id,
+                    expected_owner_id: owner_id,
+                }
+                .into());
+            }
+        }
+
+        self.burn_unchecked(&token_ids);
+
+        T::Extension::on_burn(self, &token_ids, &owner_id);
+
+        Nep171Event::NftBurn(vec![event::NftBurnData {
+            owner_id,
+            authorized_id: None,
+            token_ids,
+            memo,
+        }])
+        .emit();
+
+        Ok(())
+    }
+
+    fn transfer_call(
+        &mut self,
+        transfer: &Nep171Transfer,
+        gas_allowance: Gas,
+    ) -> Result<Promise, Nep171TransferCallError> {
+        if gas_allowance < GAS_FOR_NFT_TRANSFER_CALL {
+            return Err(InsufficientGasError {
+                required: GAS_FOR_NFT_TRANSFER_CALL,
+                gas_allowance,
+            }
+            .into());
+        }
+
+        let previous_owner_id = Self::token_owner(&transfer.token_id);
+        let approved_account_ids = T::Extension::approved_account_ids(self, &transfer.token_id);
+
+        self.transfer(transfer)?;
+
+        // unwrap is safe because the transfer succeeded
+        let previous_owner_id = previous_owner_id.unwrap();
+
+        let receiver_gas = gas_allowance.0 - GAS_FOR_NFT_TRANSFER_CALL.0;
+
+        // Initiating receiver's call and the callback
+        Ok(ext_nep171_receiver::ext(transfer.receiver_id.clone())
+            .with_static_gas(receiver_gas.into())
+            .nft_on_transfer(
+                transfer.sender_id.clone(),
+                previous_owner_id.clone(),
+                transfer.token_id.clone(),
+                transfer.msg.clone().unwrap_or_default(),
+            )
+            .then(
+                ext_nep171_resolver::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(
+                        previous_owner_id,
+                        transfer.receiver_id.clone(),
+                        transfer.token_id.clone(),
+                        approved_account_ids,
+                    ),
+            ))
+    }
+
+    fn resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u32>>,
+    ) -> bool {
+        let should_revert = match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            PromiseResult::Failed => true,
+        };
+
+        if !should_revert {
+            return true;
+        }
+
+        // The receiver may have already transferred the token elsewhere, in
+        // which case the return transfer fails and the token is considered
+        // used.
+        let return_transfer = Nep171Transfer {
+            token_id,
+            sender_id: receiver_id.clone(),
+            receiver_id: previous_owner_id,
+            approval_id: None,
+            memo: None,
+            msg: None,
+        };
+
+        match Self::token_owner(&return_transfer.token_id) {
+            Some(owner_id) if owner_id == receiver_id => {
+                if unhooked_transfer(self, &return_transfer).is_err() {
+                    return true;
+                }
+
+                T::Extension::on_transfer_reverted(
+                    self,
+                    &return_transfer.token_id,
+                    &return_transfer.receiver_id,
+                    approved_account_ids.as_ref(),
+                );
+
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// A contract that may be the recipient of an `nft_transfer_call` function
+/// call.
+#[ext_contract(ext_nep171_receiver)]
+pub trait Nep171Receiver {
+    /// Function that is called in an `nft_transfer_call` promise chain.
+    /// Returns `true` if the token should be returned to `previous_owner_id`.
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+}
+
+/// Non-fungible token contract callback after `nft_transfer_call` execution.
+#[ext_contract(ext_nep171_resolver)]
+pub trait Nep171Resolver {
+    /// Callback, last in `nft_transfer_call` promise chain. Returns `true` if
+    /// the token was successfully transferred to `receiver_id`.
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u32>>,
+    ) -> bool;
+}
+
+/// Externally-accessible NEP-171-compatible non-fungible token interface.
+#[ext_contract(ext_nep171)]
+pub trait Nep171 {
+    /// Performs a token transfer
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u32>,
+        memo: Option<String>,
+    );
+
+    /// Performs a token transfer, then initiates a promise chain that calls
+    /// `nft_on_transfer` on the receiving account, followed by
+    /// `nft_resolve_transfer` on the original token contract (this contract).
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u32>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+
+    /// Returns the token with the given ID, if it exists
+    fn nft_token(&self, token_id: TokenId) -> Option<Token>;
+}
@@ -0,0 +1,446 @@
+//! NEP-177 non-fungible token metadata implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0177.md>
+//!
+//! Contract metadata and per-token metadata are kept in storage. Use
+//! [`Nep177Extension`] as (part of) the [`Nep171ControllerInternal::Extension`]
+//! to include token metadata in `nft_token` responses and to clean up
+//! metadata when tokens are burned.
+//!
+//! [`Nep171ControllerInternal::Extension`]: crate::standard::nep171::Nep171ControllerInternal::Extension
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, ext_contract,
+    json_types::Base64VecU8,
+    AccountId, BorshStorageKey,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    slot::Slot,
+    standard::nep171::{
+        error::TokenDoesNotExistError, Nep171Controller, Nep171Extension, Nep171MintError, TokenId,
+    },
+    DefaultStorageKey,
+};
+
+/// Version of the NEP-177 metadata spec
+pub const NFT_METADATA_SPEC: &str = "nft-2.1.0";
+
+/// Major versions of the NEP-177 metadata spec accepted by
+/// [`ContractMetadata::validate`]
+pub const SUPPORTED_SPEC_MAJOR_VERSIONS: &[u32] = &[1, 2];
+
+/// Length of a sha256 hash in bytes
+const HASH_LENGTH: usize = 32;
+
+const NOT_INITIALIZED: &str = "Contract metadata not initialized";
+
+/// NEP-177-compatible contract metadata struct
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Eq, PartialEq, Clone, Debug)]
+pub struct ContractMetadata {
+    /// Version of the NEP-177 spec
+    pub spec: String,
+    /// Human-friendly name of the token contract
+    pub name: String,
+    /// Short, ideally unique string to concisely identify the token contract
+    pub symbol: String,
+    /// String representation (HTTP URL, data URL, IPFS, Arweave, etc.) of an
+    /// icon for this token
+    pub icon: Option<String>,
+    /// Centralized gateway known to have reliable access to decentralized
+    /// storage assets referenced by `reference` or `media` URLs
+    pub base_uri: Option<String>,
+    /// External (off-chain) URL to additional JSON metadata for this token contract
+    pub reference: Option<String>,
+    /// Hash of the content that should be present in the `reference` field.
+    /// For tamper protection.
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl ContractMetadata {
+    /// Creates a new contract metadata struct with the current spec version
+    pub fn new(name: String, symbol: String, base_uri: Option<String>) -> Self {
+        Self {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name,
+            symbol,
+            icon: None,
+            base_uri,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    /// Checks that the spec version is supported and that `reference` and
+    /// `reference_hash` are specified together.
+    pub fn validate(&self) -> Result<(), MetadataValidationError> {
+        validate_spec(&self.spec)?;
+        validate_hash_pair(
+            "reference",
+            self.reference.as_ref(),
+            self.reference_hash.as_ref(),
+        )
+    }
+}
+
+/// NEP-177-compatible token metadata struct
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, Eq, PartialEq, Clone, Debug, Default,
+)]
+pub struct TokenMetadata {
+    /// Name of the token, e.g. "Arch Nemesis: Mail Carrier"
+    pub title: Option<String>,
+    /// Free-form description
+    pub description: Option<String>,
+    /// URL to associated media, preferably to decentralized, content-addressed
+    /// storage
+    pub media: Option<String>,
+    /// Hash of the content referenced by the `media` field. For tamper
+    /// protection.
+    pub media_hash: Option<Base64VecU8>,
+    /// Number of copies of this set of metadata in existence when the token
+    /// was minted
+    pub copies: Option<u64>,
+    /// When the token was issued or minted, Unix epoch in milliseconds
+    pub issued_at: Option<String>,
+    /// When the token expires, Unix epoch in milliseconds
+    pub expires_at: Option<String>,
+    /// When the token starts being valid, Unix epoch in milliseconds
+    pub starts_at: Option<String>,
+    /// When the token was last updated, Unix epoch in milliseconds
+    pub updated_at: Option<String>,
+    /// Anything extra the NFT wants to store on-chain. Can be stringified
+    /// JSON.
+    pub extra: Option<String>,
+    /// External (off-chain) URL to additional JSON metadata for this token
+    pub reference: Option<String>,
+    /// Hash of the content that should be present in the `reference` field.
+    /// For tamper protection.
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl TokenMetadata {
+    /// Checks that `media` and `media_hash`, as well as `reference` and
+    /// `reference_hash`, are specified together.
+    pub fn validate(&self) -> Result<(), MetadataValidationError> {
+        validate_hash_pair("media", self.media.as_ref(), self.media_hash.as_ref())?;
+        validate_hash_pair(
+            "reference",
+            self.reference.as_ref(),
+            self.reference_hash.as_ref(),
+        )
+    }
+}
+
+fn validate_spec(spec: &str) -> Result<(), MetadataValidationError> {
+    let major_version = spec
+        .strip_prefix("nft-")
+        .and_then(|version| version.split('.').next())
+        .and_then(|major| major.parse::<u32>().ok());
+
+    match major_version {
+        Some(major) if SUPPORTED_SPEC_MAJOR_VERSIONS.contains(&major) => Ok(()),
+        _ => Err(MetadataValidationError::UnsupportedSpec {
+            spec: spec.to_string(),
+        }),
+    }
+}
+
+fn validate_hash_pair(
+    field: &'static str,
+    value: Option<&String>,
+    hash: Option<&Base64VecU8>,
+) -> Result<(), MetadataValidationError> {
+    match (value, hash) {
+        (Some(_), Some(hash)) if hash.0.len() != HASH_LENGTH => {
+            Err(MetadataValidationError::InvalidHashLength {
+                field,
+                length: hash.0.len(),
+            })
+        }
+        (Some(_), None) | (None, Some(_)) => Err(MetadataValidationError::HashMismatch { field }),
+        _ => Ok(()),
+    }
+}
+
+/// Errors that may occur when validating metadata
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MetadataValidationError {
+    /// The metadata spec version is not supported
+    #[error("Unsupported metadata spec: `{spec}`")]
+    UnsupportedSpec {
+        /// The unsupported spec string
+        spec: String,
+    },
+    /// A field and its hash must be specified together
+    #[error("`{field}` and `{field}_hash` must be specified together")]
+    HashMismatch {
+        /// Name of the field
+        field: &'static str,
+    },
+    /// A hash is not a sha256 hash
+    #[error("`{field}_hash` must be 32 bytes, but is {length} bytes")]
+    InvalidHashLength {
+        /// Name of the field
+        field: &'static str,
+        /// Length of the provided hash
+        length: usize,
+    },
+}
+
+/// Errors that may occur when minting a token with metadata
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MintWithMetadataError {
+    /// The token metadata is invalid
+    #[error(transparent)]
+    InvalidMetadata(#[from] MetadataValidationError),
+    /// The token could not be minted
+    #[error(transparent)]
+    Mint(#[from] Nep171MintError),
+}
+
+/// Errors that may occur when updating the metadata of a token
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum UpdateTokenMetadataError {
+    /// The token metadata is invalid
+    #[error(transparent)]
+    InvalidMetadata(#[from] MetadataValidationError),
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    ContractMetadata,
+    TokenMetadata(&'a str),
+}
+
+/// Internal functions for [`Nep177Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep177ControllerInternal {
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep177)
+    }
+
+    /// Slot for contract metadata
+    fn slot_contract_metadata() -> Slot<ContractMetadata> {
+        Self::root().field(StorageKey::ContractMetadata)
+    }
+
+    /// Slot for the metadata of a token
+    fn slot_token_metadata(token_id: &TokenId) -> Slot<TokenMetadata> {
+        Self::root().field(StorageKey::TokenMetadata(token_id))
+    }
+}
+
+/// Non-public implementations of functions for managing non-fungible token
+/// metadata.
+pub trait Nep177Controller {
+    /// Reads contract metadata from storage. Panics if the contract metadata
+    /// has not been set.
+    fn contract_metadata() -> ContractMetadata;
+
+    /// Validates and writes contract metadata to storage.
+    fn set_contract_metadata(
+        &mut self,
+        metadata: ContractMetadata,
+    ) -> Result<(), MetadataValidationError>;
+
+    /// Reads the metadata of a token from storage.
+    fn token_metadata(token_id: &TokenId) -> Option<TokenMetadata>;
+
+    /// Writes (or removes) the metadata of a token. No checks.
+    fn set_token_metadata_unchecked(
+        &mut self,
+        token_id: &TokenId,
+        metadata: Option<&TokenMetadata>,
+    );
+
+    /// Validates and writes the metadata of an existing token.
+    fn set_token_metadata(
+        &mut self,
+        token_id: &TokenId,
+        metadata: &TokenMetadata,
+    ) -> Result<(), UpdateTokenMetadataError>;
+
+    /// Mints a new token with the given metadata, with event emission.
+    fn mint_with_metadata(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        metadata: TokenMetadata,
+    ) -> Result<(), MintWithMetadataError>;
+}
+
+impl<T: Nep177ControllerInternal + Nep171Controller> Nep177Controller for T {
+    fn contract_metadata() -> ContractMetadata {
+        Self::slot_contract_metadata()
+            .read()
+            .unwrap_or_else(|| env::panic_str(NOT_INITIALIZED))
+    }
+
+    fn set_contract_metadata(
+        &mut self,
+        metadata: ContractMetadata,
+    ) -> Result<(), MetadataValidationError> {
+        metadata.validate()?;
+        Self::slot_contract_metadata().write(&metadata);
+        Ok(())
+    }
+
+    fn token_metadata(token_id: &TokenId) -> Option<TokenMetadata> {
+        Self::slot_token_metadata(token_id).read()
+    }
+
+    fn set_token_metadata_unchecked(
+        &mut self,
+        token_id: &TokenId,
+        metadata: Option<&TokenMetadata>,
+    ) {
+        Self::slot_token_metadata(token_id).set(metadata);
+    }
+
+    fn set_token_metadata(
+        &mut self,
+        token_id: &TokenId,
+        metadata: &TokenMetadata,
+    ) -> Result<(), UpdateTokenMetadataError> {
+        if Self::token_owner(token_id).is_none() {
+            return Err(TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            }
+            .into());
+        }
+
+        metadata.validate()?;
+        self.set_token_metadata_unchecked(token_id, Some(metadata));
+
+        Ok(())
+    }
+
+    fn mint_with_metadata(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        metadata: TokenMetadata,
+    ) -> Result<(), MintWithMetadataError> {
+        metadata.validate()?;
+        self.mint(vec![token_id.clone()], owner_id, None)?;
+        self.set_token_metadata_unchecked(&token_id, Some(&metadata));
+        Ok(())
+    }
+}
+
+/// Includes token metadata in `nft_token` responses under the `metadata` key
+/// and removes token metadata when tokens are burned.
+pub struct Nep177Extension;
+
+impl<C: Nep177Controller> Nep171Extension<C> for Nep177Extension {
+    fn on_burn(contract: &mut C, token_ids: &[TokenId], _owner_id: &AccountId) {
+        for token_id in token_ids {
+            contract.set_token_metadata_unchecked(token_id, None);
+        }
+    }
+
+    fn load_token_metadata(
+        _contract: &C,
+        token_id: &TokenId,
+        metadata: &mut HashMap<String, serde_json::Value>,
+    ) {
+        metadata.insert(
+            "metadata".to_string(),
+            serde_json::to_value(C::token_metadata(token_id)).unwrap_or_else(|_| env::abort()),
+        );
+    }
+}
+
+/// Contract that supports the NEP-177 metadata standard
+#[ext_contract(ext_nep177)]
+pub trait Nep177 {
+    /// Returns the metadata struct for this contract.
+    fn nft_metadata(&self) -> ContractMetadata;
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::json_types::Base64VecU8;
+
+    use super::{ContractMetadata, MetadataValidationError, TokenMetadata};
+
+    fn hash() -> Option<Base64VecU8> {
+        Some(vec![0u8; 32].into())
+    }
+
+    #[test]
+    fn contract_metadata_spec() {
+        let mut metadata = ContractMetadata::new("name".into(), "symbol".into(), None);
+        assert_eq!(metadata.validate(), Ok(()));
+
+        metadata.spec = "nft-1.0.0".into();
+        assert_eq!(metadata.validate(), Ok(()));
+
+        for spec in ["ft-1.0.0", "nft-3.0.0", "nft-", "nft-x.0.0"] {
+            metadata.spec = spec.into();
+            assert_eq!(
+                metadata.validate(),
+                Err(MetadataValidationError::UnsupportedSpec { spec: spec.into() }),
+            );
+        }
+    }
+
+    #[test]
+    fn contract_metadata_reference() {
+        let mut metadata = ContractMetadata::new("name".into(), "symbol".into(), None);
+
+        metadata.reference = Some("https://example.com/metadata.json".into());
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataValidationError::HashMismatch { field: "reference" }),
+        );
+
+        metadata.reference_hash = Some(b"short".to_vec().into());
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataValidationError::InvalidHashLength {
+                field: "reference",
+                length: 5,
+            }),
+        );
+
+        metadata.reference_hash = hash();
+        assert_eq!(metadata.validate(), Ok(()));
+
+        metadata.reference = None;
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataValidationError::HashMismatch { field: "reference" }),
+        );
+    }
+
+    #[test]
+    fn token_metadata_media() {
+        let mut metadata = TokenMetadata {
+            media: Some("https://example.com/image.png".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataValidationError::HashMismatch { field: "media" }),
+        );
+
+        metadata.media_hash = hash();
+        assert_eq!(metadata.validate(), Ok(()));
+
+        metadata.reference_hash = hash();
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataValidationError::HashMismatch { field: "reference" }),
+        );
+    }
+}
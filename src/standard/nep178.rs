@@ -0,0 +1,412 @@
+//! NEP-178 non-fungible token approval management implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0178.md>
+//!
+//! Use [`Nep178Extension`] as (part of) the
+//! [`Nep171ControllerInternal::Extension`] to allow approved accounts to
+//! transfer tokens, to clear approvals when tokens are transferred or burned,
+//! and to include `approved_account_ids` in `nft_token` responses.
+//!
+//! [`Nep171ControllerInternal::Extension`]: crate::standard::nep171::Nep171ControllerInternal::Extension
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    env, ext_contract, AccountId, BorshStorageKey, Promise,
+};
+use thiserror::Error;
+
+use crate::{
+    slot::Slot,
+    standard::nep171::{
+        error::{SenderNotApprovedError, TokenDoesNotExistError},
+        Nep171Controller, Nep171Extension, Nep171Transfer, Nep171TransferError, TokenId,
+    },
+    DefaultStorageKey,
+};
+
+/// Type of an approval ID
+pub type ApprovalId = u32;
+
+/// Maximum number of accounts that may be approved for a single token at any
+/// one time
+pub const MAX_APPROVALS: usize = 32;
+
+pub mod error {
+    use near_sdk::AccountId;
+    use thiserror::Error;
+
+    use crate::standard::nep171::TokenId;
+
+    /// Occurs when an account other than the owner of a token attempts to
+    /// manage its approvals
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Account `{account_id}` is not allowed to manage approvals for token `{token_id}`")]
+    pub struct UnauthorizedError {
+        /// The ID of the token in question
+        pub token_id: TokenId,
+        /// The account that attempted to manage approvals
+        pub account_id: AccountId,
+    }
+
+    /// Occurs when revoking an approval that does not exist
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Account `{account_id}` is not approved for token `{token_id}`")]
+    pub struct AccountNotApprovedError {
+        /// The ID of the token in question
+        pub token_id: TokenId,
+        /// The account that is not approved
+        pub account_id: AccountId,
+    }
+
+    /// Occurs when approving an account would exceed
+    /// [`MAX_APPROVALS`](super::MAX_APPROVALS) for a token
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Too many approvals for token `{token_id}`")]
+    pub struct TooManyApprovalsError {
+        /// The ID of the token in question
+        pub token_id: TokenId,
+    }
+}
+
+use error::*;
+
+/// Errors that may occur when approving an account for a token
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep178ApproveError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The account may not manage approvals for the token
+    #[error(transparent)]
+    Unauthorized(#[from] UnauthorizedError),
+    /// The token has too many approvals
+    #[error(transparent)]
+    TooManyApprovals(#[from] TooManyApprovalsError),
+}
+
+/// Errors that may occur when revoking an approval
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep178RevokeError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The account may not manage approvals for the token
+    #[error(transparent)]
+    Unauthorized(#[from] UnauthorizedError),
+    /// The account to revoke is not approved
+    #[error(transparent)]
+    AccountNotApproved(#[from] AccountNotApprovedError),
+}
+
+/// Errors that may occur when revoking all approvals for a token
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep178RevokeAllError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The account may not manage approvals for the token
+    #[error(transparent)]
+    Unauthorized(#[from] UnauthorizedError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    TokenApprovals(&'a str),
+    TokenNextApprovalId(&'a str),
+}
+
+/// Internal functions for [`Nep178Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep178ControllerInternal {
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep178)
+    }
+
+    /// Slot for the approved accounts of a token and their approval IDs
+    fn slot_token_approvals(token_id: &TokenId) -> Slot<HashMap<AccountId, ApprovalId>> {
+        Self::root().field(StorageKey::TokenApprovals(token_id))
+    }
+
+    /// Slot for the next approval ID of a token. Approval IDs are not reused
+    /// while the token exists.
+    fn slot_token_next_approval_id(token_id: &TokenId) -> Slot<ApprovalId> {
+        Self::root().field(StorageKey::TokenNextApprovalId(token_id))
+    }
+}
+
+/// Non-public implementations of functions for managing non-fungible token
+/// approvals.
+pub trait Nep178Controller {
+    /// Returns the accounts approved for a token and their approval IDs.
+    fn approvals_for(token_id: &TokenId) -> HashMap<AccountId, ApprovalId>;
+
+    /// Returns the approval ID of `account_id` for a token, if it is
+    /// approved.
+    fn approval_id_for(token_id: &TokenId, account_id: &AccountId) -> Option<ApprovalId>;
+
+    /// Approves `account_id` for a token and returns the new approval ID. No
+    /// checks.
+    fn approve_unchecked(&mut self, token_id: &TokenId, account_id: &AccountId) -> ApprovalId;
+
+    /// Approves `account_id` for a token on behalf of `current_owner_id`.
+    /// Re-approving an account issues a new approval ID.
+    fn approve(
+        &mut self,
+        token_id: &TokenId,
+        current_owner_id: &AccountId,
+        account_id: &AccountId,
+    ) -> Result<ApprovalId, Nep178ApproveError>;
+
+    /// Revokes the approval of `account_id` for a token. No checks.
+    fn revoke_unchecked(&mut self, token_id: &TokenId, account_id: &AccountId);
+
+    /// Revokes the approval of `account_id` for a token on behalf of
+    /// `current_owner_id`.
+    fn revoke(
+        &mut self,
+        token_id: &TokenId,
+        current_owner_id: &AccountId,
+        account_id: &AccountId,
+    ) -> Result<(), Nep178RevokeError>;
+
+    /// Revokes all approvals for a token. No checks.
+    fn revoke_all_unchecked(&mut self, token_id: &TokenId);
+
+    /// Revokes all approvals for a token on behalf of `current_owner_id`.
+    fn revoke_all(
+        &mut self,
+        token_id: &TokenId,
+        current_owner_id: &AccountId,
+    ) -> Result<(), Nep178RevokeAllError>;
+}
+
+fn require_owner<T: Nep171Controller, E: From<TokenDoesNotExistError> + From<UnauthorizedError>>(
+    token_id: &TokenId,
+    account_id: &AccountId,
+) -> Result<(), E> {
+    let owner_id = T::token_owner(token_id).ok_or_else(|| {
+        E::from(TokenDoesNotExistError {
+            token_id: token_id.clone(),
+        })
+    })?;
+
+    if &owner_id != account_id {
+        return Err(E::from(UnauthorizedError {
+            token_id: token_id.clone(),
+            account_id: account_id.clone(),
+        }));
+    }
+
+    Ok(())
+}
+
+impl<T: Nep178ControllerInternal + Nep171Controller> Nep178Controller for T {
+    fn approvals_for(token_id: &TokenId) -> HashMap<AccountId, ApprovalId> {
+        Self::slot_token_approvals(token_id)
+            .read()
+            .unwrap_or_default()
+    }
+
+    fn approval_id_for(token_id: &TokenId, account_id: &AccountId) -> Option<ApprovalId> {
+        Self::approvals_for(token_id).get(account_id).copied()
+    }
+
+    fn approve_unchecked(&mut self, token_id: &TokenId, account_id: &AccountId) -> ApprovalId {
+        let mut next_approval_id_slot = Self::slot_token_next_approval_id(token_id);
+        let approval_id = next_approval_id_slot.read().unwrap_or(0);
+        next_approval_id_slot.write(
+            &approval_id
+                .checked_add(1)
+                .unwrap_or_else(|| env::panic_str("Approval ID overflow")),
+        );
+
+        let mut approvals = Self::approvals_for(token_id);
+        approvals.insert(account_id.clone(), approval_id);
+        Self::slot_token_approvals(token_id).write(&approvals);
+
+        approval_id
+    }
+
+    fn approve(
+        &mut self,
+        token_id: &TokenId,
+        current_owner_id: &AccountId,
+        account_id: &AccountId,
+    ) -> Result<ApprovalId, Nep178ApproveError> {
+        require_owner::<Self, Nep178ApproveError>(token_id, current_owner_id)?;
+
+        let approvals = Self::approvals_for(token_id);
+        if approvals.len() >= MAX_APPROVALS && !approvals.contains_key(account_id) {
+            return Err(TooManyApprovalsError {
+                token_id: token_id.clone(),
+            }
+            .into());
+        }
+
+        Ok(self.approve_unchecked(token_id, account_id))
+    }
+
+    fn revoke_unchecked(&mut self, token_id: &TokenId, account_id: &AccountId) {
+        let mut approvals = Self::approvals_for(token_id);
+        approvals.remove(account_id);
+
+        let mut approvals_slot = Self::slot_token_approvals(token_id);
+        if approvals.is_empty() {
+            approvals_slot.remove();
+        } else {
+            approvals_slot.write(&approvals);
+        }
+    }
+
+    fn revoke(
+        &mut self,
+        token_id: &TokenId,
+        current_owner_id: &AccountId,
+        account_id: &AccountId,
+    ) -> Result<(), Nep178RevokeError> {
+        require_owner::<Self, Nep178RevokeError>(token_id, current_owner_id)?;
+
+        if Self::approval_id_for(token_id, account_id).is_none() {
+            return Err(AccountNotApprovedError {
+                token_id: token_id.clone(),
+                account_id: account_id.clone(),
+            }
+            .into());
+        }
+
+        self.revoke_unchecked(token_id, account_id);
+
+        Ok(())
+    }
+
+    fn revoke_all_unchecked(&mut self, token_id: &TokenId) {
+        Self::slot_token_approvals(token_id).remove();
+    }
+
+    fn revoke_all(
+        &mut self,
+        token_id: &TokenId,
+        current_owner_id: &AccountId,
+    ) -> Result<(), Nep178RevokeAllError> {
+        require_owner::<Self, Nep178RevokeAllError>(token_id, current_owner_id)?;
+
+        self.revoke_all_unchecked(token_id);
+
+        Ok(())
+    }
+}
+
+/// Allows approved accounts to transfer tokens, clears approvals when tokens
+/// are transferred or burned, restores approvals when an `nft_transfer_call`
+/// is reverted, and includes `approved_account_ids` in `nft_token`
+/// responses.
+///
+/// Storage released by clearing approvals during transfers and burns is not
+/// refunded.
+pub struct Nep178Extension;
+
+impl<C: Nep178ControllerInternal + Nep178Controller> Nep171Extension<C> for Nep178Extension {
+    fn authorize_transfer(
+        _contract: &C,
+        transfer: &Nep171Transfer,
+        _owner_id: &AccountId,
+    ) -> Result<(), Nep171TransferError> {
+        match C::approval_id_for(&transfer.token_id, &transfer.sender_id) {
+            Some(approval_id)
+                if transfer
+                    .approval_id
+                    .map_or(true, |expected| expected == approval_id) =>
+            {
+                Ok(())
+            }
+            _ => Err(SenderNotApprovedError {
+                token_id: transfer.token_id.clone(),
+                sender_id: transfer.sender_id.clone(),
+            }
+            .into()),
+        }
+    }
+
+    fn on_transfer(contract: &mut C, transfer: &Nep171Transfer, _owner_id: &AccountId) {
+        contract.revoke_all_unchecked(&transfer.token_id);
+    }
+
+    fn on_burn(contract: &mut C, token_ids: &[TokenId], _owner_id: &AccountId) {
+        for token_id in token_ids {
+            contract.revoke_all_unchecked(token_id);
+            C::slot_token_next_approval_id(token_id).remove();
+        }
+    }
+
+    fn approved_account_ids(
+        _contract: &C,
+        token_id: &TokenId,
+    ) -> Option<HashMap<AccountId, ApprovalId>> {
+        Some(C::approvals_for(token_id))
+    }
+
+    fn on_transfer_reverted(
+        _contract: &mut C,
+        token_id: &TokenId,
+        _previous_owner_id: &AccountId,
+        approved_account_ids: Option<&HashMap<AccountId, ApprovalId>>,
+    ) {
+        if let Some(approved_account_ids) = approved_account_ids.filter(|a| !a.is_empty()) {
+            C::slot_token_approvals(token_id).write(approved_account_ids);
+        }
+    }
+
+    fn load_token_metadata(
+        _contract: &C,
+        token_id: &TokenId,
+        metadata: &mut HashMap<String, serde_json::Value>,
+    ) {
+        metadata.insert(
+            "approved_account_ids".to_string(),
+            serde_json::to_value(C::approvals_for(token_id)).unwrap_or_else(|_| env::abort()),
+        );
+    }
+}
+
+/// A contract that may be notified when it is approved for a token.
+#[ext_contract(ext_nep178_receiver)]
+pub trait Nep178Receiver {
+    /// Called by `nft_approve` when a `msg` is provided.
+    fn nft_on_approve(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: ApprovalId,
+        msg: String,
+    );
+}
+
+/// Externally-accessible NEP-178-compatible approval management interface.
+#[ext_contract(ext_nep178)]
+pub trait Nep178 {
+    /// Approves `account_id` to transfer a token on behalf of the owner. If
+    /// `msg` is provided, calls `nft_on_approve` on `account_id`.
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise>;
+
+    /// Revokes the approval of `account_id` for a token.
+    fn nft_revoke(&mut self, token_id: TokenId, account_id: AccountId);
+
+    /// Revokes all approvals for a token.
+    fn nft_revoke_all(&mut self, token_id: TokenId);
+
+    /// Returns `true` if `approved_account_id` is approved for a token. If
+    /// `approval_id` is provided, it must match the current approval ID.
+    fn nft_is_approved(
+        &self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<ApprovalId>,
+    ) -> bool;
+}
@@ -0,0 +1,285 @@
+//! NEP-181 non-fungible token enumeration implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0181.md>
+//!
+//! Use [`Nep181Extension`] as (part of) the
+//! [`Nep171ControllerInternal::Extension`] to keep the enumeration indexes
+//! up-to-date as tokens are minted, transferred, and burned.
+//!
+//! Token sets are swap-remove vectors, so a page of `limit` tokens is read
+//! directly from storage starting at `from_index`, without iterating over the
+//! preceding tokens.
+//!
+//! [`Nep171ControllerInternal::Extension`]: crate::standard::nep171::Nep171ControllerInternal::Extension
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    ext_contract,
+    json_types::U128,
+    AccountId, BorshStorageKey,
+};
+
+use crate::{
+    slot::Slot,
+    standard::nep171::{Nep171Extension, Nep171Transfer, Token, TokenId},
+    DefaultStorageKey,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Tokens,
+    OwnerTokens(&'a AccountId),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum TokenSetStorageKey<'a> {
+    Len,
+    Element(u64),
+    Index(&'a str),
+}
+
+/// Set of token IDs stored under a namespace. Removal swaps the last element
+/// into the vacated position, so elements are always contiguous and can be
+/// read by position.
+struct TokenSet(Slot<()>);
+
+impl TokenSet {
+    fn slot_len(&self) -> Slot<u64> {
+        self.0.field(TokenSetStorageKey::Len)
+    }
+
+    fn slot_element(&self, index: u64) -> Slot<TokenId> {
+        self.0.field(TokenSetStorageKey::Element(index))
+    }
+
+    fn slot_index(&self, token_id: &TokenId) -> Slot<u64> {
+        self.0.field(TokenSetStorageKey::Index(token_id))
+    }
+
+    fn len(&self) -> u64 {
+        self.slot_len().read().unwrap_or(0)
+    }
+
+    fn set_len(&self, len: u64) {
+        let mut slot = self.slot_len();
+        if len == 0 {
+            slot.remove();
+        } else {
+            slot.write(&len);
+        }
+    }
+
+    fn insert(&self, token_id: &TokenId) {
+        let mut index_slot = self.slot_index(token_id);
+        if index_slot.exists() {
+            return;
+        }
+
+        let len = self.len();
+        self.slot_element(len).write(token_id);
+        index_slot.write(&len);
+        self.set_len(len + 1);
+    }
+
+    fn remove(&self, token_id: &TokenId) {
+        let index = match self.slot_index(token_id).take() {
+            Some(index) => index,
+            None => return,
+        };
+
+        let last_index = self.len() - 1;
+        let last = self.slot_element(last_index).take();
+
+        if index != last_index {
+            if let Some(last) = last {
+                self.slot_element(index).write(&last);
+                self.slot_index(&last).write(&index);
+            }
+        }
+
+        self.set_len(last_index);
+    }
+
+    fn page(&self, from_index: u64, limit: Option<u64>) -> Vec<TokenId> {
+        let len = self.len();
+        let end = limit
+            .map_or(len, |limit| from_index.saturating_add(limit))
+            .min(len);
+
+        (from_index..end)
+            .filter_map(|index| self.slot_element(index).read())
+            .collect()
+    }
+}
+
+/// Internal functions for [`Nep181Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep181ControllerInternal {
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep181)
+    }
+
+    /// Storage namespace for the set of all tokens
+    fn slot_tokens() -> Slot<()> {
+        Self::root().ns(StorageKey::Tokens)
+    }
+
+    /// Storage namespace for the set of tokens owned by an account
+    fn slot_owner_tokens(owner_id: &AccountId) -> Slot<()> {
+        Self::root().ns(StorageKey::OwnerTokens(owner_id))
+    }
+}
+
+/// Non-public implementations of functions for enumerating non-fungible
+/// tokens.
+pub trait Nep181Controller {
+    /// Adds a token to the enumeration indexes.
+    fn add_token_to_enumeration(&mut self, token_id: &TokenId, owner_id: &AccountId);
+
+    /// Removes a token from the enumeration indexes.
+    fn remove_token_from_enumeration(&mut self, token_id: &TokenId, owner_id: &AccountId);
+
+    /// Moves a token between owners in the enumeration indexes.
+    fn transfer_token_enumeration(
+        &mut self,
+        token_id: &TokenId,
+        from_owner_id: &AccountId,
+        to_owner_id: &AccountId,
+    );
+
+    /// Total number of tokens in existence.
+    fn total_supply() -> u64;
+
+    /// Page of token IDs starting at `from_index`. If `limit` is `None`, all
+    /// remaining tokens are returned.
+    fn tokens(from_index: u64, limit: Option<u64>) -> Vec<TokenId>;
+
+    /// Number of tokens owned by an account.
+    fn supply_for_owner(owner_id: &AccountId) -> u64;
+
+    /// Page of IDs of tokens owned by an account starting at `from_index`.
+    /// If `limit` is `None`, all remaining tokens are returned.
+    fn tokens_for_owner(owner_id: &AccountId, from_index: u64, limit: Option<u64>) -> Vec<TokenId>;
+}
+
+impl<T: Nep181ControllerInternal> Nep181Controller for T {
+    fn add_token_to_enumeration(&mut self, token_id: &TokenId, owner_id: &AccountId) {
+        TokenSet(Self::slot_tokens()).insert(token_id);
+        TokenSet(Self::slot_owner_tokens(owner_id)).insert(token_id);
+    }
+
+    fn remove_token_from_enumeration(&mut self, token_id: &TokenId, owner_id: &AccountId) {
+        TokenSet(Self::slot_tokens()).remove(token_id);
+        TokenSet(Self::slot_owner_tokens(owner_id)).remove(token_id);
+    }
+
+    fn transfer_token_enumeration(
+        &mut self,
+        token_id: &TokenId,
+        from_owner_id: &AccountId,
+        to_owner_id: &AccountId,
+    ) {
+        TokenSet(Self::slot_owner_tokens(from_owner_id)).remove(token_id);
+        TokenSet(Self::slot_owner_tokens(to_owner_id)).insert(token_id);
+    }
+
+    fn total_supply() -> u64 {
+        TokenSet(Self::slot_tokens()).len()
+    }
+
+    fn tokens(from_index: u64, limit: Option<u64>) -> Vec<TokenId> {
+        TokenSet(Self::slot_tokens()).page(from_index, limit)
+    }
+
+    fn supply_for_owner(owner_id: &AccountId) -> u64 {
+        TokenSet(Self::slot_owner_tokens(owner_id)).len()
+    }
+
+    fn tokens_for_owner(owner_id: &AccountId, from_index: u64, limit: Option<u64>) -> Vec<TokenId> {
+        TokenSet(Self::slot_owner_tokens(owner_id)).page(from_index, limit)
+    }
+}
+
+/// Keeps the enumeration indexes up-to-date as tokens are minted,
+/// transferred, and burned.
+pub struct Nep181Extension;
+
+impl<C: Nep181Controller> Nep171Extension<C> for Nep181Extension {
+    fn on_transfer(contract: &mut C, transfer: &Nep171Transfer, owner_id: &AccountId) {
+        contract.transfer_token_enumeration(&transfer.token_id, owner_id, &transfer.receiver_id);
+    }
+
+    fn on_mint(contract: &mut C, token_ids: &[TokenId], owner_id: &AccountId) {
+        for token_id in token_ids {
+            contract.add_token_to_enumeration(token_id, owner_id);
+        }
+    }
+
+    fn on_burn(contract: &mut C, token_ids: &[TokenId], owner_id: &AccountId) {
+        for token_id in token_ids {
+            contract.remove_token_from_enumeration(token_id, owner_id);
+        }
+    }
+}
+
+/// Externally-accessible NEP-181-compatible enumeration interface.
+#[ext_contract(ext_nep181)]
+pub trait Nep181 {
+    /// Total number of tokens in existence.
+    fn nft_total_supply(&self) -> U128;
+
+    /// Page of tokens starting at `from_index` (default: 0). If `limit` is
+    /// not specified, all remaining tokens are returned.
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u32>) -> Vec<Token>;
+
+    /// Number of tokens owned by `account_id`.
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+
+    /// Page of tokens owned by `account_id` starting at `from_index`
+    /// (default: 0). If `limit` is not specified, all remaining tokens are
+    /// returned.
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u32>,
+    ) -> Vec<Token>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenSet;
+    use crate::slot::Slot;
+
+    #[test]
+    fn token_set() {
+        let set = TokenSet(Slot::new(b"s".to_vec()));
+        let ids = ["a", "b", "c", "d"].map(String::from);
+
+        for id in &ids {
+            set.insert(id);
+        }
+        set.insert(&ids[0]);
+
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.page(1, Some(2)), vec!["b", "c"]);
+
+        // Removing from the middle moves the last element into the gap
+        set.remove(&ids[1]);
+        assert_eq!(set.page(0, None), vec!["a", "d", "c"]);
+
+        // Removing the last element
+        set.remove(&ids[2]);
+        assert_eq!(set.page(0, None), vec!["a", "d"]);
+
+        // Removing a missing element has no effect
+        set.remove(&ids[2]);
+        assert_eq!(set.len(), 2);
+
+        set.remove(&ids[0]);
+        set.remove(&ids[3]);
+        assert_eq!(set.len(), 0);
+        assert!(!set.slot_len().exists());
+        assert!(set.page(0, None).is_empty());
+    }
+}
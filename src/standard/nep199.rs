@@ -0,0 +1,442 @@
+//! NEP-199 non-fungible token royalties and payouts implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0199.md>
+//!
+//! Royalties are stored per token as a table of accounts and their shares in
+//! basis points (1/100th of a percent). Use [`Nep199Extension`] as (part of)
+//! the [`Nep171ControllerInternal::Extension`] to remove royalties when
+//! tokens are burned.
+//!
+//! Royalty tables are limited to `max_len_payout - 1` accounts (see:
+//! [`Nep199ControllerInternal::max_len_payout`]), so that payouts, which
+//! also include the owner, can always be requested with up to
+//! `max_len_payout` accounts.
+//!
+//! # Rounding
+//!
+//! Each royalty account receives its share of the balance rounded down. The
+//! owner of the token receives the remainder, so the amounts in a payout
+//! always sum to exactly the balance. Accounts that would receive nothing are
+//! omitted from the payout.
+//!
+//! [`Nep171ControllerInternal::Extension`]: crate::standard::nep171::Nep171ControllerInternal::Extension
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    ext_contract,
+    json_types::U128,
+    AccountId, BorshStorageKey,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    slot::Slot,
+    standard::nep171::{
+        error::TokenDoesNotExistError, Nep171Controller, Nep171Extension, Nep171Transfer,
+        Nep171TransferError, TokenId,
+    },
+    DefaultStorageKey,
+};
+
+/// Type of a royalty share, in basis points (1/100th of a percent)
+pub type BasisPoints = u16;
+
+/// Basis points representing the entire balance (100%)
+pub const TOTAL_BASIS_POINTS: BasisPoints = 10_000;
+
+/// Default maximum number of accounts in a payout, including the owner
+pub const MAX_LEN_PAYOUT: u32 = 10;
+
+/// Table of accounts that receive a share of the proceeds of token sales
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default,
+)]
+pub struct Royalties {
+    /// Accounts and their shares in basis points
+    pub accounts: HashMap<AccountId, BasisPoints>,
+}
+
+impl Royalties {
+    /// Checks that the shares do not exceed [`TOTAL_BASIS_POINTS`] in total.
+    pub fn validate(&self) -> Result<(), RoyaltiesExceedTotalError> {
+        let total_basis_points = self
+            .accounts
+            .values()
+            .map(|basis_points| u32::from(*basis_points))
+            .sum::<u32>();
+
+        if total_basis_points > u32::from(TOTAL_BASIS_POINTS) {
+            return Err(RoyaltiesExceedTotalError { total_basis_points });
+        }
+
+        Ok(())
+    }
+
+    /// Splits `balance` between the royalty accounts and `owner_id`. Fails if
+    /// the payout would include more than `max_len_payout` accounts.
+    ///
+    /// The royalty table is expected to be valid (see [`Royalties::validate`]).
+    /// Otherwise, the owner receives nothing and the amounts may exceed
+    /// `balance` in total.
+    pub fn payout(
+        &self,
+        owner_id: &AccountId,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, PayoutTooLongError> {
+        let mut payout = HashMap::new();
+        let mut remainder = balance;
+
+        for (account_id, basis_points) in &self.accounts {
+            let amount = apply_basis_points(balance, *basis_points);
+            remainder = remainder.saturating_sub(amount);
+            *payout.entry(account_id.clone()).or_insert(0) += amount;
+        }
+
+        *payout.entry(owner_id.clone()).or_insert(0) += remainder;
+        payout.retain(|_, amount| *amount > 0);
+
+        if let Some(max_len_payout) = max_len_payout {
+            if payout.len() > max_len_payout as usize {
+                return Err(PayoutTooLongError {
+                    max_len_payout,
+                    len: payout.len(),
+                });
+            }
+        }
+
+        Ok(Payout {
+            payout: payout
+                .into_iter()
+                .map(|(account_id, amount)| (account_id, amount.into()))
+                .collect(),
+        })
+    }
+}
+
+/// Computes `balance * basis_points / TOTAL_BASIS_POINTS`, rounded down,
+/// without overflowing.
+fn apply_basis_points(balance: u128, basis_points: BasisPoints) -> u128 {
+    let total = u128::from(TOTAL_BASIS_POINTS);
+    let basis_points = u128::from(basis_points);
+
+    balance / total * basis_points + balance % total * basis_points / total
+}
+
+/// NEP-199-compatible payout: the amount each account should receive
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Payout {
+    /// Accounts and the amounts they should receive
+    pub payout: HashMap<AccountId, U128>,
+}
+
+/// Occurs when the shares of a royalty table exceed [`TOTAL_BASIS_POINTS`]
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Royalties total {total_basis_points} basis points, exceeding the maximum of 10000")]
+pub struct RoyaltiesExceedTotalError {
+    /// Total basis points of the royalty table
+    pub total_basis_points: u32,
+}
+
+/// Occurs when a royalty table includes more accounts than a payout may
+/// include besides the owner
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Royalties include {len} accounts, exceeding the maximum of {max_len}")]
+pub struct RoyaltiesTooLongError {
+    /// The maximum number of royalty accounts
+    pub max_len: u32,
+    /// The number of royalty accounts
+    pub len: usize,
+}
+
+/// Occurs when a payout would include more accounts than requested
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("Payout includes {len} accounts, exceeding `max_len_payout` of {max_len_payout}")]
+pub struct PayoutTooLongError {
+    /// The requested maximum number of accounts
+    pub max_len_payout: u32,
+    /// The number of accounts the payout would include
+    pub len: usize,
+}
+
+/// Errors that may occur when setting the royalties of a token
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep199SetRoyaltiesError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The royalty table is invalid
+    #[error(transparent)]
+    RoyaltiesExceedTotal(#[from] RoyaltiesExceedTotalError),
+    /// The royalty table includes too many accounts
+    #[error(transparent)]
+    RoyaltiesTooLong(#[from] RoyaltiesTooLongError),
+}
+
+/// Errors that may occur when computing a payout
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep199PayoutError {
+    /// The token does not exist
+    #[error(transparent)]
+    TokenDoesNotExist(#[from] TokenDoesNotExistError),
+    /// The payout includes too many accounts
+    #[error(transparent)]
+    PayoutTooLong(#[from] PayoutTooLongError),
+}
+
+/// Errors that may occur when transferring a token with a payout
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep199TransferPayoutError {
+    /// The payout could not be computed
+    #[error(transparent)]
+    Payout(#[from] Nep199PayoutError),
+    /// The token could not be transferred
+    #[error(transparent)]
+    Transfer(#[from] Nep171TransferError),
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    TokenRoyalties(&'a str),
+}
+
+/// Internal functions for [`Nep199Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep199ControllerInternal {
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep199)
+    }
+
+    /// Slot for the royalty table of a token
+    fn slot_token_royalties(token_id: &TokenId) -> Slot<Royalties> {
+        Self::root().field(StorageKey::TokenRoyalties(token_id))
+    }
+
+    /// Maximum number of accounts in a payout, including the owner. Royalty
+    /// tables may include at most one account fewer.
+    fn max_len_payout() -> u32 {
+        MAX_LEN_PAYOUT
+    }
+}
+
+/// Non-public implementations of functions for managing non-fungible token
+/// royalties and payouts.
+pub trait Nep199Controller {
+    /// Reads the royalty table of a token from storage.
+    fn token_royalties(token_id: &TokenId) -> Option<Royalties>;
+
+    /// Writes (or removes) the royalty table of a token. No checks.
+    fn set_token_royalties_unchecked(&mut self, token_id: &TokenId, royalties: Option<&Royalties>);
+
+    /// Validates and writes the royalty table of an existing token. Fails if
+    /// the shares exceed [`TOTAL_BASIS_POINTS`], or if the table includes
+    /// more than `max_len_payout - 1` accounts (see:
+    /// [`Nep199ControllerInternal::max_len_payout`]).
+    fn set_token_royalties(
+        &mut self,
+        token_id: &TokenId,
+        royalties: &Royalties,
+    ) -> Result<(), Nep199SetRoyaltiesError>;
+
+    /// Computes the payout of `balance` for the sale of a token by its
+    /// current owner.
+    fn payout(
+        token_id: &TokenId,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199PayoutError>;
+
+    /// Computes the payout of `balance` for the current owner of the token,
+    /// then performs an NEP-171 token transfer, with event emission.
+    fn transfer_payout(
+        &mut self,
+        transfer: &Nep171Transfer,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199TransferPayoutError>;
+}
+
+impl<T: Nep199ControllerInternal + Nep171Controller> Nep199Controller for T {
+    fn token_royalties(token_id: &TokenId) -> Option<Royalties> {
+        Self::slot_token_royalties(token_id).read()
+    }
+
+    fn set_token_royalties_unchecked(&mut self, token_id: &TokenId, royalties: Option<&Royalties>) {
+        Self::slot_token_royalties(token_id).set(royalties);
+    }
+
+    fn set_token_royalties(
+        &mut self,
+        token_id: &TokenId,
+        royalties: &Royalties,
+    ) -> Result<(), Nep199SetRoyaltiesError> {
+        if Self::token_owner(token_id).is_none() {
+            return Err(TokenDoesNotExistError {
+                token_id: token_id.clone(),
+            }
+            .into());
+        }
+
+        royalties.validate()?;
+
+        let max_len = Self::max_len_payout().saturating_sub(1);
+        if royalties.accounts.len() > max_len as usize {
+            return Err(RoyaltiesTooLongError {
+                max_len,
+                len: royalties.accounts.len(),
+            }
+            .into());
+        }
+
+        self.set_token_royalties_unchecked(token_id, Some(royalties));
+
+        Ok(())
+    }
+
+    fn payout(
+        token_id: &TokenId,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199PayoutError> {
+        let owner_id = Self::token_owner(token_id).ok_or_else(|| TokenDoesNotExistError {
+            token_id: token_id.clone(),
+        })?;
+
+        let payout = Self::token_royalties(token_id).unwrap_or_default().payout(
+            &owner_id,
+            balance,
+            max_len_payout,
+        )?;
+
+        Ok(payout)
+    }
+
+    fn transfer_payout(
+        &mut self,
+        transfer: &Nep171Transfer,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Result<Payout, Nep199TransferPayoutError> {
+        let payout = Self::payout(&transfer.token_id, balance, max_len_payout)?;
+        self.transfer(transfer)?;
+        Ok(payout)
+    }
+}
+
+/// Removes royalties when tokens are burned.
+pub struct Nep199Extension;
+
+impl<C: Nep199Controller> Nep171Extension<C> for Nep199Extension {
+    fn on_burn(contract: &mut C, token_ids: &[TokenId], _owner_id: &AccountId) {
+        for token_id in token_ids {
+            contract.set_token_royalties_unchecked(token_id, None);
+        }
+    }
+}
+
+/// Externally-accessible NEP-199-compatible payout interface.
+#[ext_contract(ext_nep199)]
+pub trait Nep199 {
+    /// Returns how `balance` would be split for the sale of a token by its
+    /// current owner. Panics if the payout would include more than
+    /// `max_len_payout` accounts.
+    fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: Option<u32>) -> Payout;
+
+    /// Transfers a token and returns how `balance` should be split between
+    /// the previous owner and the royalty accounts.
+    fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u32>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use near_sdk::AccountId;
+
+    use super::*;
+
+    fn account(id: &str) -> AccountId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn apply_basis_points_rounds_down() {
+        assert_eq!(apply_basis_points(1000, 250), 25);
+        assert_eq!(apply_basis_points(1000, 333), 33);
+        assert_eq!(apply_basis_points(9999, 1), 0);
+        assert_eq!(apply_basis_points(u128::MAX, TOTAL_BASIS_POINTS), u128::MAX);
+        assert_eq!(apply_basis_points(u128::MAX, 5000), u128::MAX / 2);
+    }
+
+    #[test]
+    fn validate() {
+        let mut royalties = Royalties {
+            accounts: HashMap::from([(account("alice"), 6000), (account("bob"), 4000)]),
+        };
+        assert_eq!(royalties.validate(), Ok(()));
+
+        royalties.accounts.insert(account("carol"), 1);
+        assert_eq!(
+            royalties.validate(),
+            Err(RoyaltiesExceedTotalError {
+                total_basis_points: 10_001,
+            }),
+        );
+    }
+
+    #[test]
+    fn payout() {
+        let royalties = Royalties {
+            accounts: HashMap::from([
+                (account("alice"), 250),
+                (account("bob"), 333),
+                (account("owner"), 100),
+            ]),
+        };
+
+        assert_eq!(
+            royalties.payout(&account("owner"), 1000, None),
+            Ok(Payout {
+                payout: HashMap::from([
+                    (account("alice"), 25.into()),
+                    (account("bob"), 33.into()),
+                    (account("owner"), 942.into()),
+                ]),
+            }),
+        );
+
+        assert_eq!(
+            royalties.payout(&account("owner"), 1000, Some(2)),
+            Err(PayoutTooLongError {
+                max_len_payout: 2,
+                len: 3,
+            }),
+        );
+
+        // Zero amounts are omitted
+        assert_eq!(
+            royalties.payout(&account("owner"), 3, Some(1)),
+            Ok(Payout {
+                payout: HashMap::from([(account("owner"), 3.into())]),
+            }),
+        );
+
+        assert_eq!(
+            Royalties::default().payout(&account("owner"), 1000, Some(1)),
+            Ok(Payout {
+                payout: HashMap::from([(account("owner"), 1000.into())]),
+            }),
+        );
+    }
+}
@@ -0,0 +1,917 @@
+//! NEP-245 multi-token core implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0245.md>
+//!
+//! A multi-token contract tracks balances for any number of tokens, each
+//! identified by a [`TokenId`]. A token with a total supply of 1 behaves like
+//! a non-fungible token, and a token with a larger supply like a fungible
+//! token.
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, ext_contract,
+    json_types::U128,
+    AccountId, BorshStorageKey, Gas, Promise, PromiseOrValue, PromiseResult,
+};
+use near_sdk_contract_tools_macros::event;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{slot::Slot, standard::nep297::*, DefaultStorageKey};
+
+/// Type of a multi-token token ID
+pub type TokenId = String;
+
+/// Gas value required for mt_resolve_transfer calls
+pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+/// Gas value required for mt_transfer_call calls (includes gas for
+/// mt_resolve_transfer)
+pub const GAS_FOR_MT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+pub mod error {
+    use near_sdk::{AccountId, Gas};
+    use thiserror::Error;
+
+    use super::TokenId;
+
+    /// Occurs when a batch operation includes no tokens
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Token IDs must not be empty")]
+    pub struct EmptyBatchError;
+
+    /// Occurs when a batch operation includes a different number of token
+    /// IDs and amounts
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Token IDs and amounts must have the same length: got {token_ids} token IDs and {amounts} amounts")]
+    pub struct BatchLengthMismatchError {
+        /// The number of token IDs
+        pub token_ids: usize,
+        /// The number of amounts
+        pub amounts: usize,
+    }
+
+    /// Occurs when the sender's balance of a token is less than the amount
+    /// to be transferred
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Sender balance underflow: account `{account_id}` has a balance of {balance} of token `{token_id}`, which is less than {amount}")]
+    pub struct BalanceUnderflowError {
+        /// The sender
+        pub account_id: AccountId,
+        /// The token
+        pub token_id: TokenId,
+        /// The sender's balance
+        pub balance: u128,
+        /// The amount to be transferred
+        pub amount: u128,
+    }
+
+    /// Occurs when the receiver's balance of a token plus the amount to be
+    /// transferred does not fit in a `u128`
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("Receiver balance overflow: account `{account_id}` has a balance of {balance} of token `{token_id}`, to which {amount} cannot be added")]
+    pub struct BalanceOverflowError {
+        /// The receiver
+        pub account_id: AccountId,
+        /// The token
+        pub token_id: TokenId,
+        /// The receiver's balance
+        pub balance: u128,
+        /// The amount to be transferred
+        pub amount: u128,
+    }
+
+    /// Occurs when a transfer call is not allowed enough gas
+    #[derive(Error, Clone, Debug, PartialEq, Eq)]
+    #[error("More gas is required: {} gas is required, but only {} gas was allowed", .required.0, .gas_allowance.0)]
+    pub struct InsufficientGasError {
+        /// The minimum required gas
+        pub required: Gas,
+        /// The gas allowed for the call
+        pub gas_allowance: Gas,
+    }
+}
+
+use error::*;
+
+/// Errors that may occur when transferring tokens
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep245TransferError {
+    /// The batch includes no tokens
+    #[error(transparent)]
+    EmptyBatch(#[from] EmptyBatchError),
+    /// The numbers of token IDs and amounts differ
+    #[error(transparent)]
+    BatchLengthMismatch(#[from] BatchLengthMismatchError),
+    /// The sender's balance is too small
+    #[error(transparent)]
+    BalanceUnderflow(#[from] BalanceUnderflowError),
+    /// The receiver's balance is too large
+    #[error(transparent)]
+    BalanceOverflow(#[from] BalanceOverflowError),
+}
+
+/// Errors that may occur when performing a transfer call
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Nep245TransferCallError {
+    /// Not enough gas was allowed
+    #[error(transparent)]
+    InsufficientGas(#[from] InsufficientGasError),
+    /// The transfer failed
+    #[error(transparent)]
+    Transfer(#[from] Nep245TransferError),
+}
+
+/// NEP-245 standard events for minting, burning, and transferring tokens
+#[event(
+    crate = "crate",
+    macros = "crate",
+    serde = "serde",
+    standard = "nep245",
+    version = "1.0.0"
+)]
+#[derive(Debug, Clone)]
+pub enum Nep245Event {
+    /// Token mint event. Emitted when tokens are created and their supply is
+    /// increased.
+    MtMint(Vec<event::MtMintData>),
+
+    /// Token transfer event. Emitted when tokens are transferred between two
+    /// accounts. No change to supply.
+    MtTransfer(Vec<event::MtTransferData>),
+
+    /// Token burn event. Emitted when tokens are burned (removed from
+    /// supply).
+    MtBurn(Vec<event::MtBurnData>),
+}
+
+pub mod event {
+    use near_sdk::{json_types::U128, AccountId};
+    use serde::Serialize;
+
+    use super::TokenId;
+
+    /// Individual mint metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct MtMintData {
+        /// Address to which new tokens were minted
+        pub owner_id: AccountId,
+        /// IDs of the minted tokens
+        pub token_ids: Vec<TokenId>,
+        /// Amounts of minted tokens, corresponding to `token_ids`
+        pub amounts: Vec<U128>,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
+    /// Individual transfer metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct MtTransferData {
+        /// Approved account that performed the transfer on behalf of the
+        /// owner, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub authorized_id: Option<AccountId>,
+        /// Account ID of the sender
+        pub old_owner_id: AccountId,
+        /// Account ID of the receiver
+        pub new_owner_id: AccountId,
+        /// IDs of the transferred tokens
+        pub token_ids: Vec<TokenId>,
+        /// Amounts of transferred tokens, corresponding to `token_ids`
+        pub amounts: Vec<U128>,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
+    /// Individual burn metadata
+    #[derive(Serialize, Debug, Clone)]
+    pub struct MtBurnData {
+        /// Account ID from which tokens were burned
+        pub owner_id: AccountId,
+        /// Approved account that performed the burn on behalf of the owner,
+        /// if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub authorized_id: Option<AccountId>,
+        /// IDs of the burned tokens
+        pub token_ids: Vec<TokenId>,
+        /// Amounts of burned tokens, corresponding to `token_ids`
+        pub amounts: Vec<U128>,
+        /// Optional note
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub memo: Option<String>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{super::Nep245Event, *};
+        use crate::standard::nep297::Event;
+
+        #[test]
+        fn mint() {
+            assert_eq!(
+                Nep245Event::MtMint(vec![MtMintData {
+                    owner_id: "foundation.near".parse().unwrap(),
+                    token_ids: vec!["gold".to_string(), "sword".to_string()],
+                    amounts: vec![500u128.into(), 1u128.into()],
+                    memo: None,
+                }])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[{"owner_id":"foundation.near","token_ids":["gold","sword"],"amounts":["500","1"]}]}"#,
+            );
+        }
+
+        #[test]
+        fn transfer() {
+            assert_eq!(
+                Nep245Event::MtTransfer(vec![MtTransferData {
+                    authorized_id: None,
+                    old_owner_id: "from.near".parse().unwrap(),
+                    new_owner_id: "to.near".parse().unwrap(),
+                    token_ids: vec!["gold".to_string()],
+                    amounts: vec![42u128.into()],
+                    memo: Some("hi hello bonjour".to_string()),
+                }])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"from.near","new_owner_id":"to.near","token_ids":["gold"],"amounts":["42"],"memo":"hi hello bonjour"}]}"#,
+            );
+        }
+
+        #[test]
+        fn burn() {
+            assert_eq!(
+                Nep245Event::MtBurn(vec![MtBurnData {
+                    owner_id: "foundation.near".parse().unwrap(),
+                    authorized_id: None,
+                    token_ids: vec!["gold".to_string()],
+                    amounts: vec![100u128.into()],
+                    memo: None,
+                }])
+                .to_event_string(),
+                r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_burn","data":[{"owner_id":"foundation.near","token_ids":["gold"],"amounts":["100"]}]}"#,
+            );
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey<'a> {
+    Supply(&'a str),
+    Balance(&'a str, &'a AccountId),
+}
+
+/// Contracts may implement this trait to inject code into NEP-245 functions.
+///
+/// `T` is an optional value for passing state between different lifecycle
+/// hooks. This may be useful for charging callers for storage usage, for
+/// example.
+pub trait Nep245Hook<T: Default = ()> {
+    /// Executed before a token transfer is conducted
+    ///
+    /// May return an optional state value which will be passed along to the
+    /// following `after_transfer`.
+    fn before_transfer(&mut self, _transfer: &Nep245Transfer) -> T {
+        Default::default()
+    }
+
+    /// Executed after a token transfer is conducted
+    ///
+    /// Receives the state value returned by `before_transfer`.
+    fn after_transfer(&mut self, _transfer: &Nep245Transfer, _state: T) {}
+}
+
+/// Transfer metadata generic over all types of transfer (`mt_transfer`,
+/// `mt_batch_transfer`, `mt_transfer_call`, and `mt_batch_transfer_call`).
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Nep245Transfer {
+    /// Sender's account ID
+    pub sender_id: AccountId,
+    /// Receiver's account ID
+    pub receiver_id: AccountId,
+    /// IDs of the transferred tokens
+    pub token_ids: Vec<TokenId>,
+    /// Transferred amounts, corresponding to `token_ids`
+    pub amounts: Vec<u128>,
+    /// Optional memo string
+    pub memo: Option<String>,
+    /// Message passed to contract located at `receiver_id`
+    pub msg: Option<String>,
+}
+
+impl Nep245Transfer {
+    /// Returns `true` if this transfer comes from a `mt_transfer_call` or
+    /// `mt_batch_transfer_call` call, `false` otherwise
+    pub fn is_transfer_call(&self) -> bool {
+        self.msg.is_some()
+    }
+}
+
+/// Internal functions for [`Nep245Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep245ControllerInternal {
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep245)
+    }
+
+    /// Slot for the balance of a token held by an account
+    fn slot_balance(token_id: &str, account_id: &AccountId) -> Slot<u128> {
+        Self::root().field(StorageKey::Balance(token_id, account_id))
+    }
+
+    /// Slot for the total supply of a token
+    fn slot_supply(token_id: &str) -> Slot<u128> {
+        Self::root().field(StorageKey::Supply(token_id))
+    }
+
+    /// Performs a transfer (`f`), executing any transfer hooks around it. By
+    /// default, no hooks are executed. The derive macro wires this up to the
+    /// [`Nep245Hook`] transfer hooks unless `no_hooks` is specified. If `f`
+    /// fails, the `after_transfer` hook is not executed.
+    fn hook_transfer<E>(
+        contract: &mut Self,
+        _transfer: &Nep245Transfer,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        f(contract)
+    }
+}
+
+/// Performs a transfer, with event emission, executing the transfer hooks
+/// around it
+fn hooked_transfer<T: Nep245ControllerInternal>(
+    contract: &mut T,
+    transfer: &Nep245Transfer,
+) -> Result<(), Nep245TransferError> {
+    T::hook_transfer(contract, transfer, |contract| {
+        check_transfer::<T>(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            &transfer.token_ids,
+            &transfer.amounts,
+        )?;
+
+        for (token_id, amount) in transfer.token_ids.iter().zip(transfer.amounts.iter()) {
+            contract.transfer_unchecked(
+                &transfer.sender_id,
+                &transfer.receiver_id,
+                token_id,
+                *amount,
+            );
+        }
+
+        Nep245Event::MtTransfer(vec![event::MtTransferData {
+            authorized_id: None,
+            old_owner_id: transfer.sender_id.clone(),
+            new_owner_id: transfer.receiver_id.clone(),
+            token_ids: transfer.token_ids.clone(),
+            amounts: transfer.amounts.iter().copied().map(Into::into).collect(),
+            memo: transfer.memo.clone(),
+        }])
+        .emit();
+
+        Ok(())
+    })
+}
+
+fn require_batch(token_ids: &[TokenId], amounts: &[u128]) {
+    check_batch(token_ids, amounts).unwrap_or_else(|e| env::panic_str(&e.to_string()));
+}
+
+fn check_batch(token_ids: &[TokenId], amounts: &[u128]) -> Result<(), Nep245TransferError> {
+    if token_ids.is_empty() {
+        return Err(EmptyBatchError.into());
+    }
+
+    if token_ids.len() != amounts.len() {
+        return Err(BatchLengthMismatchError {
+            token_ids: token_ids.len(),
+            amounts: amounts.len(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Checks that all transfers of a batch can be performed, taking repeated
+/// token IDs into account, without modifying any balances
+fn check_transfer<T: Nep245Controller + ?Sized>(
+    sender_account_id: &AccountId,
+    receiver_account_id: &AccountId,
+    token_ids: &[TokenId],
+    amounts: &[u128],
+) -> Result<(), Nep245TransferError> {
+    check_batch(token_ids, amounts)?;
+
+    let mut sender_balances = HashMap::new();
+    let mut receiver_balances = HashMap::new();
+
+    for (token_id, amount) in token_ids.iter().zip(amounts.iter().copied()) {
+        let sender_balance = sender_balances
+            .entry(token_id)
+            .or_insert_with(|| T::balance_of(sender_account_id, token_id));
+        *sender_balance =
+            sender_balance
+                .checked_sub(amount)
+                .ok_or_else(|| BalanceUnderflowError {
+                    account_id: sender_account_id.clone(),
+                    token_id: token_id.clone(),
+                    balance: *sender_balance,
+                    amount,
+                })?;
+
+        // Transfers to the sender do not change any balances
+        if sender_account_id != receiver_account_id {
+            let receiver_balance = receiver_balances
+                .entry(token_id)
+                .or_insert_with(|| T::balance_of(receiver_account_id, token_id));
+            *receiver_balance =
+                receiver_balance
+                    .checked_add(amount)
+                    .ok_or_else(|| BalanceOverflowError {
+                        account_id: receiver_account_id.clone(),
+                        token_id: token_id.clone(),
+                        balance: *receiver_balance,
+                        amount,
+                    })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Non-public implementations of functions for managing multiple tokens.
+pub trait Nep245Controller {
+    /// Get the balance of a token held by an account. Returns 0 if the
+    /// account does not hold the token.
+    fn balance_of(account_id: &AccountId, token_id: &str) -> u128;
+
+    /// Get the total circulating supply of a token.
+    fn supply(token_id: &str) -> u128;
+
+    /// Removes tokens from an account and decreases the token's supply. No
+    /// event emission.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current balance of `account_id` is less than `amount` or
+    /// if the supply of `token_id` is less than `amount`.
+    fn withdraw_unchecked(&mut self, account_id: &AccountId, token_id: &str, amount: u128);
+
+    /// Increases the token balance of an account. Updates the token's
+    /// supply. No event emission.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the balance of `account_id` plus `amount` >= `u128::MAX`, or
+    /// if the supply of `token_id` plus `amount` >= `u128::MAX`.
+    fn deposit_unchecked(&mut self, account_id: &AccountId, token_id: &str, amount: u128);
+
+    /// Decreases the balance of `sender_account_id` by `amount` and increases
+    /// the balance of `receiver_account_id` by the same. No change to supply.
+    /// No event emission.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the balance of `sender_account_id` < `amount` or if the
+    /// balance of `receiver_account_id` plus `amount` >= `u128::MAX`.
+    fn transfer_unchecked(
+        &mut self,
+        sender_account_id: &AccountId,
+        receiver_account_id: &AccountId,
+        token_id: &str,
+        amount: u128,
+    );
+
+    /// Performs an NEP-245 batch token transfer, with event emission. Fails
+    /// without transferring any tokens if `token_ids` is empty, if
+    /// `token_ids` and `amounts` have different lengths, or if any of the
+    /// transfers would underflow the sender's balance or overflow the
+    /// receiver's balance. Executes transfer hooks (see:
+    /// [`Nep245ControllerInternal::hook_transfer`]).
+    fn transfer(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+    ) -> Result<(), Nep245TransferError>;
+
+    /// Performs an NEP-245 batch token mint, with event emission.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `token_ids` is empty or if `token_ids` and `amounts` have
+    /// different lengths.
+    ///
+    /// See also: `Nep245Controller::deposit_unchecked`
+    fn mint(
+        &mut self,
+        account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+    );
+
+    /// Performs an NEP-245 batch token burn, with event emission.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `token_ids` is empty or if `token_ids` and `amounts` have
+    /// different lengths.
+    ///
+    /// See also: `Nep245Controller::withdraw_unchecked`
+    fn burn(
+        &mut self,
+        account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+    );
+
+    /// Performs an NEP-245 batch token transfer call, with event emission.
+    /// Fails without transferring any tokens if `gas_allowance` <
+    /// `GAS_FOR_MT_TRANSFER_CALL`, or if the transfer fails (see
+    /// `Nep245Controller::transfer`). Executes transfer hooks around the
+    /// transfer, before the receiver is called.
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_call(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+        msg: String,
+        gas_allowance: Gas,
+    ) -> Result<Promise, Nep245TransferCallError>;
+
+    /// Resolves an NEP-245 `mt_transfer_call` or `mt_batch_transfer_call`
+    /// promise chain. Returns the amounts of tokens used by the receiver,
+    /// corresponding to `token_ids`. Refunds do not execute the transfer
+    /// hooks.
+    fn resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+    ) -> Vec<u128>;
+}
+
+impl<T: Nep245ControllerInternal> Nep245Controller for T {
+    fn balance_of(account_id: &AccountId, token_id: &str) -> u128 {
+        Self::slot_balance(token_id, account_id).read().unwrap_or(0)
+    }
+
+    fn supply(token_id: &str) -> u128 {
+        Self::slot_supply(token_id).read().unwrap_or(0)
+    }
+
+    fn withdraw_unchecked(&mut self, account_id: &AccountId, token_id: &str, amount: u128) {
+        if amount != 0 {
+            let balance = Self::balance_of(account_id, token_id);
+            if let Some(balance) = balance.checked_sub(amount) {
+                Self::slot_balance(token_id, account_id).write(&balance);
+            } else {
+                env::panic_str("Balance underflow");
+            }
+
+            let supply = Self::supply(token_id);
+            if let Some(supply) = supply.checked_sub(amount) {
+                Self::slot_supply(token_id).write(&supply);
+            } else {
+                env::panic_str("Supply underflow");
+            }
+        }
+    }
+
+    fn deposit_unchecked(&mut self, account_id: &AccountId, token_id: &str, amount: u128) {
+        if amount != 0 {
+            let balance = Self::balance_of(account_id, token_id);
+            if let Some(balance) = balance.checked_add(amount) {
+                Self::slot_balance(token_id, account_id).write(&balance);
+            } else {
+                env::panic_str("Balance overflow");
+            }
+
+            let supply = Self::supply(token_id);
+            if let Some(supply) = supply.checked_add(amount) {
+                Self::slot_supply(token_id).write(&supply);
+            } else {
+                env::panic_str("Supply overflow");
+            }
+        }
+    }
+
+    fn transfer_unchecked(
+        &mut self,
+        sender_account_id: &AccountId,
+        receiver_account_id: &AccountId,
+        token_id: &str,
+        amount: u128,
+    ) {
+        let sender_balance = Self::balance_of(sender_account_id, token_id);
+
+        if let Some(sender_balance) = sender_balance.checked_sub(amount) {
+            Self::slot_balance(token_id, sender_account_id).write(&sender_balance);
+
+            // Read after writing in case sender and receiver are the same
+            let receiver_balance = Self::balance_of(receiver_account_id, token_id);
+            if let Some(receiver_balance) = receiver_balance.checked_add(amount) {
+                Self::slot_balance(token_id, receiver_account_id).write(&receiver_balance);
+            } else {
+                env::panic_str("Receiver balance overflow");
+            }
+        } else {
+            env::panic_str("Sender balance underflow");
+        }
+    }
+
+    fn transfer(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+    ) -> Result<(), Nep245TransferError> {
+        hooked_transfer(
+            self,
+            &Nep245Transfer {
+                sender_id: sender_account_id,
+                receiver_id: receiver_account_id,
+                token_ids,
+                amounts,
+                memo,
+                msg: None,
+            },
+        )
+    }
+
+    fn mint(
+        &mut self,
+        account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+    ) {
+        require_batch(&token_ids, &amounts);
+
+        for (token_id, amount) in token_ids.iter().zip(amounts.iter()) {
+            self.deposit_unchecked(&account_id, token_id, *amount);
+        }
+
+        Nep245Event::MtMint(vec![event::MtMintData {
+            owner_id: account_id,
+            token_ids,
+            amounts: amounts.into_iter().map(Into::into).collect(),
+            memo,
+        }])
+        .emit();
+    }
+
+    fn burn(
+        &mut self,
+        account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+    ) {
+        require_batch(&token_ids, &amounts);
+
+        for (token_id, amount) in token_ids.iter().zip(amounts.iter()) {
+            self.withdraw_unchecked(&account_id, token_id, *amount);
+        }
+
+        Nep245Event::MtBurn(vec![event::MtBurnData {
+            owner_id: account_id,
+            authorized_id: None,
+            token_ids,
+            amounts: amounts.into_iter().map(Into::into).collect(),
+            memo,
+        }])
+        .emit();
+    }
+
+    fn transfer_call(
+        &mut self,
+        sender_account_id: AccountId,
+        receiver_account_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+        memo: Option<String>,
+        msg: String,
+        gas_allowance: Gas,
+    ) -> Result<Promise, Nep245TransferCallError> {
+        if gas_allowance < GAS_FOR_MT_TRANSFER_CALL {
+            return Err(InsufficientGasError {
+                required: GAS_FOR_MT_TRANSFER_CALL,
+                gas_allowance,
+            }
+            .into());
+        }
+
+        hooked_transfer(
+            self,
+            &Nep245Transfer {
+                sender_id: sender_account_id.clone(),
+                receiver_id: receiver_account_id.clone(),
+                token_ids: token_ids.clone(),
+                amounts: amounts.clone(),
+                memo,
+                msg: Some(msg.clone()),
+            },
+        )?;
+
+        let receiver_gas = Gas(gas_allowance.0 - GAS_FOR_MT_TRANSFER_CALL.0);
+        let previous_owner_ids = vec![sender_account_id.clone(); token_ids.len()];
+        let amounts: Vec<U128> = amounts.into_iter().map(Into::into).collect();
+
+        // Initiating receiver's call and the callback
+        Ok(ext_nep245_receiver::ext(receiver_account_id.clone())
+            .with_static_gas(receiver_gas)
+            .mt_on_transfer(
+                sender_account_id,
+                previous_owner_ids.clone(),
+                token_ids.clone(),
+                amounts.clone(),
+                msg,
+            )
+            .then(
+                ext_nep245_resolver::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .mt_resolve_transfer(
+                        previous_owner_ids,
+                        receiver_account_id,
+                        token_ids,
+                        amounts,
+                    ),
+            ))
+    }
+
+    fn resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<u128>,
+    ) -> Vec<u128> {
+        let unused_amounts = match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(value) => serde_json::from_slice::<Vec<U128>>(&value)
+                .ok()
+                .filter(|unused_amounts| unused_amounts.len() == amounts.len())
+                .map(|unused_amounts| {
+                    unused_amounts
+                        .into_iter()
+                        .zip(amounts.iter())
+                        .map(|(U128(unused_amount), amount)| u128::min(*amount, unused_amount))
+                        .collect()
+                })
+                .unwrap_or_else(|| amounts.clone()),
+            PromiseResult::Failed => amounts.clone(),
+        };
+
+        let mut refunds = vec![];
+
+        let used_amounts = previous_owner_ids
+            .into_iter()
+            .zip(token_ids)
+            .zip(amounts.into_iter().zip(unused_amounts))
+            .map(|((previous_owner_id, token_id), (amount, unused_amount))| {
+                let receiver_balance = Self::balance_of(&receiver_id, &token_id);
+                let refund_amount = u128::min(receiver_balance, unused_amount);
+
+                if refund_amount > 0 {
+                    self.transfer_unchecked(
+                        &receiver_id,
+                        &previous_owner_id,
+                        &token_id,
+                        refund_amount,
+                    );
+
+                    refunds.push(event::MtTransferData {
+                        authorized_id: None,
+                        old_owner_id: receiver_id.clone(),
+                        new_owner_id: previous_owner_id,
+                        token_ids: vec![token_id],
+                        amounts: vec![refund_amount.into()],
+                        memo: None,
+                    });
+                }
+
+                amount - refund_amount
+            })
+            .collect();
+
+        if !refunds.is_empty() {
+            Nep245Event::MtTransfer(refunds).emit();
+        }
+
+        used_amounts
+    }
+}
+
+/// A contract that may be the recipient of an `mt_transfer_call` or
+/// `mt_batch_transfer_call` function call.
+#[ext_contract(ext_nep245_receiver)]
+pub trait Nep245Receiver {
+    /// Function that is called in an `mt_transfer_call` or
+    /// `mt_batch_transfer_call` promise chain. Returns the number of tokens
+    /// that should be returned to the previous owners, corresponding to
+    /// `token_ids`. (The contract will attempt to refund these amounts.)
+    fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
+
+/// Multi-token contract callback after `mt_transfer_call` or
+/// `mt_batch_transfer_call` execution.
+#[ext_contract(ext_nep245_resolver)]
+pub trait Nep245Resolver {
+    /// Callback, last in `mt_transfer_call` promise chain. Returns the
+    /// amounts of tokens used by the receiver, corresponding to `token_ids`.
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128>;
+}
+
+/// Externally-accessible NEP-245-compatible multi-token interface.
+///
+/// Transfers by approved accounts are not supported, so `approval` (or
+/// `approvals`) must be `None`.
+#[ext_contract(ext_nep245)]
+pub trait Nep245 {
+    /// Performs a token transfer
+    fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    );
+
+    /// Performs a batch token transfer
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    );
+
+    /// Performs a token transfer, then initiates a promise chain that calls
+    /// `mt_on_transfer` on the receiving account, followed by
+    /// `mt_resolve_transfer` on the original token contract (this contract).
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise;
+
+    /// Performs a batch token transfer, then initiates a promise chain that
+    /// calls `mt_on_transfer` on the receiving account, followed by
+    /// `mt_resolve_transfer` on the original token contract (this contract).
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise;
+
+    /// Returns the amount of `token_id` controlled by `account_id`
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128;
+
+    /// Returns the amounts of `token_ids` controlled by `account_id`
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128>;
+
+    /// Returns the current total amount of `token_id` tracked by the
+    /// contract
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128>;
+
+    /// Returns the current total amounts of `token_ids` tracked by the
+    /// contract
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>>;
+}
@@ -0,0 +1,85 @@
+//! NEP-330 contract source metadata implementation
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0330.md>
+//!
+//! Source metadata is hardcoded into the contract code. The version of the
+//! code that was replaced by the most recent upgrade may also be recorded
+//! (see: [`Nep330Controller::record_upgrade`]).
+#![allow(missing_docs)] // ext_contract doesn't play nice with #![warn(missing_docs)]
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    ext_contract, BorshStorageKey,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{slot::Slot, DefaultStorageKey};
+
+/// NEP-330-compatible contract source metadata
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Eq, PartialEq, Clone, Debug)]
+pub struct ContractSourceMetadata {
+    /// Version of the contract source code, e.g. a semantic version or a
+    /// commit hash
+    pub version: Option<String>,
+    /// Where the source code can be found, e.g. a repository URL
+    pub link: Option<String>,
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    PreviousVersion,
+}
+
+/// Internal functions for [`Nep330Controller`]. Using these methods may result in unexpected behavior.
+pub trait Nep330ControllerInternal {
+    /// Source metadata of the currently-deployed contract code
+    fn source_metadata() -> ContractSourceMetadata;
+
+    /// Root storage slot
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Nep330)
+    }
+
+    /// Slot for the version of the contract code replaced by the most recent
+    /// upgrade
+    fn slot_previous_version() -> Slot<Option<String>> {
+        Self::root().field(StorageKey::PreviousVersion)
+    }
+}
+
+/// Non-public implementations of functions for managing contract source
+/// metadata.
+pub trait Nep330Controller {
+    /// Source metadata of the currently-deployed contract code.
+    fn contract_source_metadata() -> ContractSourceMetadata;
+
+    /// Version of the contract code replaced by the most recent upgrade.
+    /// Returns `None` if no upgrade has been recorded, and `Some(None)` if the
+    /// replaced code did not specify a version.
+    fn previous_version() -> Option<Option<String>>;
+
+    /// Records the version of the currently-deployed contract code as the
+    /// previous version. Should be called by the currently-deployed code
+    /// immediately before it is replaced by an upgrade.
+    fn record_upgrade(&mut self);
+}
+
+impl<T: Nep330ControllerInternal> Nep330Controller for T {
+    fn contract_source_metadata() -> ContractSourceMetadata {
+        Self::source_metadata()
+    }
+
+    fn previous_version() -> Option<Option<String>> {
+        Self::slot_previous_version().read()
+    }
+
+    fn record_upgrade(&mut self) {
+        Self::slot_previous_version().write(&Self::source_metadata().version);
+    }
+}
+
+/// Contract that supports the NEP-330 source metadata standard
+#[ext_contract(ext_nep330)]
+pub trait Nep330 {
+    /// Returns the source metadata of this contract.
+    fn contract_source_metadata(&self) -> ContractSourceMetadata;
+}
@@ -0,0 +1,263 @@
+//! Transfer fee pattern diverts a fraction of each NEP-141 transfer to a fee
+//! collector account.
+//!
+//! The fee is configured in basis points (1/100 of a percent) of the
+//! transferred amount, and it is always paid by the receiver out of the
+//! transferred tokens. After a transfer, [`TransferFee::charge_transfer_fee`]
+//! transfers the fee from the receiver to the collector, so each transfer
+//! emits two `ft_transfer` events: one for the full amount from the sender to
+//! the receiver, and one for the fee from the receiver to the collector. The
+//! fee transfer executes the NEP-141 transfer hooks like any other transfer.
+//!
+//! This includes transfer calls (`ft_transfer_call`): the receiving contract
+//! is notified of the full amount, but holds the amount less the fee. Since
+//! refunds are capped by the receiver's balance, a full refund returns the
+//! amount less the fee to the sender.
+//!
+//! Transfers from or to exempt accounts, as well as transfers involving the
+//! collector, are not charged.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::TransferFee) derives a
+//! default implementation of [`TransferFee`] and [`TransferFeeExternal`]. Use
+//! `#[transfer_fee(nep141_hook)]` to also derive a [`Nep141Hook`]
+//! implementation that checks the fee before and charges it after each
+//! transfer. To combine this with other hooks, implement [`Nep141Hook`]
+//! instead and call [`TransferFee::before_nep141_transfer`] from its
+//! `before_transfer` and [`TransferFee::charge_transfer_fee`] from its
+//! `after_transfer` (see: [`Nep141Hook`]).
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * Initially, no fee is charged.
+//! * (UB) The transfer fee root storage slot is not used or modified. The default key is `~tf`.
+//! * (ERR) The fee may not exceed 10,000 basis points (100%).
+//! * (ERR) Charged transfers fail if the collector may not hold a balance
+//!   (see: [`Nep141ControllerInternal::is_account_registered`]).
+//!
+//! [`Nep141Hook`]: crate::standard::nep141::Nep141Hook
+#![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, ext_contract, require, AccountId, BorshStorageKey,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    slot::Slot,
+    standard::nep141::{
+        error::AccountNotRegisteredError, Nep141Controller, Nep141ControllerInternal,
+        Nep141Transfer,
+    },
+    DefaultStorageKey,
+};
+
+/// Number of basis points in 100%
+pub const BASIS_POINTS: u16 = 10_000;
+
+const INVALID_FEE_FAIL_MESSAGE: &str = "Transfer fee may not exceed 10000 basis points";
+const FEE_MEMO: &str = "transfer fee";
+
+/// Transfer fee configuration
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct TransferFeeConfig {
+    /// Fee in basis points of the transferred amount
+    pub basis_points: u16,
+    /// Account that receives the fees
+    pub collector_id: AccountId,
+}
+
+impl TransferFeeConfig {
+    /// Fee for transferring `amount` tokens, rounded down.
+    pub fn fee_for(&self, amount: u128) -> u128 {
+        let basis_points = u128::from(self.basis_points);
+        let total = u128::from(BASIS_POINTS);
+
+        // amount * basis_points / total without overflow
+        amount / total * basis_points + amount % total * basis_points / total
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey, Debug, Clone)]
+enum StorageKey {
+    Config,
+    Exempt(AccountId),
+}
+
+/// Internal functions for [`TransferFee`]. Using these methods may result in unexpected behavior.
+pub trait TransferFeeInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::TransferFee)
+    }
+
+    /// Storage slot for the fee configuration
+    fn slot_config() -> Slot<TransferFeeConfig> {
+        Self::root().field(StorageKey::Config)
+    }
+
+    /// Storage slot for the exemption status of an account
+    fn slot_exempt(account_id: &AccountId) -> Slot<bool> {
+        Self::root().field(StorageKey::Exempt(account_id.clone()))
+    }
+}
+
+/// Contract private-only interactions for charging NEP-141 transfer fees.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{near_bindgen, AccountId};
+/// use near_sdk_contract_tools::{
+///     owner::Owner, transfer_fee::TransferFee, FungibleToken, Owner, TransferFee,
+/// };
+///
+/// #[derive(Owner, FungibleToken, TransferFee)]
+/// #[fungible_token(name = "Treasury Token", symbol = "TT", decimals = 18)]
+/// #[transfer_fee(nep141_hook)]
+/// #[near_bindgen]
+/// struct Contract {
+///     // ...
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn set_fee(&mut self, basis_points: u16, collector_id: AccountId) {
+///         Self::require_owner();
+///         self.set_transfer_fee(basis_points, collector_id);
+///     }
+///
+///     pub fn exempt(&mut self, account_id: AccountId) {
+///         Self::require_owner();
+///         self.set_transfer_fee_exempt(&account_id, true);
+///     }
+/// }
+/// ```
+pub trait TransferFee {
+    /// Returns the fee configuration, if a fee is charged
+    fn transfer_fee() -> Option<TransferFeeConfig>;
+
+    /// Charges `basis_points` of each transfer, paid to `collector_id`.
+    /// Panics if `basis_points` exceeds [`BASIS_POINTS`].
+    fn set_transfer_fee(&mut self, basis_points: u16, collector_id: AccountId);
+
+    /// Stops charging fees.
+    fn remove_transfer_fee(&mut self);
+
+    /// Returns `true` if transfers from or to the account are not charged
+    fn is_transfer_fee_exempt(account_id: &AccountId) -> bool;
+
+    /// Sets whether transfers from or to the account are charged.
+    fn set_transfer_fee_exempt(&mut self, account_id: &AccountId, exempt: bool);
+
+    /// Fee charged for a transfer. 0 if no fee is configured, or if the
+    /// transfer is from or to an exempt account or the collector.
+    fn transfer_fee_for(transfer: &Nep141Transfer) -> u128;
+
+    /// Rejects a transfer whose fee cannot be charged. Call from
+    /// [`Nep141Hook::before_transfer`](crate::standard::nep141::Nep141Hook::before_transfer).
+    fn before_nep141_transfer(transfer: &Nep141Transfer);
+
+    /// Charges the fee for a completed transfer to its receiver, returning
+    /// the fee. The fee is transferred using [`Nep141Controller::transfer`],
+    /// emitting an `ft_transfer` event and executing transfer hooks. Call
+    /// from [`Nep141Hook::after_transfer`](crate::standard::nep141::Nep141Hook::after_transfer).
+    fn charge_transfer_fee(&mut self, transfer: &Nep141Transfer) -> u128;
+}
+
+impl<T: TransferFeeInternal + Nep141ControllerInternal> TransferFee for T {
+    fn transfer_fee() -> Option<TransferFeeConfig> {
+        Self::slot_config().read()
+    }
+
+    fn set_transfer_fee(&mut self, basis_points: u16, collector_id: AccountId) {
+        require!(basis_points <= BASIS_POINTS, INVALID_FEE_FAIL_MESSAGE);
+
+        Self::slot_config().write(&TransferFeeConfig {
+            basis_points,
+            collector_id,
+        });
+    }
+
+    fn remove_transfer_fee(&mut self) {
+        Self::slot_config().remove();
+    }
+
+    fn is_transfer_fee_exempt(account_id: &AccountId) -> bool {
+        Self::slot_exempt(account_id).exists()
+    }
+
+    fn set_transfer_fee_exempt(&mut self, account_id: &AccountId, exempt: bool) {
+        let mut slot = Self::slot_exempt(account_id);
+
+        if exempt {
+            slot.write(&true);
+        } else {
+            slot.remove();
+        }
+    }
+
+    fn transfer_fee_for(transfer: &Nep141Transfer) -> u128 {
+        charged_fee::<Self>(transfer).map_or(0, |(_, fee)| fee)
+    }
+
+    fn before_nep141_transfer(transfer: &Nep141Transfer) {
+        if let Some((collector_id, _)) = charged_fee::<Self>(transfer) {
+            if !Self::is_account_registered(&collector_id) {
+                env::panic_str(
+                    &AccountNotRegisteredError {
+                        account_id: collector_id,
+                    }
+                    .to_string(),
+                );
+            }
+        }
+    }
+
+    fn charge_transfer_fee(&mut self, transfer: &Nep141Transfer) -> u128 {
+        match charged_fee::<Self>(transfer) {
+            Some((collector_id, fee)) => {
+                self.transfer(
+                    transfer.receiver_id.clone(),
+                    collector_id,
+                    fee,
+                    Some(FEE_MEMO.to_string()),
+                );
+
+                fee
+            }
+            None => 0,
+        }
+    }
+}
+
+/// Collector and fee for a transfer, if a non-zero fee is charged.
+fn charged_fee<T: TransferFee + ?Sized>(transfer: &Nep141Transfer) -> Option<(AccountId, u128)> {
+    let config = T::transfer_fee()?;
+
+    let is_exempt = |account_id: &AccountId| {
+        account_id == &config.collector_id || T::is_transfer_fee_exempt(account_id)
+    };
+
+    if is_exempt(&transfer.sender_id) || is_exempt(&transfer.receiver_id) {
+        return None;
+    }
+
+    let fee = config.fee_for(transfer.amount);
+
+    (fee > 0).then_some((config.collector_id, fee))
+}
+
+/// External (public) methods for [`TransferFee`]
+#[ext_contract(ext_transfer_fee)]
+pub trait TransferFeeExternal {
+    /// Returns the fee configuration, if a fee is charged
+    fn fee_config(&self) -> Option<TransferFeeConfig>;
+
+    /// Returns `true` if transfers from or to the account are not charged
+    fn fee_is_exempt(&self, account_id: AccountId) -> bool;
+}
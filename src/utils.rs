@@ -1,6 +1,6 @@
 //! Utility functions for storage key generation, storage fee management
 
-use near_sdk::{env, require, Promise};
+use near_sdk::{env, require, AccountId, Promise};
 
 /// Concatenate bytes to form a key. Useful for generating storage keys.
 ///
@@ -78,6 +78,39 @@ pub fn apply_storage_fee_and_refund(
     }
 }
 
+/// Refunds `account_id` the storage fee for storage released by an action,
+/// given an initial storage amount. Returns refund Promise if any storage was
+/// released.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk_contract_tools::utils::refund_released_storage_fee;
+///
+/// near_sdk::env::storage_write(b"key", b"value");
+/// let initial_storage_usage = near_sdk::env::storage_usage();
+///
+/// // Action that releases storage.
+/// near_sdk::env::storage_remove(b"key");
+///
+/// assert!(refund_released_storage_fee(initial_storage_usage, "alice".parse().unwrap()).is_some());
+/// ```
+pub fn refund_released_storage_fee(
+    initial_storage_usage: u64,
+    account_id: AccountId,
+) -> Option<Promise> {
+    let storage_released = initial_storage_usage.saturating_sub(env::storage_usage());
+
+    if storage_released > 0 {
+        Some(
+            Promise::new(account_id)
+                .transfer(u128::from(storage_released) * env::storage_byte_cost()),
+        )
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::prefix_key;
@@ -0,0 +1,390 @@
+//! Vesting pattern implements cliff + linear vesting schedules for NEP-141
+//! fungible tokens.
+//!
+//! Vesting tokens are held in the beneficiary's regular NEP-141 balance, but
+//! they are locked: the beneficiary may not transfer or burn them until they
+//! are claimed. After the cliff, tokens vest linearly until the end of the
+//! schedule, and vested tokens can be claimed using [`Vesting::claim`].
+//! Revocable schedules may be revoked using
+//! [`Vesting::revoke_vesting_schedule`], which releases the vested tokens and
+//! returns the unvested tokens to a given account.
+//!
+//! [`Vesting`] implements methods to manage schedules, emitting the
+//! respective events. [`VestingExternal`] exposes external functions to query
+//! schedules and to claim vested tokens.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::Vesting) derives a
+//! default implementation for both these traits. Use
+//! `#[vesting(nep141_hook)]` to also derive a [`Nep141Hook`] implementation
+//! that enforces the locks. To combine this with other hooks, implement
+//! [`Nep141Hook`] instead and call [`Vesting::before_nep141_transfer`],
+//! [`Vesting::before_nep141_burn`], and [`Vesting::refundable_nep141_amount`]
+//! from it (see: [`Nep141Hook`]).
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) The vesting root storage slot is not used or modified. The default key is `~v`.
+//! * (UB) Locked tokens are only moved by NEP-141 functions that execute
+//!   [`Nep141Hook`] hooks, and the hooks call [`Vesting::before_nep141_transfer`]
+//!   and [`Vesting::before_nep141_burn`]. Transfer call refunds do not execute
+//!   the transfer hooks, so [`Nep141Hook::refundable_amount`] must call
+//!   [`Vesting::refundable_nep141_amount`].
+//! * (ERR) An account may have at most one vesting schedule at a time.
+//! * (ERR) A vesting schedule may only be created for tokens that are already
+//!   in the beneficiary's balance.
+//! * (ERR) Only revocable schedules may be revoked.
+//!
+//! [`Nep141Hook`]: crate::standard::nep141::Nep141Hook
+#![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, ext_contract,
+    json_types::{U128, U64},
+    require, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools_macros::event;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    slot::Slot,
+    standard::{
+        nep141::{Nep141Burn, Nep141Controller, Nep141ControllerInternal, Nep141Transfer},
+        nep297::Event,
+    },
+    DefaultStorageKey,
+};
+
+const SCHEDULE_EXISTS_FAIL_MESSAGE: &str = "Account already has a vesting schedule";
+const NO_SCHEDULE_FAIL_MESSAGE: &str = "Account has no vesting schedule";
+const NOT_REVOCABLE_FAIL_MESSAGE: &str = "Vesting schedule is not revocable";
+const INVALID_SCHEDULE_FAIL_MESSAGE: &str =
+    "Vesting schedule must have a positive duration and a cliff no longer than the duration";
+const INSUFFICIENT_BALANCE_FAIL_MESSAGE: &str = "Insufficient balance to lock";
+const LOCKED_FAIL_MESSAGE: &str = "Cannot move locked tokens";
+
+/// Events emitted when vesting schedules change
+#[event(
+    standard = "x-vest",
+    version = "1.0.0",
+    crate = "crate",
+    macros = "near_sdk_contract_tools_macros"
+)]
+#[derive(Debug, Clone)]
+pub enum VestingEvent {
+    /// Emitted when a vesting schedule is created
+    Create {
+        /// Beneficiary of the schedule
+        account_id: AccountId,
+        /// The new schedule
+        schedule: VestingSchedule,
+    },
+    /// Emitted when vested tokens are claimed
+    Claim {
+        /// Beneficiary of the schedule
+        account_id: AccountId,
+        /// Amount of tokens released
+        amount: U128,
+    },
+    /// Emitted when a vesting schedule is revoked
+    Revoke {
+        /// Beneficiary of the schedule
+        account_id: AccountId,
+        /// Account that received the unvested tokens
+        refund_to: AccountId,
+        /// Amount of unvested tokens returned
+        amount: U128,
+    },
+}
+
+/// A cliff + linear vesting schedule. Timestamps and durations are in
+/// nanoseconds, like [`env::block_timestamp`].
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct VestingSchedule {
+    /// Total amount of tokens vesting
+    pub amount: U128,
+    /// Amount of tokens claimed so far
+    pub released: U128,
+    /// When vesting starts
+    pub start: U64,
+    /// Time after `start` before which no tokens vest
+    pub cliff: U64,
+    /// Time after `start` at which all tokens have vested
+    pub duration: U64,
+    /// Whether the schedule may be revoked
+    pub revocable: bool,
+}
+
+impl VestingSchedule {
+    /// Creates a new schedule with no tokens released.
+    pub fn new(amount: u128, start: u64, cliff: u64, duration: u64, revocable: bool) -> Self {
+        Self {
+            amount: amount.into(),
+            released: 0.into(),
+            start: start.into(),
+            cliff: cliff.into(),
+            duration: duration.into(),
+            revocable,
+        }
+    }
+
+    /// Total amount of tokens vested at `timestamp`, including tokens that
+    /// have already been released.
+    pub fn vested_at(&self, timestamp: u64) -> u128 {
+        let amount = self.amount.0;
+        let duration = self.duration.0 as u128;
+        let elapsed = timestamp.saturating_sub(self.start.0) as u128;
+
+        if elapsed < self.cliff.0 as u128 {
+            0
+        } else if elapsed >= duration {
+            amount
+        } else {
+            // amount * elapsed / duration without overflow
+            amount / duration * elapsed + amount % duration * elapsed / duration
+        }
+    }
+
+    /// Amount of tokens that may be claimed at `timestamp`.
+    pub fn claimable_at(&self, timestamp: u64) -> u128 {
+        self.vested_at(timestamp) - self.released.0
+    }
+
+    /// Amount of tokens that are still locked.
+    pub fn locked(&self) -> u128 {
+        self.amount.0 - self.released.0
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey, Debug, Clone)]
+enum StorageKey {
+    Schedule(AccountId),
+}
+
+/// Internal functions for [`Vesting`]. Using these methods may result in unexpected behavior.
+pub trait VestingInternal {
+    /// Storage root
+    fn root() -> Slot<()> {
+        Slot::new(DefaultStorageKey::Vesting)
+    }
+
+    /// Storage slot for the vesting schedule of an account
+    fn slot_schedule(account_id: &AccountId) -> Slot<VestingSchedule> {
+        Self::root().field(StorageKey::Schedule(account_id.clone()))
+    }
+}
+
+/// Contract private-only interactions for vesting NEP-141 tokens.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{json_types::{U128, U64}, near_bindgen, AccountId};
+/// use near_sdk_contract_tools::{
+///     owner::Owner,
+///     standard::nep141::Nep141Controller,
+///     vesting::{Vesting, VestingSchedule},
+///     FungibleToken, Owner, Vesting,
+/// };
+///
+/// #[derive(Owner, FungibleToken, Vesting)]
+/// #[fungible_token(name = "Vesting Token", symbol = "VEST", decimals = 18)]
+/// #[vesting(nep141_hook)]
+/// #[near_bindgen]
+/// struct Contract {
+///     // ...
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn grant(&mut self, account_id: AccountId, amount: U128, duration: U64) {
+///         Self::require_owner();
+///         self.mint(account_id.clone(), amount.0, None);
+///         self.create_vesting_schedule(
+///             account_id,
+///             VestingSchedule::new(amount.0, near_sdk::env::block_timestamp(), 0, duration.0, true),
+///         );
+///     }
+///
+///     pub fn revoke_grant(&mut self, account_id: AccountId) {
+///         Self::require_owner();
+///         self.revoke_vesting_schedule(account_id, near_sdk::env::predecessor_account_id());
+///     }
+/// }
+/// ```
+pub trait Vesting {
+    /// Returns the vesting schedule of an account, if any
+    fn vesting_schedule_of(account_id: &AccountId) -> Option<VestingSchedule>;
+
+    /// Amount of vested tokens that the account may claim now
+    fn claimable_of(account_id: &AccountId) -> u128;
+
+    /// Amount of tokens in the account's balance that may not be moved
+    fn locked_of(account_id: &AccountId) -> u128;
+
+    /// Locks `schedule.amount` tokens in the balance of `account_id` under
+    /// the given schedule. Panics if the account already has a schedule, if
+    /// the schedule is invalid, or if the account's balance is too small.
+    /// Emits a `VestingEvent::Create` event.
+    fn create_vesting_schedule(&mut self, account_id: AccountId, schedule: VestingSchedule);
+
+    /// Releases all vested tokens of `account_id` that have not been claimed
+    /// yet, returning the amount released. The schedule is removed once all
+    /// tokens have been released. Emits a `VestingEvent::Claim` event if any
+    /// tokens were released.
+    fn claim(&mut self, account_id: AccountId) -> u128;
+
+    /// Revokes the vesting schedule of `account_id`. Vested tokens are
+    /// released, and unvested tokens are transferred to `refund_to`. Returns
+    /// the amount of unvested tokens. Panics if the schedule is not
+    /// revocable. Emits a `VestingEvent::Revoke` event.
+    fn revoke_vesting_schedule(&mut self, account_id: AccountId, refund_to: AccountId) -> u128;
+
+    /// Rejects if removing `amount` tokens from the balance of `account_id`
+    /// would leave less than its locked amount.
+    fn require_unlocked(account_id: &AccountId, amount: u128);
+
+    /// Rejects an NEP-141 transfer of locked tokens. Call from
+    /// [`Nep141Hook::before_transfer`](crate::standard::nep141::Nep141Hook::before_transfer).
+    fn before_nep141_transfer(transfer: &Nep141Transfer);
+
+    /// Rejects an NEP-141 burn of locked tokens. Call from
+    /// [`Nep141Hook::before_burn`](crate::standard::nep141::Nep141Hook::before_burn).
+    fn before_nep141_burn(burn: &Nep141Burn);
+
+    /// Limits an NEP-141 transfer call refund to the unlocked tokens of the
+    /// refunding account. Call from
+    /// [`Nep141Hook::refundable_amount`](crate::standard::nep141::Nep141Hook::refundable_amount).
+    fn refundable_nep141_amount(refund: &Nep141Transfer) -> u128;
+}
+
+impl<T: VestingInternal + Nep141ControllerInternal> Vesting for T {
+    fn vesting_schedule_of(account_id: &AccountId) -> Option<VestingSchedule> {
+        Self::slot_schedule(account_id).read()
+    }
+
+    fn claimable_of(account_id: &AccountId) -> u128 {
+        Self::vesting_schedule_of(account_id)
+            .map_or(0, |schedule| schedule.claimable_at(env::block_timestamp()))
+    }
+
+    fn locked_of(account_id: &AccountId) -> u128 {
+        Self::vesting_schedule_of(account_id).map_or(0, |schedule| schedule.locked())
+    }
+
+    fn create_vesting_schedule(&mut self, account_id: AccountId, schedule: VestingSchedule) {
+        let mut slot = Self::slot_schedule(&account_id);
+
+        require!(!slot.exists(), SCHEDULE_EXISTS_FAIL_MESSAGE);
+        require!(
+            schedule.duration.0 > 0 && schedule.cliff.0 <= schedule.duration.0,
+            INVALID_SCHEDULE_FAIL_MESSAGE,
+        );
+        require!(
+            Self::balance_of(&account_id) >= schedule.locked(),
+            INSUFFICIENT_BALANCE_FAIL_MESSAGE,
+        );
+
+        slot.write(&schedule);
+
+        VestingEvent::Create {
+            account_id,
+            schedule,
+        }
+        .emit();
+    }
+
+    fn claim(&mut self, account_id: AccountId) -> u128 {
+        let mut slot = Self::slot_schedule(&account_id);
+        let mut schedule = slot
+            .read()
+            .unwrap_or_else(|| env::panic_str(NO_SCHEDULE_FAIL_MESSAGE));
+
+        let amount = schedule.claimable_at(env::block_timestamp());
+
+        if amount == 0 {
+            return 0;
+        }
+
+        schedule.released = (schedule.released.0 + amount).into();
+
+        if schedule.locked() == 0 {
+            slot.remove();
+        } else {
+            slot.write(&schedule);
+        }
+
+        VestingEvent::Claim {
+            account_id,
+            amount: amount.into(),
+        }
+        .emit();
+
+        amount
+    }
+
+    fn revoke_vesting_schedule(&mut self, account_id: AccountId, refund_to: AccountId) -> u128 {
+        let schedule = Self::slot_schedule(&account_id)
+            .take()
+            .unwrap_or_else(|| env::panic_str(NO_SCHEDULE_FAIL_MESSAGE));
+
+        require!(schedule.revocable, NOT_REVOCABLE_FAIL_MESSAGE);
+
+        let amount = schedule.amount.0 - schedule.vested_at(env::block_timestamp());
+
+        if amount > 0 {
+            self.transfer(account_id.clone(), refund_to.clone(), amount, None);
+        }
+
+        VestingEvent::Revoke {
+            account_id,
+            refund_to,
+            amount: amount.into(),
+        }
+        .emit();
+
+        amount
+    }
+
+    fn require_unlocked(account_id: &AccountId, amount: u128) {
+        require!(
+            Self::balance_of(account_id).saturating_sub(amount) >= Self::locked_of(account_id),
+            LOCKED_FAIL_MESSAGE,
+        );
+    }
+
+    fn before_nep141_transfer(transfer: &Nep141Transfer) {
+        Self::require_unlocked(&transfer.sender_id, transfer.amount);
+    }
+
+    fn before_nep141_burn(burn: &Nep141Burn) {
+        Self::require_unlocked(&burn.account_id, burn.amount);
+    }
+
+    fn refundable_nep141_amount(refund: &Nep141Transfer) -> u128 {
+        let unlocked =
+            Self::balance_of(&refund.sender_id).saturating_sub(Self::locked_of(&refund.sender_id));
+
+        std::cmp::min(refund.amount, unlocked)
+    }
+}
+
+/// External (public) methods for [`Vesting`]
+#[ext_contract(ext_vesting)]
+pub trait VestingExternal {
+    /// Returns the vesting schedule of an account, if any
+    fn vest_schedule_of(&self, account_id: AccountId) -> Option<VestingSchedule>;
+
+    /// Amount of vested tokens that the account may claim now
+    fn vest_claimable_of(&self, account_id: AccountId) -> U128;
+
+    /// Amount of tokens in the account's balance that may not be moved
+    fn vest_locked_of(&self, account_id: AccountId) -> U128;
+
+    /// Releases the predecessor's vested tokens, returning the amount released
+    fn vest_claim(&mut self) -> U128;
+}
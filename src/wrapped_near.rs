@@ -0,0 +1,142 @@
+//! Wrapped NEAR pattern implements a fungible token that is backed 1:1 by
+//! native NEAR, like `wrap.near`.
+//!
+//! [`WrappedNear`] mints tokens for attached deposits and burns tokens in
+//! exchange for native NEAR using [`Nep141Controller::mint`] and
+//! [`Nep141Controller::burn`], so the usual NEP-141 events are emitted and
+//! mint and burn hooks are executed. [`WrappedNearExternal`] exposes these
+//! functions as `near_deposit` and `near_withdraw`.
+//!
+//! This [derive macro](near_sdk_contract_tools_macros::WrappedNear) derives a
+//! default implementation of [`WrappedNearExternal`]. The contract must
+//! implement NEP-141 with NEP-145 registration as well (e.g. using
+//! `#[derive(FungibleToken)]` with `#[fungible_token(require_registration)]`).
+//!
+//! Storage is paid for so that the contract's balance keeps backing the
+//! total supply: the balance record of an account is paid for by its NEP-145
+//! storage deposit. Any other storage written by a deposit (e.g. the total
+//! supply record, or checkpoints in snapshot mode) is paid for by burning
+//! part of the minted tokens, and storage written by a withdrawal is paid for
+//! out of the withdrawn NEAR.
+//!
+//! # Safety
+//! The default implementation assumes or enforces the following invariants.
+//! Violating assumed invariants may corrupt contract state and show unexpected
+//! behavior (UB). Enforced invariants throw an error (ERR) but contract
+//! state remains intact.
+//!
+//! * (UB) Tokens are not minted or burned other than through [`WrappedNear`],
+//!   so that the total supply is backed by the contract's balance.
+//! * (UB) NEP-141 balances may only be held by accounts registered with
+//!   NEP-145 (see: [`Nep141ControllerInternal::is_account_registered`]), so
+//!   that transfers to new accounts do not write balance records that are
+//!   paid for by the contract's balance.
+//! * (ERR) Deposits require the predecessor to be registered with NEP-145.
+//! * (ERR) The storage fee for the records written by a deposit, other than
+//!   the balance record, is burned from the minted tokens, so the deposit
+//!   must be larger than the fee.
+//! * (ERR) The storage fee for the records written by a withdrawal is
+//!   deducted from the withdrawn NEAR, so the withdrawal must be larger than
+//!   the fee.
+//! * (ERR) Withdrawals require a deposit of exactly 1 yoctoNEAR.
+#![allow(missing_docs)] // #[ext_contract(...)] does not play nicely with clippy
+
+use near_sdk::{assert_one_yocto, env, ext_contract, json_types::U128, require, Promise};
+
+use crate::standard::{
+    nep141::{Nep141Controller, Nep141ControllerInternal},
+    nep145::{error::AccountNotRegisteredError, record_storage_usage, Nep145Controller},
+};
+
+const INSUFFICIENT_DEPOSIT_FAIL_MESSAGE: &str =
+    "Attached deposit must be larger than the storage fee";
+const INSUFFICIENT_WITHDRAWAL_FAIL_MESSAGE: &str = "Withdrawal must be larger than the storage fee";
+const STORAGE_FEE_MEMO: &str = "storage fee";
+
+/// Contract private-only interactions for wrapping native NEAR.
+pub trait WrappedNear {
+    /// Mints tokens to the predecessor for the attached deposit, then burns
+    /// the storage fee for the records written by the mint, other than the
+    /// balance record, from them (memo `"storage fee"`). Returns the
+    /// resulting amount of tokens. The predecessor must be registered with
+    /// NEP-145.
+    fn deposit_near(&mut self) -> u128;
+
+    /// Burns `amount` tokens of the predecessor and transfers the same
+    /// amount of native NEAR, less the storage fee for the records written
+    /// by the burn, to the predecessor. Requires a deposit of exactly 1
+    /// yoctoNEAR.
+    fn withdraw_near(&mut self, amount: u128) -> Promise;
+}
+
+impl<T: Nep141ControllerInternal + Nep145Controller> WrappedNear for T {
+    fn deposit_near(&mut self) -> u128 {
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let deposit = env::attached_deposit();
+
+        if Self::get_storage_balance(&account_id).is_none() {
+            env::panic_str(&AccountNotRegisteredError { account_id }.to_string());
+        }
+
+        // A new balance record is paid for by the NEP-145 storage deposit
+        let balance_slot = Self::slot_account(&account_id);
+        let prepaid_storage_usage = if balance_slot.exists() {
+            0
+        } else {
+            record_storage_usage(&balance_slot, &0)
+        };
+
+        self.mint(account_id.clone(), deposit, None);
+
+        // Any other storage written by the mint (e.g. checkpoints) is paid
+        // for by burning part of the minted tokens, so that the total supply
+        // stays backed by the contract's balance. The attached deposit is
+        // the amount being wrapped, so there is nothing to refund.
+        let storage_fee = u128::from(
+            env::storage_usage()
+                .saturating_sub(initial_storage_usage)
+                .saturating_sub(prepaid_storage_usage),
+        ) * env::storage_byte_cost();
+
+        require!(deposit > storage_fee, INSUFFICIENT_DEPOSIT_FAIL_MESSAGE);
+
+        if storage_fee > 0 {
+            self.burn(account_id, storage_fee, Some(STORAGE_FEE_MEMO.to_string()));
+        }
+
+        deposit - storage_fee
+    }
+
+    fn withdraw_near(&mut self, amount: u128) -> Promise {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+
+        self.burn(account_id.clone(), amount, None);
+
+        // Storage written by the burn (e.g. checkpoints) is paid for by
+        // keeping part of the withdrawn NEAR
+        let storage_fee = u128::from(env::storage_usage().saturating_sub(initial_storage_usage))
+            * env::storage_byte_cost();
+
+        require!(amount > storage_fee, INSUFFICIENT_WITHDRAWAL_FAIL_MESSAGE);
+
+        Promise::new(account_id).transfer(amount - storage_fee)
+    }
+}
+
+/// External (public) methods for [`WrappedNear`]
+#[ext_contract(ext_wrapped_near)]
+pub trait WrappedNearExternal {
+    /// Mints tokens to the predecessor for the attached deposit. The storage
+    /// fee for the records written by the deposit, other than the balance
+    /// record, is burned from the minted tokens. The predecessor must be
+    /// registered with NEP-145.
+    fn near_deposit(&mut self);
+
+    /// Burns `amount` tokens of the predecessor in exchange for the same
+    /// amount of native NEAR, less the storage fee for the records written
+    /// by the withdrawal. Requires a deposit of exactly 1 yoctoNEAR.
+    fn near_withdraw(&mut self, amount: U128) -> Promise;
+}
@@ -0,0 +1,272 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, BorshStorageKey, PromiseResult, RuntimeFeesConfig, VMConfig,
+};
+use near_sdk_contract_tools::{
+    freeze::{Freeze, FreezeExternal},
+    standard::nep141::{Nep141, Nep141Controller},
+    Freeze, FungibleToken,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Freeze,
+}
+
+#[derive(Freeze)]
+#[freeze(storage_key = "StorageKey::Freeze")]
+#[near_bindgen]
+struct Contract {}
+
+#[derive(Freeze, FungibleToken)]
+#[freeze(nep141_hook)]
+#[fungible_token(name = "Freezable Fungible Token", symbol = "FFT", decimals = 18)]
+#[near_bindgen]
+struct FreezableFungibleToken {}
+
+#[test]
+fn derive_freeze() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    assert!(
+        !contract.frz_is_frozen(alice.clone()),
+        "Initial state should be unfrozen",
+    );
+
+    Contract::require_not_frozen(&alice);
+
+    contract.freeze(alice.clone());
+
+    assert!(contract.frz_is_frozen(alice.clone()), "Freezing works");
+    assert!(
+        !contract.frz_is_frozen(bob),
+        "Other accounts are unaffected"
+    );
+
+    Contract::require_frozen(&alice);
+
+    contract.unfreeze(alice.clone());
+
+    assert!(!contract.frz_is_frozen(alice.clone()), "Unfreezing works");
+
+    Contract::require_not_frozen(&alice);
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"x-frz","version":"1.0.0","event":"freeze","data":{"account_id":"alice"}}"#,
+            r#"EVENT_JSON:{"standard":"x-frz","version":"1.0.0","event":"unfreeze","data":{"account_id":"alice"}}"#,
+        ],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Account `alice` is frozen")]
+fn derive_freeze_fail_already_frozen() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+
+    contract.freeze(alice.clone());
+    contract.freeze(alice);
+}
+
+#[test]
+#[should_panic(expected = "Account `alice` is not frozen")]
+fn derive_freeze_fail_not_frozen() {
+    let mut contract = Contract {};
+
+    contract.unfreeze("alice".parse().unwrap());
+}
+
+fn setup_fungible_token() -> (FreezableFungibleToken, AccountId, AccountId) {
+    let mut ft = FreezableFungibleToken {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.mint(alice.clone(), 100, None);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    (ft, alice, bob)
+}
+
+#[test]
+fn nep141_hook_unfrozen() {
+    let (mut ft, alice, bob) = setup_fungible_token();
+
+    ft.freeze("carol".parse().unwrap());
+    ft.ft_transfer(bob.clone(), 50.into(), None);
+    ft.burn(bob.clone(), 10, None);
+
+    assert_eq!(ft.ft_balance_of(alice).0, 50);
+    assert_eq!(ft.ft_balance_of(bob).0, 40);
+}
+
+#[test]
+#[should_panic(expected = "Account `alice` is frozen")]
+fn nep141_hook_frozen_sender() {
+    let (mut ft, alice, bob) = setup_fungible_token();
+
+    ft.freeze(alice);
+    ft.ft_transfer(bob, 50.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Account `bob` is frozen")]
+fn nep141_hook_frozen_receiver() {
+    let (mut ft, _, bob) = setup_fungible_token();
+
+    ft.freeze(bob.clone());
+    ft.ft_transfer(bob, 50.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Account `bob` is frozen")]
+fn nep141_hook_frozen_mint() {
+    let (mut ft, _, bob) = setup_fungible_token();
+
+    ft.freeze(bob.clone());
+    ft.mint(bob, 50, None);
+}
+
+#[test]
+#[should_panic(expected = "Account `alice` is frozen")]
+fn nep141_hook_frozen_burn() {
+    let (mut ft, alice, _) = setup_fungible_token();
+
+    ft.freeze(alice.clone());
+    ft.burn(alice, 50, None);
+}
+
+#[test]
+#[should_panic(expected = "Account `alice` is frozen")]
+fn nep141_hook_frozen_controller_transfer() {
+    let (mut ft, alice, bob) = setup_fungible_token();
+
+    ft.freeze(alice.clone());
+    ft.transfer(alice, bob, 50, None);
+}
+
+fn failed_promise() {
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed],
+    );
+}
+
+#[test]
+fn nep141_hook_frozen_refund() {
+    let (mut ft, alice, bob) = setup_fungible_token();
+
+    ft.transfer(alice.clone(), bob.clone(), 50, None);
+
+    // Frozen receiver
+    ft.freeze(bob.clone());
+    failed_promise();
+    assert_eq!(ft.resolve_transfer(alice.clone(), bob.clone(), 50), 50);
+    assert_eq!(ft.ft_balance_of(bob.clone()).0, 50);
+
+    // Frozen sender
+    ft.unfreeze(bob.clone());
+    ft.freeze(alice.clone());
+    failed_promise();
+    assert_eq!(ft.resolve_transfer(alice.clone(), bob.clone(), 50), 50);
+    assert_eq!(ft.ft_balance_of(bob.clone()).0, 50);
+
+    ft.unfreeze(alice.clone());
+    failed_promise();
+    assert_eq!(ft.resolve_transfer(alice.clone(), bob.clone(), 50), 0);
+    assert_eq!(ft.ft_balance_of(alice).0, 100);
+    assert_eq!(ft.ft_balance_of(bob).0, 0);
+}
+
+mod combined_hooks {
+    use near_sdk_contract_tools::{
+        standard::nep141::{Nep141Burn, Nep141Hook, Nep141Mint, Nep141Transfer},
+        vesting::{Vesting, VestingSchedule},
+        Vesting,
+    };
+
+    use super::*;
+
+    #[derive(Freeze, FungibleToken, Vesting)]
+    #[fungible_token(name = "Combined Fungible Token", symbol = "CFT", decimals = 18)]
+    #[near_bindgen]
+    struct CombinedFungibleToken {}
+
+    impl Nep141Hook for CombinedFungibleToken {
+        fn before_transfer(&mut self, transfer: &Nep141Transfer) {
+            <Self as Freeze>::before_nep141_transfer(transfer);
+            <Self as Vesting>::before_nep141_transfer(transfer);
+        }
+
+        fn before_mint(&mut self, mint: &Nep141Mint) {
+            <Self as Freeze>::before_nep141_mint(mint);
+        }
+
+        fn before_burn(&mut self, burn: &Nep141Burn) {
+            <Self as Freeze>::before_nep141_burn(burn);
+            <Self as Vesting>::before_nep141_burn(burn);
+        }
+
+        fn refundable_amount(&self, refund: &Nep141Transfer) -> u128 {
+            std::cmp::min(
+                <Self as Freeze>::refundable_nep141_amount(refund),
+                <Self as Vesting>::refundable_nep141_amount(refund),
+            )
+        }
+    }
+
+    fn setup() -> (CombinedFungibleToken, AccountId, AccountId) {
+        let mut ft = CombinedFungibleToken {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        ft.mint(alice.clone(), 100, None);
+        ft.create_vesting_schedule(alice.clone(), VestingSchedule::new(60, 0, 0, 1000, false));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(1)
+            .build());
+
+        (ft, alice, bob)
+    }
+
+    #[test]
+    fn unlocked_and_unfrozen() {
+        let (mut ft, alice, bob) = setup();
+
+        ft.ft_transfer(bob.clone(), 40.into(), None);
+
+        assert_eq!(ft.ft_balance_of(alice).0, 60);
+        assert_eq!(ft.ft_balance_of(bob).0, 40);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account `bob` is frozen")]
+    fn frozen() {
+        let (mut ft, _, bob) = setup();
+
+        ft.freeze(bob.clone());
+        ft.ft_transfer(bob, 40.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot move locked tokens")]
+    fn locked() {
+        let (mut ft, _, bob) = setup();
+
+        ft.ft_transfer(bob, 41.into(), None);
+    }
+}
@@ -14,10 +14,16 @@ use near_sdk_contract_tools::{
 };
 
 mod event;
+mod freeze;
 mod migrate;
 mod owner;
 mod pause;
+mod simple_multisig;
 mod standard;
+mod transfer_fee;
+mod vesting;
+mod weighted_multisig;
+mod wrapped_near;
 
 mod my_event {
     use near_sdk::AccountId;
@@ -0,0 +1,122 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, BorshStorageKey,
+};
+use near_sdk_contract_tools::{
+    approval::{
+        self,
+        simple_multisig::{ApprovalState, Configuration},
+        ApprovalManager,
+    },
+    rbac::Rbac,
+    Rbac, SimpleMultisig,
+};
+use serde::Serialize;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+enum Action {
+    SayHello,
+    SayGoodbye,
+}
+
+impl approval::Action<Contract> for Action {
+    type Output = &'static str;
+
+    fn execute(self, _contract: &mut Contract) -> Self::Output {
+        match self {
+            Self::SayHello => "hello",
+            Self::SayGoodbye => "goodbye",
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey, Debug)]
+enum Role {
+    Multisig,
+}
+
+#[derive(Rbac, SimpleMultisig)]
+#[rbac(roles = "Role")]
+#[simple_multisig(action = "Action", role = "Role::Multisig")]
+#[near_bindgen]
+struct Contract {}
+
+fn setup() -> (Contract, AccountId) {
+    let alice: AccountId = "alice".parse().unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .build());
+
+    let mut contract = Contract {};
+    <Contract as ApprovalManager<_, _, _>>::init(Configuration::new(1, 0));
+    contract.add_role(alice.clone(), &Role::Multisig);
+
+    (contract, alice)
+}
+
+#[test]
+fn derive_simple_multisig_list_requests() {
+    let (mut contract, _) = setup();
+
+    assert_eq!(contract.multisig_count_requests(), 0);
+    assert!(contract.multisig_list_requests(None, None).is_empty());
+
+    let hello = contract
+        .create_request(Action::SayHello, ApprovalState::new())
+        .unwrap();
+    let goodbye = contract
+        .create_request(Action::SayGoodbye, ApprovalState::new())
+        .unwrap();
+
+    assert_eq!(contract.multisig_count_requests(), 2);
+
+    let requests = contract.multisig_list_requests(None, None);
+    assert_eq!(
+        requests
+            .iter()
+            .map(|(id, request)| (*id, &request.action))
+            .collect::<Vec<_>>(),
+        vec![(hello, &Action::SayHello), (goodbye, &Action::SayGoodbye)],
+    );
+
+    let page = contract.multisig_list_requests(Some(1), Some(5));
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].0, goodbye);
+
+    contract.approve_request(hello).unwrap();
+    assert_eq!(contract.execute_request(hello).unwrap(), "hello");
+
+    assert_eq!(contract.multisig_count_requests(), 1);
+    let requests = contract.multisig_list_requests(Some(0), Some(1));
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].0, goodbye);
+    assert_eq!(requests[0].1.action, Action::SayGoodbye);
+}
+
+#[test]
+fn derive_simple_multisig_list_requests_limit() {
+    let (mut contract, alice) = setup();
+
+    for _ in 0..approval::MAX_LIST_REQUESTS_LIMIT + 1 {
+        // Fresh context per request to stay under the mocked log limit.
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .build());
+        contract
+            .create_request(Action::SayHello, ApprovalState::new())
+            .unwrap();
+    }
+
+    assert_eq!(
+        contract.multisig_list_requests(None, None).len(),
+        approval::DEFAULT_LIST_REQUESTS_LIMIT as usize,
+    );
+    assert_eq!(
+        contract.multisig_list_requests(None, Some(u32::MAX)).len(),
+        approval::MAX_LIST_REQUESTS_LIMIT as usize,
+    );
+}
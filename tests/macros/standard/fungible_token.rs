@@ -69,3 +69,354 @@ fn metadata() {
         Some(Base64VecU8::from([97, 115, 100, 102].to_vec()))
     );
 }
+
+mod require_registration {
+    use near_sdk::{env, near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+    use near_sdk_contract_tools::{
+        standard::{
+            nep141::{Nep141, Nep141Controller, Nep141ControllerInternal},
+            nep145::{
+                longest_account_id, Nep145, Nep145Controller, Nep145ControllerInternal,
+                StorageBalanceBounds,
+            },
+        },
+        FungibleToken,
+    };
+
+    #[derive(FungibleToken)]
+    #[fungible_token(
+        name = "My Fungible Token",
+        symbol = "MYFT",
+        decimals = 18,
+        no_hooks,
+        require_registration
+    )]
+    #[near_bindgen]
+    struct RegisteredFungibleTokenContract {}
+
+    fn setup() -> (RegisteredFungibleTokenContract, AccountId, AccountId) {
+        let mut ft = RegisteredFungibleTokenContract {};
+        ft.set_storage_balance_bounds(&StorageBalanceBounds {
+            min: 100.into(),
+            max: None,
+        });
+
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(100)
+            .build());
+        ft.storage_deposit(None, None);
+
+        ft.mint(alice.clone(), 100, None);
+
+        (ft, alice, bob)
+    }
+
+    #[test]
+    fn transfer_to_registered() {
+        let (mut ft, alice, bob) = setup();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(100)
+            .build());
+        ft.storage_deposit(Some(bob.clone()), None);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(1)
+            .build());
+        ft.ft_transfer(bob.clone(), 40.into(), None);
+
+        assert_eq!(ft.ft_balance_of(alice).0, 60);
+        assert_eq!(ft.ft_balance_of(bob).0, 40);
+    }
+
+    #[test]
+    #[should_panic = "Account `bob` is not registered"]
+    fn transfer_to_unregistered() {
+        let (mut ft, alice, bob) = setup();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice)
+            .attached_deposit(1)
+            .build());
+        ft.ft_transfer(bob, 40.into(), None);
+    }
+
+    #[test]
+    #[should_panic = "Account `bob` is not registered"]
+    fn mint_to_unregistered() {
+        let (mut ft, _, bob) = setup();
+
+        ft.mint(bob, 40, None);
+    }
+
+    #[test]
+    #[should_panic = "Cannot unregister an account with a positive token balance without `force`"]
+    fn unregister_with_balance() {
+        let (mut ft, alice, _) = setup();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice)
+            .attached_deposit(1)
+            .build());
+        ft.storage_unregister(None);
+    }
+
+    #[test]
+    fn force_unregister_burns_balance() {
+        let (mut ft, alice, _) = setup();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(1)
+            .build());
+        assert!(ft.storage_unregister(Some(true)));
+
+        assert_eq!(ft.ft_balance_of(alice.clone()).0, 0);
+        assert_eq!(ft.ft_total_supply().0, 0);
+        assert_eq!(ft.storage_balance_of(alice.clone()), None);
+        assert!(!RegisteredFungibleTokenContract::slot_account(&alice).exists());
+    }
+
+    mod default_bounds {
+        use super::*;
+
+        #[derive(FungibleToken)]
+        #[fungible_token(
+            name = "My Fungible Token",
+            symbol = "MYFT",
+            decimals = 18,
+            no_hooks,
+            require_registration,
+            nep145_storage_key = "b\"storage\".to_vec()"
+        )]
+        #[near_bindgen]
+        struct DefaultBoundsFungibleTokenContract {}
+
+        #[test]
+        fn default_bounds_cover_account_records() {
+            let mut ft = DefaultBoundsFungibleTokenContract {};
+            let alice: AccountId = "alice".parse().unwrap();
+            let account_id = longest_account_id();
+
+            let min = ft.storage_balance_bounds().min.0;
+
+            // Create the total supply record first
+            testing_env!(VMContextBuilder::new()
+                .predecessor_account_id(alice.clone())
+                .attached_deposit(min)
+                .build());
+            ft.storage_deposit(None, None);
+            ft.mint(alice, 10, None);
+
+            testing_env!(VMContextBuilder::new()
+                .predecessor_account_id(account_id.clone())
+                .attached_deposit(min)
+                .build());
+
+            let initial_storage_usage = env::storage_usage();
+            ft.storage_deposit(None, None);
+            ft.mint(account_id.clone(), 10, None);
+            let storage_usage = env::storage_usage() - initial_storage_usage;
+
+            assert_eq!(
+                storage_usage,
+                DefaultBoundsFungibleTokenContract::storage_usage_per_account(),
+            );
+            assert_eq!(min, u128::from(storage_usage) * env::storage_byte_cost());
+
+            let slot = DefaultBoundsFungibleTokenContract::slot_storage_balance(&account_id);
+            assert!(slot.key.starts_with(b"storage"));
+            assert!(slot.exists());
+        }
+    }
+}
+
+mod mint_burn_owner {
+    use near_sdk::{near_bindgen, test_utils::VMContextBuilder, testing_env, AccountId};
+    use near_sdk_contract_tools::{
+        owner::Owner,
+        standard::nep141::{error::MaxSupplyExceededError, DepositError, Nep141, Nep141Controller},
+        FungibleToken, Owner,
+    };
+
+    #[derive(Owner, FungibleToken)]
+    #[fungible_token(
+        name = "My Fungible Token",
+        symbol = "MYFT",
+        decimals = 18,
+        no_hooks,
+        mint = "owner",
+        burn = "owner",
+        max_supply = "1000"
+    )]
+    #[near_bindgen]
+    struct OwnerFungibleTokenContract {}
+
+    fn call_as(account_id: &AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(1)
+            .build());
+    }
+
+    fn setup() -> (OwnerFungibleTokenContract, AccountId, AccountId) {
+        let mut ft = OwnerFungibleTokenContract {};
+        let owner: AccountId = "owner".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        Owner::init(&mut ft, &owner);
+
+        (ft, owner, alice)
+    }
+
+    #[test]
+    fn mint_burn() {
+        let (mut ft, owner, alice) = setup();
+
+        call_as(&owner);
+        ft.ft_mint(alice.clone(), 600.into(), None);
+        ft.ft_burn(alice.clone(), 200.into(), None);
+
+        assert_eq!(ft.ft_balance_of(alice).0, 400);
+        assert_eq!(ft.ft_total_supply().0, 400);
+    }
+
+    #[test]
+    #[should_panic = "Owner only"]
+    fn mint_unauthorized() {
+        let (mut ft, _, alice) = setup();
+
+        call_as(&alice);
+        ft.ft_mint(alice.clone(), 1.into(), None);
+    }
+
+    #[test]
+    #[should_panic = "Owner only"]
+    fn burn_unauthorized() {
+        let (mut ft, owner, alice) = setup();
+
+        call_as(&owner);
+        ft.ft_mint(alice.clone(), 1.into(), None);
+
+        call_as(&alice);
+        ft.ft_burn(alice.clone(), 1.into(), None);
+    }
+
+    #[test]
+    fn max_supply() {
+        let (mut ft, owner, alice) = setup();
+
+        call_as(&owner);
+        ft.ft_mint(alice, 1000.into(), None);
+
+        assert_eq!(ft.ft_total_supply().0, 1000);
+    }
+
+    #[test]
+    #[should_panic = "Maximum supply exceeded"]
+    fn max_supply_exceeded() {
+        let (mut ft, owner, alice) = setup();
+
+        call_as(&owner);
+        ft.ft_mint(alice.clone(), 1000.into(), None);
+        ft.ft_mint(alice, 1.into(), None);
+    }
+
+    #[test]
+    fn max_supply_controller() {
+        let (mut ft, _, alice) = setup();
+
+        ft.mint(alice.clone(), 600, None);
+
+        assert_eq!(
+            ft.try_mint(alice.clone(), 401, None),
+            Err(DepositError::MaxSupplyExceeded(MaxSupplyExceededError {
+                max_supply: 1000,
+                total_supply: 600,
+                amount: 401,
+            })),
+        );
+        assert_eq!(ft.ft_total_supply().0, 600);
+
+        ft.mint(alice, 400, None);
+
+        assert_eq!(ft.ft_total_supply().0, 1000);
+    }
+
+    #[test]
+    #[should_panic = "Maximum supply exceeded"]
+    fn max_supply_controller_exceeded() {
+        let (mut ft, _, alice) = setup();
+
+        ft.mint(alice, 1001, None);
+    }
+}
+
+mod mint_burn_role {
+    use near_sdk::{
+        borsh::{self, BorshSerialize},
+        near_bindgen,
+        test_utils::VMContextBuilder,
+        testing_env, AccountId, BorshStorageKey,
+    };
+    use near_sdk_contract_tools::{rbac::Rbac, standard::nep141::Nep141, FungibleToken, Rbac};
+
+    #[derive(BorshSerialize, BorshStorageKey)]
+    enum Role {
+        Minter,
+    }
+
+    #[derive(Rbac, FungibleToken)]
+    #[fungible_token(
+        name = "My Fungible Token",
+        symbol = "MYFT",
+        decimals = 18,
+        no_hooks,
+        mint = "role(Role::Minter)",
+        burn = "self"
+    )]
+    #[rbac(roles = "Role")]
+    #[near_bindgen]
+    struct RoleFungibleTokenContract {}
+
+    fn call_as(account_id: &AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(1)
+            .build());
+    }
+
+    #[test]
+    fn mint_self_burn() {
+        let mut ft = RoleFungibleTokenContract {};
+        let minter: AccountId = "minter".parse().unwrap();
+        let alice: AccountId = "alice".parse().unwrap();
+
+        ft.add_role(minter.clone(), &Role::Minter);
+
+        call_as(&minter);
+        ft.ft_mint(alice.clone(), 100.into(), None);
+
+        call_as(&alice);
+        ft.ft_burn(30.into(), None);
+
+        assert_eq!(ft.ft_balance_of(alice).0, 70);
+        assert_eq!(ft.ft_total_supply().0, 70);
+    }
+
+    #[test]
+    #[should_panic = "Unauthorized role"]
+    fn mint_unauthorized() {
+        let mut ft = RoleFungibleTokenContract {};
+        let alice: AccountId = "alice".parse().unwrap();
+
+        call_as(&alice);
+        ft.ft_mint(alice.clone(), 100.into(), None);
+    }
+}
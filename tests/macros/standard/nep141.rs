@@ -3,13 +3,19 @@ use near_sdk::{
     collections::Vector,
     env,
     json_types::U128,
-    log, near_bindgen,
-    test_utils::VMContextBuilder,
-    testing_env, AccountId, PromiseOrValue,
+    log,
+    mock::VmAction,
+    near_bindgen,
+    test_utils::{get_created_receipts, get_logs, VMContextBuilder},
+    testing_env, AccountId, Gas, PromiseOrValue, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    standard::{nep141::*, nep145::Nep145},
+    Nep141, Nep145,
 };
-use near_sdk_contract_tools::{standard::nep141::*, Nep141};
 
 #[derive(Nep141, BorshDeserialize, BorshSerialize)]
+#[nep141(allowance, transfer_batch)]
 #[near_bindgen]
 struct FungibleToken {
     pub transfers: Vector<Nep141Transfer>,
@@ -35,9 +41,39 @@ impl Nep141Hook<HookState> for FungibleToken {
         self.hooks.push(&"after_transfer".to_string());
         println!(
             "Storage delta: {}",
-            env::storage_usage() - state.storage_usage_start
+            env::storage_usage() as i64 - state.storage_usage_start as i64
         );
     }
+
+    fn before_mint(&mut self, mint: &Nep141Mint) -> HookState {
+        self.hooks.push(&format!("before_mint[{}]", mint.amount));
+
+        HookState {
+            storage_usage_start: env::storage_usage(),
+        }
+    }
+
+    fn after_mint(&mut self, mint: &Nep141Mint, _state: HookState) {
+        self.hooks.push(&format!(
+            "after_mint[{}]",
+            Self::balance_of(&mint.account_id)
+        ));
+    }
+
+    fn before_burn(&mut self, burn: &Nep141Burn) -> HookState {
+        self.hooks.push(&format!("before_burn[{}]", burn.amount));
+
+        HookState {
+            storage_usage_start: env::storage_usage(),
+        }
+    }
+
+    fn after_burn(&mut self, burn: &Nep141Burn, _state: HookState) {
+        self.hooks.push(&format!(
+            "after_burn[{}]",
+            Self::balance_of(&burn.account_id)
+        ));
+    }
 }
 
 #[near_bindgen]
@@ -116,3 +152,769 @@ fn nep141_transfer() {
     assert_eq!(ft.ft_balance_of(bob.clone()).0, 70);
     assert_eq!(ft.ft_total_supply().0, 120);
 }
+
+#[test]
+fn nep141_transfer_from() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let carol: AccountId = "carol".parse().unwrap();
+
+    ft.deposit_unchecked(&alice, 100);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(ONE_NEAR)
+        .build());
+
+    let initial_storage_usage = env::storage_usage();
+    ft.ft_approve(bob.clone(), 30.into(), None);
+    let allowance_storage_usage = env::storage_usage() - initial_storage_usage;
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep141-allowance","version":"1.0.0","event":"ft_approve","data":[{"owner_id":"alice","spender_id":"bob","amount":"30"}]}"#,
+        ],
+    );
+    assert_eq!(ft.ft_allowance(alice.clone(), bob.clone()).0, 30);
+    assert_eq!(ft.ft_allowance(bob.clone(), alice.clone()).0, 0);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(bob.clone())
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_transfer_from(alice.clone(), carol.clone(), 20.into(), None);
+
+    assert_eq!(
+        ft.transfers.pop(),
+        Some(Nep141Transfer {
+            sender_id: alice.clone(),
+            receiver_id: carol.clone(),
+            amount: 20,
+            memo: None,
+            msg: None,
+        }),
+    );
+    assert_eq!(ft.hooks.to_vec(), vec!["before_transfer", "after_transfer"]);
+    assert_eq!(ft.ft_allowance(alice.clone(), bob.clone()).0, 10);
+    assert_eq!(ft.ft_balance_of(alice.clone()).0, 80);
+    assert_eq!(ft.ft_balance_of(carol.clone()).0, 20);
+
+    // Spending the entire allowance clears it and refunds its storage fee
+    ft.ft_transfer_from(alice.clone(), carol.clone(), 10.into(), None);
+
+    assert_eq!(ft.ft_allowance(alice.clone(), bob.clone()).0, 0);
+    assert!(!FungibleToken::slot_allowance(&alice, &bob).exists());
+    assert_eq!(ft.ft_balance_of(carol).0, 30);
+    assert_eq!(
+        transfers_to(&alice),
+        vec![u128::from(allowance_storage_usage) * env::storage_byte_cost()],
+    );
+}
+
+fn transfers_to(account_id: &AccountId) -> Vec<u128> {
+    get_created_receipts()
+        .into_iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions)
+        .filter_map(|action| match action {
+            VmAction::Transfer { deposit } => Some(deposit),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn nep141_approve_storage_fee() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(ONE_NEAR)
+        .build());
+
+    let initial_storage_usage = env::storage_usage();
+    ft.ft_approve(bob.clone(), 30.into(), None);
+    let storage_fee =
+        u128::from(env::storage_usage() - initial_storage_usage) * env::storage_byte_cost();
+
+    assert!(storage_fee > 0);
+    assert_eq!(transfers_to(&alice), vec![ONE_NEAR - storage_fee]);
+
+    // Changing an existing allowance does not consume storage
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_approve(bob.clone(), 20.into(), None);
+    assert_eq!(ft.ft_allowance(alice.clone(), bob.clone()).0, 20);
+
+    // Clearing the allowance refunds the storage fee and the attached
+    // deposit
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1000)
+        .build());
+
+    ft.ft_approve(bob.clone(), 0.into(), None);
+
+    assert!(!FungibleToken::slot_allowance(&alice, &bob).exists());
+    assert_eq!(
+        transfers_to(&alice).into_iter().sum::<u128>(),
+        storage_fee + 1000,
+    );
+}
+
+#[test]
+#[should_panic = "Insufficient deposit"]
+fn nep141_approve_new_spender_one_yocto() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id("alice".parse().unwrap())
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_approve("bob".parse().unwrap(), 30.into(), None);
+}
+
+#[test]
+#[should_panic = "Insufficient allowance"]
+fn nep141_transfer_from_insufficient_allowance() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.deposit_unchecked(&alice, 100);
+    ft.approve(alice.clone(), bob.clone(), 30, None);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(bob.clone())
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_transfer_from(alice, bob, 31.into(), None);
+}
+
+#[test]
+fn nep141_transfer_batch() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let carol: AccountId = "carol".parse().unwrap();
+
+    ft.deposit_unchecked(&alice, 100);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_transfer_batch(
+        vec![bob.clone(), carol.clone()],
+        vec![30.into(), 20.into()],
+        Some("payroll".to_string()),
+    );
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"30","memo":"payroll"},{"old_owner_id":"alice","new_owner_id":"carol","amount":"20","memo":"payroll"}]}"#,
+        ],
+    );
+    // The hooks of each transfer are nested inside those of the previous one
+    assert_eq!(
+        ft.hooks.to_vec(),
+        vec![
+            "before_transfer",
+            "before_transfer",
+            "after_transfer",
+            "after_transfer",
+        ],
+    );
+    assert_eq!(
+        ft.transfers.to_vec(),
+        vec![
+            Nep141Transfer {
+                sender_id: alice.clone(),
+                receiver_id: bob.clone(),
+                amount: 30,
+                memo: Some("payroll".to_string()),
+                msg: None,
+            },
+            Nep141Transfer {
+                sender_id: alice.clone(),
+                receiver_id: carol.clone(),
+                amount: 20,
+                memo: Some("payroll".to_string()),
+                msg: None,
+            },
+        ],
+    );
+    assert_eq!(ft.ft_balance_of(alice).0, 50);
+    assert_eq!(ft.ft_balance_of(bob).0, 30);
+    assert_eq!(ft.ft_balance_of(carol).0, 20);
+}
+
+#[test]
+fn nep141_try_transfer_batch_error() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.deposit_unchecked(&alice, 100);
+
+    let transfer = |amount| Nep141Transfer {
+        sender_id: alice.clone(),
+        receiver_id: bob.clone(),
+        amount,
+        memo: None,
+        msg: None,
+    };
+
+    assert_eq!(
+        ft.try_transfer_batch(vec![transfer(60), transfer(60)]),
+        Err(TransferError::BalanceUnderflow(
+            error::BalanceUnderflowError {
+                account_id: alice.clone(),
+                balance: 40,
+                amount: 60,
+            }
+        )),
+    );
+    // The batch is rejected before any transfer is applied
+    assert!(get_logs().is_empty());
+    assert!(ft.hooks.is_empty());
+    assert_eq!(ft.ft_balance_of(alice.clone()).0, 100);
+    assert_eq!(ft.ft_balance_of(bob.clone()).0, 0);
+
+    // Balances larger than the total supply are only possible by writing
+    // directly to storage
+    FungibleToken::slot_account(&bob).write(&(u128::MAX - 60));
+
+    assert_eq!(
+        ft.try_transfer_batch(vec![transfer(50), transfer(40)]),
+        Err(TransferError::BalanceOverflow(
+            error::BalanceOverflowError {
+                account_id: bob.clone(),
+                balance: u128::MAX - 10,
+                amount: 40,
+            }
+        )),
+    );
+    assert!(ft.hooks.is_empty());
+    assert_eq!(ft.ft_balance_of(alice).0, 100);
+    assert_eq!(ft.ft_balance_of(bob).0, u128::MAX - 60);
+}
+
+#[test]
+#[should_panic = "Length mismatch between receiver IDs and amounts"]
+fn nep141_transfer_batch_length_mismatch() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id("alice".parse().unwrap())
+        .attached_deposit(1)
+        .build());
+
+    ft.ft_transfer_batch(vec!["bob".parse().unwrap()], vec![], None);
+}
+
+#[test]
+fn nep141_mint_burn_hooks() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    ft.mint(alice.clone(), 100, None);
+    ft.burn(alice.clone(), 40, None);
+
+    assert_eq!(
+        ft.hooks.to_vec(),
+        vec![
+            "before_mint[100]",
+            "after_mint[100]",
+            "before_burn[40]",
+            "after_burn[60]",
+        ],
+    );
+    assert_eq!(ft.ft_balance_of(alice).0, 60);
+    assert_eq!(ft.ft_total_supply().0, 60);
+}
+
+#[test]
+fn nep141_try_errors() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.mint(alice.clone(), 100, None);
+
+    assert_eq!(
+        ft.try_transfer(alice.clone(), bob.clone(), 101, None),
+        Err(TransferError::BalanceUnderflow(
+            error::BalanceUnderflowError {
+                account_id: alice.clone(),
+                balance: 100,
+                amount: 101,
+            }
+        )),
+    );
+
+    assert_eq!(
+        ft.try_mint(bob.clone(), u128::MAX, None),
+        Err(DepositError::TotalSupplyOverflow(
+            error::TotalSupplyOverflowError {
+                total_supply: 100,
+                amount: u128::MAX,
+            }
+        )),
+    );
+
+    assert_eq!(
+        ft.try_burn(bob.clone(), 1, None),
+        Err(WithdrawError::BalanceUnderflow(
+            error::BalanceUnderflowError {
+                account_id: bob.clone(),
+                balance: 0,
+                amount: 1,
+            }
+        )),
+    );
+
+    assert!(matches!(
+        ft.try_transfer_call(
+            alice.clone(),
+            bob.clone(),
+            10,
+            None,
+            String::new(),
+            near_sdk::Gas(1),
+        ),
+        Err(TransferCallError::InsufficientGas(_)),
+    ));
+
+    assert!(matches!(
+        ft.try_transfer_from(&bob, alice.clone(), bob.clone(), 10, None),
+        Err(TransferFromError::InsufficientAllowance(_)),
+    ));
+
+    // Failed operations have no effect
+    assert_eq!(ft.ft_balance_of(alice).0, 100);
+    assert_eq!(ft.ft_balance_of(bob).0, 0);
+    assert_eq!(ft.ft_total_supply().0, 100);
+    assert_eq!(
+        ft.hooks.to_vec(),
+        vec![
+            "before_mint[100]",
+            "after_mint[100]",
+            "before_transfer",
+            "before_mint[340282366920938463463374607431768211455]",
+            "before_burn[1]"
+        ],
+    );
+}
+
+#[test]
+fn nep141_transfer_call_hooks_receive_msg() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.deposit_unchecked(&alice, 100);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .prepaid_gas(Gas(300_000_000_000_000))
+        .build());
+
+    ft.ft_transfer_call(bob.clone(), 10.into(), None, "hello".to_string());
+
+    let transfer = ft.transfers.pop().unwrap();
+
+    assert!(transfer.is_transfer_call());
+    assert_eq!(
+        transfer,
+        Nep141Transfer {
+            sender_id: alice,
+            receiver_id: bob,
+            amount: 10,
+            memo: None,
+            msg: Some("hello".to_string()),
+        },
+    );
+    assert_eq!(ft.hooks.to_vec(), vec!["before_transfer", "after_transfer"]);
+}
+
+#[test]
+fn nep141_controller_transfer_hooks() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.deposit_unchecked(&alice, 100);
+
+    ft.transfer(alice.clone(), bob.clone(), 10, Some("memo".to_string()));
+
+    assert_eq!(
+        ft.transfers.to_vec(),
+        vec![Nep141Transfer {
+            sender_id: alice.clone(),
+            receiver_id: bob.clone(),
+            amount: 10,
+            memo: Some("memo".to_string()),
+            msg: None,
+        }],
+    );
+    assert_eq!(ft.hooks.to_vec(), vec!["before_transfer", "after_transfer"]);
+
+    // Unchecked transfers do not execute hooks
+    ft.transfer_unchecked(&alice, &bob, 10);
+
+    assert_eq!(ft.hooks.len(), 2);
+    assert_eq!(ft.ft_balance_of(alice).0, 80);
+    assert_eq!(ft.ft_balance_of(bob).0, 20);
+}
+
+#[test]
+#[should_panic = "Balance underflow: account `alice` has a balance of 0, which is less than 1"]
+fn nep141_transfer_panics_with_error_message() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.transfer(alice, bob, 1, None);
+}
+
+#[derive(Nep141, BorshDeserialize, BorshSerialize)]
+#[nep141(
+    no_hooks,
+    storage_key = "b\"custom_gas\".to_vec()",
+    gas_for_resolve_transfer = "Gas(10_000_000_000_000)",
+    gas_for_ft_transfer_call = "Gas(50_000_000_000_000)"
+)]
+#[near_bindgen]
+struct CustomGasFungibleToken {}
+
+/// Static gas attached to each function call created by the contract
+fn function_call_gas() -> Vec<(String, Gas)> {
+    get_created_receipts()
+        .into_iter()
+        .flat_map(|receipt| receipt.actions)
+        .filter_map(|action| match action {
+            VmAction::FunctionCall {
+                function_name, gas, ..
+            } => Some((function_name, gas)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn nep141_transfer_call_gas() {
+    let mut ft = FungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.mint(alice.clone(), 100, None);
+
+    let prepaid_gas = Gas(300_000_000_000_000);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice)
+        .attached_deposit(1)
+        .prepaid_gas(prepaid_gas)
+        .build());
+
+    ft.ft_transfer_call(bob, 10.into(), None, String::new());
+
+    let gas = function_call_gas();
+
+    assert_eq!(
+        gas,
+        vec![
+            (
+                "ft_on_transfer".to_string(),
+                prepaid_gas - GAS_FOR_FT_TRANSFER_CALL,
+            ),
+            ("ft_resolve_transfer".to_string(), GAS_FOR_RESOLVE_TRANSFER),
+        ],
+    );
+
+    // The rest of the reserved gas is left for the transfer call itself
+    let attached_gas = gas.iter().map(|(_, gas)| gas.0).sum::<u64>();
+    assert_eq!(
+        prepaid_gas.0 - attached_gas,
+        GAS_FOR_FT_TRANSFER_CALL.0 - GAS_FOR_RESOLVE_TRANSFER.0,
+    );
+}
+
+#[test]
+fn nep141_transfer_call_custom_gas() {
+    let mut ft = CustomGasFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.mint(alice.clone(), 100, None);
+
+    assert_eq!(
+        ft.try_transfer_call(
+            alice.clone(),
+            bob.clone(),
+            10,
+            None,
+            String::new(),
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .err(),
+        Some(TransferCallError::InsufficientGas(
+            error::InsufficientGasError {
+                required: Gas(50_000_000_000_000),
+                gas_allowance: GAS_FOR_FT_TRANSFER_CALL,
+            }
+        )),
+    );
+
+    ft.transfer_call(alice, bob, 10, None, String::new(), Gas(80_000_000_000_000));
+
+    assert_eq!(
+        function_call_gas(),
+        vec![
+            ("ft_on_transfer".to_string(), Gas(30_000_000_000_000)),
+            ("ft_resolve_transfer".to_string(), Gas(10_000_000_000_000)),
+        ],
+    );
+}
+
+#[derive(Nep141, BorshDeserialize, BorshSerialize)]
+#[nep141(
+    no_hooks,
+    storage_key = "b\"invalid_gas\".to_vec()",
+    gas_for_resolve_transfer = "Gas(10_000_000_000_000)",
+    gas_for_ft_transfer_call = "Gas(5_000_000_000_000)"
+)]
+#[near_bindgen]
+struct InvalidGasFungibleToken {}
+
+#[test]
+fn nep141_transfer_call_invalid_gas_configuration() {
+    let mut ft = InvalidGasFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    ft.mint(alice.clone(), 100, None);
+
+    assert_eq!(
+        ft.try_transfer_call(
+            alice.clone(),
+            bob,
+            10,
+            None,
+            String::new(),
+            Gas(300_000_000_000_000),
+        )
+        .err(),
+        Some(TransferCallError::InvalidGasConfiguration(
+            error::InvalidGasConfigurationError {
+                gas_for_ft_transfer_call: Gas(5_000_000_000_000),
+                gas_for_resolve_transfer: Gas(10_000_000_000_000),
+            }
+        )),
+    );
+    assert_eq!(ft.ft_balance_of(alice).0, 100);
+}
+
+#[derive(Nep141, Nep145)]
+#[nep141(
+    storage_key = "b\"snapshot\".to_vec()",
+    no_hooks,
+    require_registration,
+    snapshot
+)]
+#[nep145(storage_key = "b\"snapshot_storage\".to_vec()")]
+#[near_bindgen]
+struct SnapshotFungibleToken {}
+
+fn register_with(ft: &mut SnapshotFungibleToken, account_id: &AccountId, deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .attached_deposit(deposit)
+        .build());
+    ft.storage_deposit(None, None);
+}
+
+fn at_block(block_height: u64) {
+    testing_env!(VMContextBuilder::new()
+        .block_index(block_height)
+        .predecessor_account_id("alice".parse().unwrap())
+        .attached_deposit(1)
+        .build());
+}
+
+#[test]
+fn nep141_snapshot() {
+    let mut ft = SnapshotFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    register_with(&mut ft, &alice, ONE_NEAR);
+    register_with(&mut ft, &bob, ONE_NEAR);
+
+    at_block(10);
+    ft.mint(alice.clone(), 100, None);
+
+    at_block(20);
+    ft.ft_transfer(bob.clone(), 30.into(), None);
+    // Multiple changes in one block are recorded in a single checkpoint
+    ft.ft_transfer(bob.clone(), 10.into(), None);
+
+    at_block(30);
+    ft.burn(bob.clone(), 40, None);
+
+    assert_eq!(
+        SnapshotFungibleToken::slot_balance_checkpoint_count(&bob).read(),
+        Some(2),
+    );
+
+    let balances_at = |block_height: u64| {
+        (
+            ft.ft_balance_of_at(alice.clone(), block_height.into()).0,
+            ft.ft_balance_of_at(bob.clone(), block_height.into()).0,
+            ft.ft_total_supply_at(block_height.into()).0,
+        )
+    };
+
+    assert_eq!(balances_at(0), (0, 0, 0));
+    assert_eq!(balances_at(9), (0, 0, 0));
+    assert_eq!(balances_at(10), (100, 0, 100));
+    assert_eq!(balances_at(15), (100, 0, 100));
+    assert_eq!(balances_at(20), (60, 40, 100));
+    assert_eq!(balances_at(29), (60, 40, 100));
+    assert_eq!(balances_at(30), (60, 0, 60));
+    assert_eq!(balances_at(1000), (60, 0, 60));
+}
+
+#[test]
+fn nep141_snapshot_charges_storage() {
+    let mut ft = SnapshotFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    register_with(&mut ft, &alice, ONE_NEAR);
+    register_with(&mut ft, &bob, ONE_NEAR);
+
+    at_block(10);
+    ft.mint(alice.clone(), 100, None);
+
+    let available = |ft: &SnapshotFungibleToken, account_id: &AccountId| {
+        ft.storage_balance_of(account_id.clone())
+            .unwrap()
+            .available
+            .0
+    };
+    let alice_available = available(&ft, &alice);
+    let bob_available = available(&ft, &bob);
+
+    at_block(20);
+    let initial_storage_usage = env::storage_usage();
+    ft.ft_transfer(bob.clone(), 30.into(), None);
+    let storage_fee =
+        u128::from(env::storage_usage() - initial_storage_usage) * env::storage_byte_cost();
+
+    assert!(storage_fee > 0);
+    assert_eq!(available(&ft, &alice), alice_available - storage_fee);
+    assert_eq!(available(&ft, &bob), bob_available);
+
+    // Checkpoints in the same block are overwritten, and not charged again
+    ft.ft_transfer(bob.clone(), 10.into(), None);
+
+    assert_eq!(available(&ft, &alice), alice_available - storage_fee);
+}
+
+#[test]
+#[should_panic]
+fn nep141_snapshot_insufficient_storage_balance() {
+    let mut ft = SnapshotFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let min_deposit = ft.storage_balance_bounds().min.0;
+    register_with(&mut ft, &alice, min_deposit);
+    register_with(&mut ft, &bob, min_deposit);
+
+    at_block(10);
+    ft.mint(alice.clone(), 100, None);
+
+    at_block(20);
+    ft.ft_transfer(bob, 30.into(), None);
+}
+
+#[test]
+fn nep141_snapshot_disabled() {
+    let mut ft = CustomGasFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    at_block(10);
+    ft.mint(alice.clone(), 100, None);
+
+    assert_eq!(CustomGasFungibleToken::balance_of_at(&alice, 10), 0);
+    assert!(!CustomGasFungibleToken::slot_balance_checkpoint_count(&alice).exists());
+}
@@ -0,0 +1,204 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId,
+};
+use near_sdk_contract_tools::{standard::nep145::*, Nep145};
+
+#[derive(Nep145, BorshDeserialize, BorshSerialize)]
+#[near_bindgen]
+struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new() -> Self {
+        let mut contract = Self {};
+
+        contract.set_storage_balance_bounds(&StorageBalanceBounds {
+            min: 100.into(),
+            max: Some(500.into()),
+        });
+
+        contract
+    }
+}
+
+fn context(predecessor: &AccountId, attached_deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(predecessor.clone())
+        .attached_deposit(attached_deposit)
+        .build());
+}
+
+#[test]
+fn nep145_deposit() {
+    let mut contract = Contract::new();
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    assert_eq!(
+        contract.storage_balance_bounds(),
+        StorageBalanceBounds {
+            min: 100.into(),
+            max: Some(500.into()),
+        },
+    );
+    assert_eq!(contract.storage_balance_of(alice.clone()), None);
+
+    context(&alice, 150);
+    let balance = contract.storage_deposit(None, None);
+    assert_eq!(
+        balance,
+        StorageBalance {
+            total: 150.into(),
+            available: 50.into(),
+        },
+    );
+    assert_eq!(contract.storage_balance_of(alice.clone()), Some(balance));
+
+    // Deposits beyond the maximum are not accepted
+    context(&alice, 1000);
+    let balance = contract.storage_deposit(None, None);
+    assert_eq!(
+        balance,
+        StorageBalance {
+            total: 500.into(),
+            available: 400.into(),
+        },
+    );
+
+    // Alice registers Bob, depositing only the minimum
+    context(&alice, 300);
+    let balance = contract.storage_deposit(Some(bob.clone()), Some(true));
+    assert_eq!(
+        balance,
+        StorageBalance {
+            total: 100.into(),
+            available: 0.into(),
+        },
+    );
+}
+
+#[test]
+#[should_panic = "does not meet the minimum storage balance"]
+fn nep145_deposit_below_minimum() {
+    let mut contract = Contract::new();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    context(&alice, 99);
+    contract.storage_deposit(None, None);
+}
+
+#[test]
+fn nep145_withdraw_and_unregister() {
+    let mut contract = Contract::new();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    context(&alice, 300);
+    contract.storage_deposit(None, None);
+
+    context(&alice, 1);
+    let balance = contract.storage_withdraw(Some(50.into()));
+    assert_eq!(
+        balance,
+        StorageBalance {
+            total: 250.into(),
+            available: 150.into(),
+        },
+    );
+
+    let balance = contract.storage_withdraw(None);
+    assert_eq!(
+        balance,
+        StorageBalance {
+            total: 100.into(),
+            available: 0.into(),
+        },
+    );
+
+    assert!(contract.storage_unregister(None));
+    assert_eq!(contract.storage_balance_of(alice), None);
+    assert!(!contract.storage_unregister(None));
+}
+
+#[test]
+#[should_panic = "has 200 yoctoNEAR of storage balance available, but 201 yoctoNEAR was requested"]
+fn nep145_withdraw_too_much() {
+    let mut contract = Contract::new();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    context(&alice, 300);
+    contract.storage_deposit(None, None);
+
+    context(&alice, 1);
+    contract.storage_withdraw(Some(201.into()));
+}
+
+#[test]
+#[should_panic = "is not registered"]
+fn nep145_withdraw_unregistered() {
+    let mut contract = Contract::new();
+    let alice: AccountId = "alice".parse().unwrap();
+
+    context(&alice, 1);
+    contract.storage_withdraw(None);
+}
+
+#[test]
+fn nep145_custom_storage_key() {
+    #[derive(Nep145, BorshDeserialize, BorshSerialize)]
+    #[nep145(storage_key = "b\"storage\".to_vec()")]
+    #[near_bindgen]
+    struct CustomKey {}
+
+    let mut contract = CustomKey {};
+    let alice: AccountId = "alice".parse().unwrap();
+
+    context(&alice, CustomKey::get_storage_balance_bounds().min.0);
+    contract.storage_deposit(None, None);
+
+    assert!(CustomKey::root().key.starts_with(b"storage"));
+    assert!(env::storage_has_key(
+        &CustomKey::slot_storage_balance(&alice).key
+    ));
+}
+
+#[derive(Nep145, BorshDeserialize, BorshSerialize)]
+#[nep145(storage_key = "b\"default\".to_vec()")]
+#[near_bindgen]
+struct DefaultBounds {}
+
+#[test]
+fn nep145_default_bounds() {
+    let mut contract = DefaultBounds {};
+    let account_id = longest_account_id();
+
+    let bounds = contract.storage_balance_bounds();
+    assert_eq!(bounds.max, None);
+    assert_eq!(
+        bounds.min.0,
+        u128::from(DefaultBounds::storage_usage_per_account()) * env::storage_byte_cost(),
+    );
+
+    context(&account_id, bounds.min.0);
+    let initial_storage_usage = env::storage_usage();
+    contract.storage_deposit(None, None);
+
+    assert_eq!(
+        env::storage_usage() - initial_storage_usage,
+        DefaultBounds::storage_usage_per_account(),
+        "Minimum covers the registration record",
+    );
+}
+
+#[test]
+#[should_panic = "does not meet the minimum storage balance"]
+fn nep145_default_bounds_registration_is_not_free() {
+    let mut contract = DefaultBounds {};
+    let alice: AccountId = "alice".parse().unwrap();
+
+    context(&alice, 1);
+    contract.storage_deposit(None, None);
+}
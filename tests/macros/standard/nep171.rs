@@ -0,0 +1,380 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::Vector,
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, Gas, PromiseResult, RuntimeFeesConfig, VMConfig,
+};
+use near_sdk_contract_tools::{
+    standard::nep171::{
+        error::{InsufficientGasError, SenderNotApprovedError, TokenDoesNotExistError},
+        *,
+    },
+    Nep171,
+};
+
+#[derive(Nep171, BorshDeserialize, BorshSerialize)]
+#[near_bindgen]
+struct NonFungibleToken {
+    pub transfers: Vector<Nep171Transfer>,
+    pub hooks: Vector<String>,
+}
+
+impl Nep171Hook for NonFungibleToken {
+    fn before_nft_transfer(&mut self, transfer: &Nep171Transfer) {
+        self.transfers.push(transfer);
+        self.hooks.push(&"before_nft_transfer".to_string());
+    }
+
+    fn after_nft_transfer(&mut self, _transfer: &Nep171Transfer, _state: ()) {
+        self.hooks.push(&"after_nft_transfer".to_string());
+    }
+}
+
+/// Allows `market` to transfer any token
+struct MarketExtension;
+
+impl<C> Nep171Extension<C> for MarketExtension {
+    fn authorize_transfer(
+        _contract: &C,
+        transfer: &Nep171Transfer,
+        _owner_id: &AccountId,
+    ) -> Result<(), Nep171TransferError> {
+        if transfer.sender_id.as_str() == "market" {
+            Ok(())
+        } else {
+            Err(SenderNotApprovedError {
+                token_id: transfer.token_id.clone(),
+                sender_id: transfer.sender_id.clone(),
+            }
+            .into())
+        }
+    }
+
+    fn load_token_metadata(
+        _contract: &C,
+        _token_id: &TokenId,
+        metadata: &mut std::collections::HashMap<String, serde_json::Value>,
+    ) {
+        metadata.insert("market".to_string(), "market".into());
+    }
+}
+
+#[derive(Nep171, BorshDeserialize, BorshSerialize)]
+#[nep171(no_hooks, extension = "MarketExtension")]
+#[near_bindgen]
+struct MarketNonFungibleToken {}
+
+fn new_contract() -> NonFungibleToken {
+    NonFungibleToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    }
+}
+
+fn predecessor(account_id: &AccountId) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .attached_deposit(1)
+        .build());
+}
+
+#[test]
+fn nep171_mint() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice","token_ids":["token1"]}]}"#
+        ],
+    );
+
+    assert_eq!(
+        nft.nft_token("token1".to_string()),
+        Some(Token {
+            token_id: "token1".to_string(),
+            owner_id: alice.clone(),
+            extensions_metadata: Default::default(),
+        }),
+    );
+    assert_eq!(nft.nft_token("token2".to_string()), None);
+
+    assert!(nft
+        .mint(
+            vec!["token2".to_string(), "token1".to_string()],
+            alice,
+            None
+        )
+        .is_err());
+    assert_eq!(nft.nft_token("token2".to_string()), None);
+}
+
+#[test]
+fn nep171_transfer() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    predecessor(&alice);
+
+    nft.nft_transfer(bob.clone(), "token1".to_string(), None, None);
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","token_ids":["token1"]}]}"#
+        ],
+    );
+
+    assert_eq!(
+        nft.transfers.pop(),
+        Some(Nep171Transfer {
+            token_id: "token1".to_string(),
+            sender_id: alice.clone(),
+            receiver_id: bob.clone(),
+            approval_id: None,
+            memo: None,
+            msg: None,
+        }),
+    );
+
+    let expected_hook_execution_order = vec!["before_nft_transfer", "after_nft_transfer"];
+    let actual_hook_execution_order = nft.hooks.to_vec();
+    assert_eq!(expected_hook_execution_order, actual_hook_execution_order);
+
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(bob)
+    );
+}
+
+#[test]
+#[should_panic = "Sender `bob` does not have permission to transfer token `token1`"]
+fn nep171_transfer_unauthorized() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice, None).unwrap();
+
+    predecessor(&bob);
+
+    nft.nft_transfer(bob, "token1".to_string(), None, None);
+}
+
+#[test]
+#[should_panic = "Receiver `alice` already owns token `token1`"]
+fn nep171_transfer_to_self() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    predecessor(&alice);
+
+    nft.nft_transfer(alice, "token1".to_string(), None, None);
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of exactly 1 yoctoNEAR"]
+fn nep171_transfer_no_deposit() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice)
+        .build());
+
+    nft.nft_transfer(bob, "token1".to_string(), None, None);
+}
+
+#[test]
+fn nep171_transfer_call_insufficient_gas() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    let transfer = Nep171Transfer {
+        token_id: "token1".to_string(),
+        sender_id: alice.clone(),
+        receiver_id: bob,
+        approval_id: None,
+        memo: None,
+        msg: Some(String::new()),
+    };
+    let gas_allowance = Gas(GAS_FOR_NFT_TRANSFER_CALL.0 - 1);
+
+    assert_eq!(
+        nft.transfer_call(&transfer, gas_allowance).err(),
+        Some(Nep171TransferCallError::InsufficientGas(
+            InsufficientGasError {
+                required: GAS_FOR_NFT_TRANSFER_CALL,
+                gas_allowance,
+            }
+        )),
+    );
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(alice),
+        "Token is not transferred",
+    );
+}
+
+#[test]
+fn nep171_burn() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(
+        vec!["token1".to_string(), "token2".to_string()],
+        alice.clone(),
+        None,
+    )
+    .unwrap();
+
+    assert!(nft.burn(vec!["token1".to_string()], bob, None).is_err());
+    assert_eq!(
+        nft.burn(vec!["token3".to_string()], alice.clone(), None),
+        Err(TokenDoesNotExistError {
+            token_id: "token3".to_string(),
+        }
+        .into()),
+    );
+
+    nft.burn(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    assert_eq!(NonFungibleToken::token_owner(&"token1".to_string()), None);
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token2".to_string()),
+        Some(alice),
+    );
+}
+
+#[test]
+fn nep171_resolve_transfer() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    predecessor(&alice);
+    nft.transfer(&Nep171Transfer {
+        token_id: "token1".to_string(),
+        sender_id: alice.clone(),
+        receiver_id: bob.clone(),
+        approval_id: None,
+        memo: None,
+        msg: Some("msg".to_string()),
+    })
+    .unwrap();
+
+    // Receiver asks for the token to be returned
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"true".to_vec())],
+    );
+
+    // Controller transfers run the hooks
+    assert_eq!(
+        nft.hooks.to_vec(),
+        vec!["before_nft_transfer", "after_nft_transfer"],
+    );
+
+    assert!(!nft.resolve_transfer(alice.clone(), bob.clone(), "token1".to_string(), None));
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(alice.clone()),
+    );
+
+    // Returning the token does not run the hooks
+    assert_eq!(nft.hooks.len(), 2);
+
+    nft.transfer(&Nep171Transfer {
+        token_id: "token1".to_string(),
+        sender_id: alice.clone(),
+        receiver_id: bob.clone(),
+        approval_id: None,
+        memo: None,
+        msg: Some("msg".to_string()),
+    })
+    .unwrap();
+
+    // Receiver keeps the token
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"false".to_vec())],
+    );
+
+    assert!(nft.resolve_transfer(alice, bob.clone(), "token1".to_string(), None));
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(bob),
+    );
+}
+
+#[test]
+fn nep171_extension() {
+    let mut nft = MarketNonFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let market: AccountId = "market".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    let token = nft.nft_token("token1".to_string()).unwrap();
+    assert_eq!(
+        token.extensions_metadata.get("market"),
+        Some(&"market".into()),
+    );
+
+    predecessor(&market);
+
+    nft.nft_transfer(bob.clone(), "token1".to_string(), None, None);
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"market","old_owner_id":"alice","new_owner_id":"bob","token_ids":["token1"]}]}"#
+        ],
+    );
+    assert_eq!(
+        MarketNonFungibleToken::token_owner(&"token1".to_string()),
+        Some(bob),
+    );
+}
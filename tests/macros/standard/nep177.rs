@@ -0,0 +1,160 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen, AccountId,
+};
+use near_sdk_contract_tools::{
+    standard::{
+        nep171::{error::TokenDoesNotExistError, Nep171, Nep171Controller},
+        nep177::*,
+    },
+    Nep171, Nep177,
+};
+
+#[derive(Nep171, Nep177, BorshDeserialize, BorshSerialize)]
+#[nep171(no_hooks, extension = "Nep177Extension")]
+#[near_bindgen]
+struct NonFungibleToken {}
+
+fn new_contract() -> NonFungibleToken {
+    let mut contract = NonFungibleToken {};
+
+    contract
+        .set_contract_metadata(ContractMetadata::new(
+            "Non-Fungible Token".to_string(),
+            "NFT".to_string(),
+            None,
+        ))
+        .unwrap();
+
+    contract
+}
+
+fn token_metadata(title: &str) -> TokenMetadata {
+    TokenMetadata {
+        title: Some(title.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn nep177_contract_metadata() {
+    let mut nft = new_contract();
+
+    assert_eq!(
+        nft.nft_metadata(),
+        ContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Non-Fungible Token".to_string(),
+            symbol: "NFT".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        },
+    );
+
+    let invalid = ContractMetadata {
+        spec: "ft-1.0.0".to_string(),
+        ..nft.nft_metadata()
+    };
+
+    assert_eq!(
+        nft.set_contract_metadata(invalid),
+        Err(MetadataValidationError::UnsupportedSpec {
+            spec: "ft-1.0.0".to_string(),
+        }),
+    );
+    assert_eq!(nft.nft_metadata().spec, NFT_METADATA_SPEC);
+}
+
+#[test]
+#[should_panic = "Contract metadata not initialized"]
+fn nep177_contract_metadata_uninitialized() {
+    let nft = NonFungibleToken {};
+
+    nft.nft_metadata();
+}
+
+#[test]
+fn nep177_mint_with_metadata() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    nft.mint_with_metadata("token1".to_string(), alice.clone(), token_metadata("One"))
+        .unwrap();
+
+    let token = nft.nft_token("token1".to_string()).unwrap();
+    assert_eq!(token.owner_id, alice);
+    assert_eq!(
+        token.extensions_metadata.get("metadata"),
+        Some(&serde_json::to_value(token_metadata("One")).unwrap()),
+    );
+
+    assert_eq!(
+        nft.mint_with_metadata(
+            "token2".to_string(),
+            alice.clone(),
+            TokenMetadata {
+                media: Some("https://example.com/image.png".to_string()),
+                ..Default::default()
+            },
+        ),
+        Err(MetadataValidationError::HashMismatch { field: "media" }.into()),
+    );
+    assert_eq!(NonFungibleToken::token_owner(&"token2".to_string()), None);
+    assert_eq!(
+        NonFungibleToken::token_metadata(&"token2".to_string()),
+        None
+    );
+
+    assert!(nft
+        .mint_with_metadata("token1".to_string(), alice, token_metadata("Two"))
+        .is_err());
+    assert_eq!(
+        NonFungibleToken::token_metadata(&"token1".to_string()),
+        Some(token_metadata("One")),
+    );
+}
+
+#[test]
+fn nep177_set_token_metadata() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    assert_eq!(
+        nft.set_token_metadata(&"token1".to_string(), &token_metadata("One")),
+        Err(TokenDoesNotExistError {
+            token_id: "token1".to_string(),
+        }
+        .into()),
+    );
+
+    nft.mint_with_metadata("token1".to_string(), alice, token_metadata("One"))
+        .unwrap();
+    nft.set_token_metadata(&"token1".to_string(), &token_metadata("Uno"))
+        .unwrap();
+
+    assert_eq!(
+        NonFungibleToken::token_metadata(&"token1".to_string()),
+        Some(token_metadata("Uno")),
+    );
+}
+
+#[test]
+fn nep177_burn() {
+    let mut nft = new_contract();
+
+    let alice: AccountId = "alice".parse().unwrap();
+
+    nft.mint_with_metadata("token1".to_string(), alice.clone(), token_metadata("One"))
+        .unwrap();
+    nft.burn(vec!["token1".to_string()], alice, None).unwrap();
+
+    assert_eq!(nft.nft_token("token1".to_string()), None);
+    assert_eq!(
+        NonFungibleToken::token_metadata(&"token1".to_string()),
+        None
+    );
+}
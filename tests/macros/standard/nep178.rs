@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    standard::{
+        nep171::{Nep171, Nep171Controller, Nep171Resolver, Nep171Transfer},
+        nep178::{error::UnauthorizedError, *},
+    },
+    Nep171, Nep178,
+};
+
+#[derive(Nep171, Nep178, BorshDeserialize, BorshSerialize)]
+#[nep171(no_hooks, extension = "Nep178Extension")]
+#[near_bindgen]
+struct NonFungibleToken {}
+
+fn predecessor(account_id: &AccountId, attached_deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .attached_deposit(attached_deposit)
+        .build());
+}
+
+fn setup() -> (NonFungibleToken, AccountId, AccountId, AccountId) {
+    let mut nft = NonFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let market: AccountId = "market".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    (nft, alice, bob, market)
+}
+
+#[test]
+fn nep178_approve() {
+    let (mut nft, alice, bob, market) = setup();
+
+    predecessor(&alice, ONE_NEAR);
+
+    assert!(nft
+        .nft_approve("token1".to_string(), market.clone(), None)
+        .is_none());
+
+    assert!(nft.nft_is_approved("token1".to_string(), market.clone(), None));
+    assert!(nft.nft_is_approved("token1".to_string(), market.clone(), Some(0)));
+    assert!(!nft.nft_is_approved("token1".to_string(), market.clone(), Some(1)));
+    assert!(!nft.nft_is_approved("token1".to_string(), bob.clone(), None));
+
+    // Re-approving issues a new approval ID
+    nft.nft_approve("token1".to_string(), market.clone(), None);
+    nft.nft_approve("token1".to_string(), bob.clone(), None);
+
+    assert_eq!(
+        NonFungibleToken::approvals_for(&"token1".to_string()),
+        HashMap::from([(market, 1), (bob, 2)]),
+    );
+
+    let token = nft.nft_token("token1".to_string()).unwrap();
+    assert_eq!(
+        token.extensions_metadata.get("approved_account_ids"),
+        Some(&serde_json::json!({ "market": 1, "bob": 2 })),
+    );
+}
+
+#[test]
+#[should_panic = "Account `bob` is not allowed to manage approvals for token `token1`"]
+fn nep178_approve_unauthorized() {
+    let (mut nft, _alice, bob, market) = setup();
+
+    predecessor(&bob, ONE_NEAR);
+
+    nft.nft_approve("token1".to_string(), market, None);
+}
+
+#[test]
+#[should_panic = "Requires attached deposit of at least 1 yoctoNEAR"]
+fn nep178_approve_no_deposit() {
+    let (mut nft, alice, _bob, market) = setup();
+
+    predecessor(&alice, 0);
+
+    nft.nft_approve("token1".to_string(), market, None);
+}
+
+#[test]
+#[should_panic = "Insufficient deposit"]
+fn nep178_approve_insufficient_deposit() {
+    let (mut nft, alice, _bob, market) = setup();
+
+    predecessor(&alice, 1);
+
+    nft.nft_approve("token1".to_string(), market, None);
+}
+
+#[test]
+fn nep178_approve_limit() {
+    let (mut nft, alice, _bob, market) = setup();
+
+    for i in 0..MAX_APPROVALS {
+        nft.approve(
+            &"token1".to_string(),
+            &alice,
+            &format!("account{i}").parse().unwrap(),
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        nft.approve(&"token1".to_string(), &alice, &market),
+        Err(error::TooManyApprovalsError {
+            token_id: "token1".to_string(),
+        }
+        .into()),
+    );
+
+    // Re-approving does not increase the number of approvals
+    nft.approve(&"token1".to_string(), &alice, &"account0".parse().unwrap())
+        .unwrap();
+}
+
+#[test]
+fn nep178_transfer_approved() {
+    let (mut nft, alice, bob, market) = setup();
+
+    predecessor(&alice, ONE_NEAR);
+    nft.nft_approve("token1".to_string(), market.clone(), None);
+
+    predecessor(&market, 1);
+    nft.nft_transfer(bob.clone(), "token1".to_string(), Some(0), None);
+
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(bob),
+    );
+    assert!(NonFungibleToken::approvals_for(&"token1".to_string()).is_empty());
+}
+
+#[test]
+#[should_panic = "Sender `market` does not have permission to transfer token `token1`"]
+fn nep178_transfer_wrong_approval_id() {
+    let (mut nft, alice, bob, market) = setup();
+
+    predecessor(&alice, ONE_NEAR);
+    nft.nft_approve("token1".to_string(), market.clone(), None);
+    nft.nft_approve("token1".to_string(), market.clone(), None);
+
+    predecessor(&market, 1);
+    nft.nft_transfer(bob, "token1".to_string(), Some(0), None);
+}
+
+#[test]
+fn nep178_revoke() {
+    let (mut nft, alice, bob, market) = setup();
+
+    predecessor(&alice, ONE_NEAR);
+    nft.nft_approve("token1".to_string(), market.clone(), None);
+    nft.nft_approve("token1".to_string(), bob.clone(), None);
+
+    predecessor(&alice, 1);
+    nft.nft_revoke("token1".to_string(), market.clone());
+
+    assert!(!nft.nft_is_approved("token1".to_string(), market.clone(), None));
+    assert!(nft.nft_is_approved("token1".to_string(), bob.clone(), None));
+
+    assert_eq!(
+        nft.revoke(&"token1".to_string(), &alice, &market),
+        Err(error::AccountNotApprovedError {
+            token_id: "token1".to_string(),
+            account_id: market,
+        }
+        .into()),
+    );
+    assert_eq!(
+        nft.revoke(&"token1".to_string(), &bob, &bob),
+        Err(UnauthorizedError {
+            token_id: "token1".to_string(),
+            account_id: bob.clone(),
+        }
+        .into()),
+    );
+
+    nft.nft_revoke_all("token1".to_string());
+
+    assert!(!nft.nft_is_approved("token1".to_string(), bob, None));
+}
+
+#[test]
+fn nep178_burn() {
+    let (mut nft, alice, _bob, market) = setup();
+
+    nft.approve(&"token1".to_string(), &alice, &market).unwrap();
+    nft.burn(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+
+    assert!(NonFungibleToken::approvals_for(&"token1".to_string()).is_empty());
+    assert_eq!(nft.approve(&"token1".to_string(), &alice, &market), Ok(0),);
+}
+
+#[test]
+fn nep178_resolve_transfer_restores_approvals() {
+    let (mut nft, alice, bob, market) = setup();
+
+    nft.approve(&"token1".to_string(), &alice, &market).unwrap();
+    let approved_account_ids = NonFungibleToken::approvals_for(&"token1".to_string());
+
+    nft.transfer(&Nep171Transfer {
+        token_id: "token1".to_string(),
+        sender_id: market.clone(),
+        receiver_id: bob.clone(),
+        approval_id: Some(0),
+        memo: None,
+        msg: Some("msg".to_string()),
+    })
+    .unwrap();
+
+    assert!(NonFungibleToken::approvals_for(&"token1".to_string()).is_empty());
+
+    // Receiver asks for the token to be returned
+    testing_env!(
+        VMContextBuilder::new()
+            .predecessor_account_id("contract".parse().unwrap())
+            .current_account_id("contract".parse().unwrap())
+            .build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"true".to_vec())],
+    );
+
+    assert!(!nft.nft_resolve_transfer(
+        alice.clone(),
+        bob,
+        "token1".to_string(),
+        Some(approved_account_ids.clone()),
+    ));
+
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(alice),
+    );
+    assert_eq!(
+        NonFungibleToken::approvals_for(&"token1".to_string()),
+        approved_account_ids,
+    );
+}
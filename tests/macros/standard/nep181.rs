@@ -0,0 +1,121 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId,
+};
+use near_sdk_contract_tools::{
+    standard::{
+        nep171::{Nep171, Nep171Controller},
+        nep178::Nep178Extension,
+        nep181::*,
+    },
+    Nep171, Nep178, Nep181,
+};
+
+#[derive(Nep171, Nep178, Nep181, BorshDeserialize, BorshSerialize)]
+#[nep171(no_hooks, extension = "(Nep178Extension, Nep181Extension)")]
+#[near_bindgen]
+struct NonFungibleToken {}
+
+fn token_ids(tokens: Vec<near_sdk_contract_tools::standard::nep171::Token>) -> Vec<String> {
+    tokens.into_iter().map(|t| t.token_id).collect()
+}
+
+#[test]
+fn nep181_mint_and_paginate() {
+    let mut nft = NonFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(
+        (0..5).map(|i| format!("token{i}")).collect(),
+        alice.clone(),
+        None,
+    )
+    .unwrap();
+    nft.mint(vec!["token5".to_string()], bob.clone(), None)
+        .unwrap();
+
+    assert_eq!(nft.nft_total_supply(), U128(6));
+    assert_eq!(nft.nft_supply_for_owner(alice.clone()), U128(5));
+    assert_eq!(nft.nft_supply_for_owner(bob.clone()), U128(1));
+    assert_eq!(nft.nft_supply_for_owner("carol".parse().unwrap()), U128(0));
+
+    assert_eq!(
+        token_ids(nft.nft_tokens(None, None)),
+        (0..6).map(|i| format!("token{i}")).collect::<Vec<_>>(),
+    );
+    assert_eq!(
+        token_ids(nft.nft_tokens(Some(U128(2)), Some(3))),
+        vec!["token2", "token3", "token4"],
+    );
+    assert_eq!(
+        token_ids(nft.nft_tokens(Some(U128(4)), Some(10))),
+        vec!["token4", "token5"],
+    );
+    assert!(nft.nft_tokens(Some(U128(6)), Some(10)).is_empty());
+    assert!(nft.nft_tokens(Some(U128(u128::MAX)), None).is_empty());
+
+    assert_eq!(
+        token_ids(nft.nft_tokens_for_owner(alice.clone(), Some(U128(3)), None)),
+        vec!["token3", "token4"],
+    );
+    assert_eq!(
+        token_ids(nft.nft_tokens_for_owner(bob, None, Some(1))),
+        vec!["token5"],
+    );
+    assert!(nft
+        .nft_tokens_for_owner("carol".parse().unwrap(), None, None)
+        .is_empty());
+
+    // Tokens include metadata from other extensions
+    assert!(nft.nft_tokens(None, Some(1))[0]
+        .extensions_metadata
+        .contains_key("approved_account_ids"));
+}
+
+#[test]
+fn nep181_transfer_and_burn() {
+    let mut nft = NonFungibleToken {};
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    nft.mint(
+        vec![
+            "token0".to_string(),
+            "token1".to_string(),
+            "token2".to_string(),
+        ],
+        alice.clone(),
+        None,
+    )
+    .unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    nft.nft_transfer(bob.clone(), "token0".to_string(), None, None);
+
+    assert_eq!(nft.nft_total_supply(), U128(3));
+    assert_eq!(
+        NonFungibleToken::tokens_for_owner(&alice, 0, None),
+        vec!["token2", "token1"],
+    );
+    assert_eq!(
+        NonFungibleToken::tokens_for_owner(&bob, 0, None),
+        vec!["token0"],
+    );
+
+    nft.burn(vec!["token0".to_string()], bob.clone(), None)
+        .unwrap();
+
+    assert_eq!(nft.nft_total_supply(), U128(2));
+    assert_eq!(NonFungibleToken::supply_for_owner(&bob), 0);
+    assert_eq!(NonFungibleToken::tokens(0, None), vec!["token2", "token1"]);
+}
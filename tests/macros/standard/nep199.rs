@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    standard::{
+        nep171::{Nep171Controller, Nep171Hook, Nep171Transfer},
+        nep178::{Nep178, Nep178Extension},
+        nep199::*,
+    },
+    Nep171, Nep178, Nep199,
+};
+
+#[derive(Nep171, Nep178, Nep199, BorshDeserialize, BorshSerialize)]
+#[nep171(extension = "(Nep178Extension, Nep199Extension)")]
+#[near_bindgen]
+struct NonFungibleToken {
+    pub transfers: u32,
+}
+
+impl Nep171Hook for NonFungibleToken {
+    fn after_nft_transfer(&mut self, _transfer: &Nep171Transfer, _state: ()) {
+        self.transfers += 1;
+    }
+}
+
+fn setup() -> (NonFungibleToken, AccountId, AccountId, AccountId) {
+    let mut nft = NonFungibleToken { transfers: 0 };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let artist: AccountId = "artist".parse().unwrap();
+    let market: AccountId = "market".parse().unwrap();
+
+    nft.mint(vec!["token1".to_string()], alice.clone(), None)
+        .unwrap();
+    nft.set_token_royalties(
+        &"token1".to_string(),
+        &Royalties {
+            accounts: HashMap::from([(artist.clone(), 1000)]),
+        },
+    )
+    .unwrap();
+
+    (nft, alice, artist, market)
+}
+
+#[test]
+fn nep199_payout() {
+    let (nft, alice, artist, _market) = setup();
+
+    assert_eq!(
+        nft.nft_payout("token1".to_string(), U128(1_000_005), None),
+        Payout {
+            payout: HashMap::from([(artist, U128(100_000)), (alice, U128(900_005))]),
+        },
+    );
+}
+
+#[test]
+#[should_panic = "Payout includes 2 accounts, exceeding `max_len_payout` of 1"]
+fn nep199_payout_too_long() {
+    let (nft, _alice, _artist, _market) = setup();
+
+    nft.nft_payout("token1".to_string(), U128(1000), Some(1));
+}
+
+#[test]
+#[should_panic = "Token `token2` does not exist"]
+fn nep199_payout_nonexistent() {
+    let (nft, _alice, _artist, _market) = setup();
+
+    nft.nft_payout("token2".to_string(), U128(1000), None);
+}
+
+#[test]
+fn nep199_set_royalties() {
+    let (mut nft, _alice, artist, _market) = setup();
+
+    assert_eq!(
+        nft.set_token_royalties(
+            &"token1".to_string(),
+            &Royalties {
+                accounts: HashMap::from([(artist.clone(), 10_001)]),
+            },
+        ),
+        Err(RoyaltiesExceedTotalError {
+            total_basis_points: 10_001,
+        }
+        .into()),
+    );
+    assert!(nft
+        .set_token_royalties(&"token2".to_string(), &Royalties::default())
+        .is_err());
+
+    // The owner is part of every payout
+    let accounts = |len: u32| {
+        (0..len)
+            .map(|i| (format!("artist{i}").parse().unwrap(), 1))
+            .collect::<HashMap<AccountId, BasisPoints>>()
+    };
+    assert_eq!(
+        nft.set_token_royalties(
+            &"token1".to_string(),
+            &Royalties {
+                accounts: accounts(MAX_LEN_PAYOUT),
+            },
+        ),
+        Err(RoyaltiesTooLongError {
+            max_len: MAX_LEN_PAYOUT - 1,
+            len: MAX_LEN_PAYOUT as usize,
+        }
+        .into()),
+    );
+
+    assert_eq!(
+        NonFungibleToken::token_royalties(&"token1".to_string()),
+        Some(Royalties {
+            accounts: HashMap::from([(artist, 1000)]),
+        }),
+    );
+
+    let royalties = Royalties {
+        accounts: accounts(MAX_LEN_PAYOUT - 1),
+    };
+    nft.set_token_royalties(&"token1".to_string(), &royalties)
+        .unwrap();
+
+    assert_eq!(
+        NonFungibleToken::token_royalties(&"token1".to_string()),
+        Some(royalties),
+    );
+    assert_eq!(
+        nft.nft_payout("token1".to_string(), U128(1_000_000), Some(MAX_LEN_PAYOUT))
+            .payout
+            .len(),
+        MAX_LEN_PAYOUT as usize,
+    );
+}
+
+#[test]
+fn nep199_transfer_payout() {
+    let (mut nft, alice, artist, market) = setup();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(ONE_NEAR)
+        .build());
+    nft.nft_approve("token1".to_string(), market.clone(), None);
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(market.clone())
+        .attached_deposit(1)
+        .build());
+
+    let bob: AccountId = "bob".parse().unwrap();
+    let payout = nft.nft_transfer_payout(
+        bob.clone(),
+        "token1".to_string(),
+        Some(0),
+        None,
+        U128(1000),
+        Some(2),
+    );
+
+    assert_eq!(
+        payout,
+        Payout {
+            payout: HashMap::from([(artist, U128(100)), (alice, U128(900))]),
+        },
+    );
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"market","old_owner_id":"alice","new_owner_id":"bob","token_ids":["token1"]}]}"#
+        ],
+    );
+    assert_eq!(
+        NonFungibleToken::token_owner(&"token1".to_string()),
+        Some(bob.clone()),
+    );
+    assert_eq!(nft.transfers, 1);
+
+    nft.burn(vec!["token1".to_string()], bob, None).unwrap();
+    assert_eq!(
+        NonFungibleToken::token_royalties(&"token1".to_string()),
+        None
+    );
+}
@@ -0,0 +1,365 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::Vector,
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, Gas, PromiseResult, RuntimeFeesConfig, VMConfig,
+};
+use near_sdk_contract_tools::{
+    standard::nep245::{error::*, *},
+    Nep245,
+};
+
+#[derive(Nep245, BorshDeserialize, BorshSerialize)]
+#[near_bindgen]
+struct MultiToken {
+    pub transfers: Vector<Nep245Transfer>,
+    pub hooks: Vector<String>,
+}
+
+impl Nep245Hook for MultiToken {
+    fn before_transfer(&mut self, transfer: &Nep245Transfer) {
+        self.transfers.push(transfer);
+        self.hooks.push(&"before_transfer".to_string());
+    }
+
+    fn after_transfer(&mut self, _transfer: &Nep245Transfer, _state: ()) {
+        self.hooks.push(&"after_transfer".to_string());
+    }
+}
+
+fn setup() -> (MultiToken, AccountId, AccountId) {
+    let mut mt = MultiToken {
+        transfers: Vector::new(b"t"),
+        hooks: Vector::new(b"h"),
+    };
+
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    mt.mint(
+        alice.clone(),
+        vec!["gold".to_string(), "sword".to_string()],
+        vec![100, 1],
+        None,
+    );
+
+    (mt, alice, bob)
+}
+
+#[test]
+fn nep245_mint() {
+    let (mt, alice, bob) = setup();
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[{"owner_id":"alice","token_ids":["gold","sword"],"amounts":["100","1"]}]}"#,
+        ],
+    );
+
+    assert_eq!(
+        mt.mt_batch_balance_of(
+            alice,
+            vec![
+                "gold".to_string(),
+                "sword".to_string(),
+                "shield".to_string()
+            ],
+        ),
+        vec![100.into(), 1.into(), 0.into()],
+    );
+    assert_eq!(mt.mt_balance_of(bob, "gold".to_string()).0, 0);
+    assert_eq!(
+        mt.mt_batch_supply(vec!["gold".to_string(), "shield".to_string()]),
+        vec![Some(100.into()), None],
+    );
+}
+
+#[test]
+fn nep245_transfer() {
+    let (mut mt, alice, bob) = setup();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    mt.mt_transfer(bob.clone(), "gold".to_string(), 40.into(), None, None);
+
+    assert_eq!(
+        mt.transfers.pop(),
+        Some(Nep245Transfer {
+            sender_id: alice.clone(),
+            receiver_id: bob.clone(),
+            token_ids: vec!["gold".to_string()],
+            amounts: vec![40],
+            memo: None,
+            msg: None,
+        }),
+    );
+    assert_eq!(mt.hooks.to_vec(), vec!["before_transfer", "after_transfer"]);
+
+    assert_eq!(mt.mt_balance_of(alice, "gold".to_string()).0, 60);
+    assert_eq!(mt.mt_balance_of(bob, "gold".to_string()).0, 40);
+    assert_eq!(mt.mt_supply("gold".to_string()), Some(100.into()));
+}
+
+#[test]
+fn nep245_batch_transfer() {
+    let (mut mt, alice, bob) = setup();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .build());
+
+    mt.mt_batch_transfer(
+        bob.clone(),
+        vec!["gold".to_string(), "sword".to_string()],
+        vec![25.into(), 1.into()],
+        None,
+        Some("loot".to_string()),
+    );
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","token_ids":["gold","sword"],"amounts":["25","1"],"memo":"loot"}]}"#,
+        ],
+    );
+
+    assert_eq!(
+        mt.mt_batch_balance_of(alice, vec!["gold".to_string(), "sword".to_string()]),
+        vec![75.into(), 0.into()],
+    );
+    assert_eq!(
+        mt.mt_batch_balance_of(bob, vec!["gold".to_string(), "sword".to_string()]),
+        vec![25.into(), 1.into()],
+    );
+}
+
+#[test]
+#[should_panic = "Sender balance underflow"]
+fn nep245_transfer_insufficient_balance() {
+    let (mut mt, alice, bob) = setup();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice)
+        .attached_deposit(1)
+        .build());
+
+    mt.mt_transfer(bob, "sword".to_string(), 2.into(), None, None);
+}
+
+#[test]
+#[should_panic = "Token IDs and amounts must have the same length"]
+fn nep245_batch_transfer_length_mismatch() {
+    let (mut mt, alice, bob) = setup();
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice)
+        .attached_deposit(1)
+        .build());
+
+    mt.mt_batch_transfer(
+        bob,
+        vec!["gold".to_string(), "sword".to_string()],
+        vec![1.into()],
+        None,
+        None,
+    );
+}
+
+#[test]
+fn nep245_transfer_call_insufficient_gas() {
+    let (mut mt, alice, bob) = setup();
+
+    let gas_allowance = Gas(GAS_FOR_MT_TRANSFER_CALL.0 - 1);
+
+    assert_eq!(
+        mt.transfer_call(
+            alice.clone(),
+            bob.clone(),
+            vec!["gold".to_string()],
+            vec![10],
+            None,
+            String::new(),
+            gas_allowance,
+        )
+        .err(),
+        Some(
+            InsufficientGasError {
+                required: GAS_FOR_MT_TRANSFER_CALL,
+                gas_allowance,
+            }
+            .into()
+        ),
+    );
+    assert_eq!(mt.mt_balance_of(alice, "gold".to_string()).0, 100);
+    assert_eq!(mt.mt_balance_of(bob, "gold".to_string()).0, 0);
+}
+
+#[test]
+fn nep245_transfer_call_insufficient_balance() {
+    let (mut mt, alice, bob) = setup();
+
+    // The second transfer of the same token exceeds the remaining balance
+    assert_eq!(
+        mt.transfer_call(
+            alice.clone(),
+            bob.clone(),
+            vec!["gold".to_string(), "gold".to_string()],
+            vec![60, 60],
+            None,
+            String::new(),
+            GAS_FOR_MT_TRANSFER_CALL,
+        )
+        .err(),
+        Some(Nep245TransferCallError::Transfer(
+            BalanceUnderflowError {
+                account_id: alice.clone(),
+                token_id: "gold".to_string(),
+                balance: 40,
+                amount: 60,
+            }
+            .into()
+        )),
+    );
+    assert_eq!(mt.mt_balance_of(alice, "gold".to_string()).0, 100);
+    assert_eq!(mt.mt_balance_of(bob, "gold".to_string()).0, 0);
+}
+
+#[test]
+fn nep245_transfer_errors() {
+    let (mut mt, alice, bob) = setup();
+
+    assert_eq!(
+        mt.transfer(alice.clone(), bob.clone(), vec![], vec![], None),
+        Err(EmptyBatchError.into()),
+    );
+    assert_eq!(
+        mt.transfer(
+            alice.clone(),
+            bob.clone(),
+            vec!["gold".to_string()],
+            vec![1, 2],
+            None,
+        ),
+        Err(BatchLengthMismatchError {
+            token_ids: 1,
+            amounts: 2,
+        }
+        .into()),
+    );
+
+    // Only reachable with an inconsistent supply
+    MultiToken::slot_balance("gold", &bob).write(&(u128::MAX - 99));
+
+    assert_eq!(
+        mt.transfer(
+            alice.clone(),
+            bob.clone(),
+            vec!["sword".to_string(), "gold".to_string()],
+            vec![1, 100],
+            None,
+        ),
+        Err(BalanceOverflowError {
+            account_id: bob.clone(),
+            token_id: "gold".to_string(),
+            balance: u128::MAX - 99,
+            amount: 100,
+        }
+        .into()),
+    );
+    assert_eq!(
+        mt.mt_batch_balance_of(alice, vec!["gold".to_string(), "sword".to_string()]),
+        vec![100.into(), 1.into()],
+    );
+    assert_eq!(mt.mt_balance_of(bob, "sword".to_string()).0, 0);
+}
+
+#[test]
+fn nep245_burn() {
+    let (mut mt, alice, _) = setup();
+
+    mt.burn(alice.clone(), vec!["gold".to_string()], vec![30], None);
+
+    assert_eq!(mt.mt_balance_of(alice, "gold".to_string()).0, 70);
+    assert_eq!(mt.mt_supply("gold".to_string()), Some(70.into()));
+}
+
+#[test]
+fn nep245_resolve_transfer() {
+    let (mut mt, alice, bob) = setup();
+
+    let token_ids = vec!["gold".to_string(), "sword".to_string()];
+
+    mt.transfer(
+        alice.clone(),
+        bob.clone(),
+        token_ids.clone(),
+        vec![50, 1],
+        None,
+    )
+    .unwrap();
+
+    // Controller transfers run the hooks
+    assert_eq!(mt.hooks.to_vec(), vec!["before_transfer", "after_transfer"]);
+
+    // Receiver returns some gold, keeps the sword
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"[\"20\",\"0\"]".to_vec())],
+    );
+
+    let used = mt.resolve_transfer(
+        vec![alice.clone(), alice.clone()],
+        bob.clone(),
+        token_ids.clone(),
+        vec![50, 1],
+    );
+
+    assert_eq!(used, vec![30, 1]);
+    // Refunds do not run the hooks
+    assert_eq!(mt.hooks.len(), 2);
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["gold"],"amounts":["20"]}]}"#,
+        ],
+    );
+    assert_eq!(
+        mt.mt_batch_balance_of(alice.clone(), token_ids.clone()),
+        vec![70.into(), 0.into()],
+    );
+
+    // Receiver call fails: everything is returned
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed],
+    );
+
+    let used = mt.resolve_transfer(
+        vec![alice.clone(), alice.clone()],
+        bob.clone(),
+        token_ids.clone(),
+        vec![30, 1],
+    );
+
+    assert_eq!(used, vec![0, 0]);
+    assert_eq!(
+        mt.mt_batch_balance_of(alice, token_ids.clone()),
+        vec![100.into(), 1.into()],
+    );
+    assert_eq!(
+        mt.mt_batch_balance_of(bob, token_ids),
+        vec![0.into(), 0.into()],
+    );
+}
@@ -0,0 +1,55 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+};
+use near_sdk_contract_tools::{standard::nep330::*, Nep330, Upgrade};
+
+#[derive(Nep330, Upgrade, BorshDeserialize, BorshSerialize)]
+#[upgrade(hook = "empty", serializer = "borsh", nep330)]
+#[near_bindgen]
+struct DefaultMetadata {}
+
+#[derive(Nep330, BorshDeserialize, BorshSerialize)]
+#[nep330(version = "abc123", link = "https://example.com/source")]
+#[near_bindgen]
+struct CustomMetadata {}
+
+#[test]
+fn nep330_default() {
+    let contract = DefaultMetadata {};
+
+    assert_eq!(
+        contract.contract_source_metadata(),
+        ContractSourceMetadata {
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            link: Some(env!("CARGO_PKG_REPOSITORY").to_string()),
+        },
+    );
+}
+
+#[test]
+fn nep330_custom() {
+    let contract = CustomMetadata {};
+
+    assert_eq!(
+        contract.contract_source_metadata(),
+        ContractSourceMetadata {
+            version: Some("abc123".to_string()),
+            link: Some("https://example.com/source".to_string()),
+        },
+    );
+}
+
+#[test]
+fn nep330_record_upgrade() {
+    let mut contract = DefaultMetadata {};
+
+    assert_eq!(DefaultMetadata::previous_version(), None);
+
+    contract.upgrade(vec![]);
+
+    assert_eq!(
+        DefaultMetadata::previous_version(),
+        Some(Some(env!("CARGO_PKG_VERSION").to_string())),
+    );
+}
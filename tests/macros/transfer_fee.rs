@@ -0,0 +1,191 @@
+use near_sdk::{
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, Gas,
+};
+use near_sdk_contract_tools::{
+    standard::nep141::{Nep141, Nep141Controller, Nep141TransferBatch},
+    transfer_fee::{TransferFee, TransferFeeConfig, TransferFeeExternal},
+    FungibleToken, TransferFee,
+};
+
+#[derive(FungibleToken, TransferFee)]
+#[fungible_token(name = "Treasury Token", symbol = "TT", decimals = 18, transfer_batch)]
+#[transfer_fee(nep141_hook)]
+#[near_bindgen]
+struct Contract {}
+
+fn setup(basis_points: u16) -> (Contract, AccountId, AccountId, AccountId) {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let collector: AccountId = "collector".parse().unwrap();
+
+    contract.deposit_unchecked(&alice, 10_000);
+    contract.set_transfer_fee(basis_points, collector.clone());
+
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(1)
+        .prepaid_gas(Gas(300_000_000_000_000))
+        .build());
+
+    (contract, alice, bob, collector)
+}
+
+#[test]
+fn fee_for() {
+    let config = TransferFeeConfig {
+        basis_points: 250,
+        collector_id: "collector".parse().unwrap(),
+    };
+
+    assert_eq!(config.fee_for(0), 0);
+    assert_eq!(config.fee_for(39), 0);
+    assert_eq!(config.fee_for(40), 1);
+    assert_eq!(config.fee_for(1_000), 25);
+    assert_eq!(config.fee_for(u128::MAX), u128::MAX / 40);
+}
+
+#[test]
+fn transfer_charges_receiver() {
+    let (mut contract, alice, bob, collector) = setup(250);
+
+    assert_eq!(
+        contract.fee_config(),
+        Some(TransferFeeConfig {
+            basis_points: 250,
+            collector_id: collector.clone(),
+        }),
+    );
+
+    contract.ft_transfer(bob.clone(), 1_000.into(), None);
+
+    assert_eq!(contract.ft_balance_of(alice).0, 9_000);
+    assert_eq!(contract.ft_balance_of(bob).0, 975);
+    assert_eq!(contract.ft_balance_of(collector).0, 25);
+    assert_eq!(contract.ft_total_supply().0, 10_000);
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"1000"}]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"collector","amount":"25","memo":"transfer fee"}]}"#,
+        ],
+    );
+}
+
+#[test]
+fn transfer_call_charges_receiver() {
+    let (mut contract, alice, bob, collector) = setup(250);
+
+    let _ = contract.ft_transfer_call(bob.clone(), 1_000.into(), None, "".to_string());
+
+    assert_eq!(contract.ft_balance_of(alice).0, 9_000);
+    assert_eq!(contract.ft_balance_of(bob).0, 975);
+    assert_eq!(contract.ft_balance_of(collector).0, 25);
+}
+
+#[test]
+fn transfer_batch_charges_receivers_after_event() {
+    let (mut contract, alice, bob, collector) = setup(250);
+    let carol: AccountId = "carol".parse().unwrap();
+
+    contract.ft_transfer_batch(
+        vec![bob.clone(), carol.clone()],
+        vec![1_000.into(), 2_000.into()],
+        None,
+    );
+
+    assert_eq!(contract.ft_balance_of(alice).0, 7_000);
+    assert_eq!(contract.ft_balance_of(bob).0, 975);
+    assert_eq!(contract.ft_balance_of(carol).0, 1_950);
+    assert_eq!(contract.ft_balance_of(collector).0, 75);
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"1000"},{"old_owner_id":"alice","new_owner_id":"carol","amount":"2000"}]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"carol","new_owner_id":"collector","amount":"50","memo":"transfer fee"}]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"collector","amount":"25","memo":"transfer fee"}]}"#,
+        ],
+    );
+}
+
+mod frozen_collector {
+    use near_sdk_contract_tools::{
+        freeze::Freeze,
+        standard::nep141::{Nep141Hook, Nep141Transfer},
+        Freeze,
+    };
+
+    use super::*;
+
+    #[derive(Freeze, FungibleToken, TransferFee)]
+    #[fungible_token(name = "Treasury Token", symbol = "TT", decimals = 18)]
+    #[near_bindgen]
+    struct FreezableContract {}
+
+    impl Nep141Hook for FreezableContract {
+        fn before_transfer(&mut self, transfer: &Nep141Transfer) {
+            <Self as Freeze>::before_nep141_transfer(transfer);
+            <Self as TransferFee>::before_nep141_transfer(transfer);
+        }
+
+        fn after_transfer(&mut self, transfer: &Nep141Transfer, _state: ()) {
+            self.charge_transfer_fee(transfer);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Account `collector` is frozen")]
+    fn fee_transfer_executes_hooks() {
+        let mut contract = FreezableContract {};
+        let alice: AccountId = "alice".parse().unwrap();
+        let collector: AccountId = "collector".parse().unwrap();
+
+        contract.deposit_unchecked(&alice, 10_000);
+        contract.set_transfer_fee(250, collector.clone());
+        contract.freeze(collector);
+
+        contract.transfer(alice, "bob".parse().unwrap(), 1_000, None);
+    }
+}
+
+#[test]
+fn exempt() {
+    let (mut contract, alice, bob, collector) = setup(250);
+
+    contract.set_transfer_fee_exempt(&bob, true);
+    assert!(contract.fee_is_exempt(bob.clone()));
+
+    contract.ft_transfer(bob.clone(), 1_000.into(), None);
+    assert_eq!(contract.ft_balance_of(bob.clone()).0, 1_000);
+
+    // The collector is implicitly exempt
+    contract.ft_transfer(collector.clone(), 1_000.into(), None);
+    assert_eq!(contract.ft_balance_of(collector.clone()).0, 1_000);
+
+    contract.set_transfer_fee_exempt(&bob, false);
+    assert!(!contract.fee_is_exempt(bob.clone()));
+
+    contract.ft_transfer(bob.clone(), 1_000.into(), None);
+    assert_eq!(contract.ft_balance_of(alice).0, 7_000);
+    assert_eq!(contract.ft_balance_of(bob).0, 1_975);
+    assert_eq!(contract.ft_balance_of(collector).0, 1_025);
+}
+
+#[test]
+fn remove_fee() {
+    let (mut contract, _, bob, _) = setup(250);
+
+    contract.remove_transfer_fee();
+    assert_eq!(contract.fee_config(), None);
+
+    contract.ft_transfer(bob.clone(), 1_000.into(), None);
+    assert_eq!(contract.ft_balance_of(bob).0, 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Transfer fee may not exceed 10000 basis points")]
+fn invalid_fee() {
+    setup(10_001);
+}
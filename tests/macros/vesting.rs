@@ -0,0 +1,226 @@
+use near_sdk::{
+    near_bindgen,
+    test_utils::{get_logs, VMContextBuilder},
+    testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig,
+};
+use near_sdk_contract_tools::{
+    standard::nep141::{Nep141, Nep141Controller},
+    vesting::{Vesting, VestingExternal, VestingSchedule},
+    FungibleToken, Vesting,
+};
+
+#[derive(FungibleToken, Vesting)]
+#[fungible_token(name = "Vesting Fungible Token", symbol = "VFT", decimals = 18)]
+#[vesting(nep141_hook)]
+#[near_bindgen]
+struct Contract {}
+
+fn at_time(account_id: &AccountId, timestamp: u64) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .block_timestamp(timestamp)
+        .attached_deposit(1)
+        .build());
+}
+
+/// Alice receives 1000 tokens vesting from 100 to 1100, with a cliff at 300
+fn setup(revocable: bool) -> (Contract, AccountId, AccountId) {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+
+    at_time(&alice, 0);
+    contract.mint(alice.clone(), 1000, None);
+    contract.create_vesting_schedule(
+        alice.clone(),
+        VestingSchedule::new(1000, 100, 200, 1000, revocable),
+    );
+
+    (contract, alice, bob)
+}
+
+#[test]
+fn vesting_schedule() {
+    let schedule = VestingSchedule::new(1000, 100, 200, 1000, false);
+
+    assert_eq!(schedule.vested_at(0), 0);
+    assert_eq!(schedule.vested_at(299), 0);
+    assert_eq!(schedule.vested_at(300), 200);
+    assert_eq!(schedule.vested_at(650), 550);
+    assert_eq!(schedule.vested_at(1100), 1000);
+    assert_eq!(schedule.vested_at(u64::MAX), 1000);
+
+    let large = VestingSchedule::new(u128::MAX, 0, 0, 3, false);
+
+    assert_eq!(large.vested_at(1), u128::MAX / 3);
+    assert_eq!(large.vested_at(3), u128::MAX);
+}
+
+#[test]
+fn create_and_claim() {
+    let (mut contract, alice, bob) = setup(false);
+
+    assert_eq!(contract.vest_locked_of(alice.clone()).0, 1000);
+    assert_eq!(contract.vest_claimable_of(alice.clone()).0, 0);
+
+    at_time(&alice, 200);
+    assert_eq!(contract.vest_claim().0, 0);
+
+    at_time(&alice, 600);
+    assert_eq!(contract.vest_claimable_of(alice.clone()).0, 500);
+    assert_eq!(contract.vest_claim().0, 500);
+    assert_eq!(contract.vest_locked_of(alice.clone()).0, 500);
+
+    contract.ft_transfer(bob.clone(), 500.into(), None);
+
+    at_time(&alice, 2000);
+    assert_eq!(contract.vest_claim().0, 500);
+    assert_eq!(contract.vest_schedule_of(alice.clone()), None);
+
+    contract.ft_transfer(bob.clone(), 500.into(), None);
+
+    assert_eq!(contract.ft_balance_of(alice).0, 0);
+    assert_eq!(contract.ft_balance_of(bob).0, 1000);
+}
+
+#[test]
+fn events() {
+    let (mut contract, alice, _) = setup(false);
+
+    at_time(&alice, 600);
+    contract.vest_claim();
+
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"x-vest","version":"1.0.0","event":"claim","data":{"account_id":"alice","amount":"500"}}"#,
+        ],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot move locked tokens")]
+fn transfer_locked() {
+    let (mut contract, alice, bob) = setup(false);
+
+    at_time(&alice, 600);
+    // Vested, but not claimed
+    contract.ft_transfer(bob, 1.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Cannot move locked tokens")]
+fn transfer_more_than_unlocked() {
+    let (mut contract, alice, bob) = setup(false);
+
+    contract.mint(alice.clone(), 10, None);
+
+    at_time(&alice, 600);
+    contract.ft_transfer(bob, 11.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Cannot move locked tokens")]
+fn burn_locked() {
+    let (mut contract, alice, _) = setup(false);
+
+    contract.burn(alice, 1, None);
+}
+
+#[test]
+fn resolve_transfer_refunds_only_unlocked() {
+    let (mut contract, alice, bob) = setup(false);
+    let carol: AccountId = "carol".parse().unwrap();
+
+    // Bob transfers 60 tokens to Alice, who forwards them to Carol
+    contract.mint(bob.clone(), 100, None);
+    contract.transfer(bob.clone(), alice.clone(), 60, None);
+    contract.transfer(alice.clone(), carol.clone(), 60, None);
+
+    // ...and then claims all of them as unused
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed],
+    );
+
+    assert_eq!(
+        contract.resolve_transfer(bob.clone(), alice.clone(), 60),
+        60
+    );
+    assert_eq!(contract.ft_balance_of(alice.clone()).0, 1000);
+    assert_eq!(contract.ft_balance_of(bob.clone()).0, 40);
+
+    // Only unlocked tokens are refunded
+    contract.mint(alice.clone(), 25, None);
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed],
+    );
+
+    assert_eq!(
+        contract.resolve_transfer(bob.clone(), alice.clone(), 60),
+        35
+    );
+    assert_eq!(contract.ft_balance_of(alice).0, 1000);
+    assert_eq!(contract.ft_balance_of(bob).0, 65);
+}
+
+#[test]
+fn revoke() {
+    let (mut contract, alice, bob) = setup(true);
+
+    at_time(&alice, 600);
+    contract.vest_claim();
+
+    at_time(&bob, 850);
+    assert_eq!(
+        contract.revoke_vesting_schedule(alice.clone(), bob.clone()),
+        250,
+    );
+
+    assert_eq!(contract.vest_schedule_of(alice.clone()), None);
+    assert_eq!(contract.ft_balance_of(alice.clone()).0, 750);
+    assert_eq!(contract.ft_balance_of(bob).0, 250);
+    assert_eq!(
+        get_logs().last().unwrap(),
+        r#"EVENT_JSON:{"standard":"x-vest","version":"1.0.0","event":"revoke","data":{"account_id":"alice","refund_to":"bob","amount":"250"}}"#,
+    );
+
+    // Vested tokens are released
+    at_time(&alice, 850);
+    contract.ft_transfer("carol".parse().unwrap(), 750.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Vesting schedule is not revocable")]
+fn revoke_not_revocable() {
+    let (mut contract, alice, bob) = setup(false);
+
+    contract.revoke_vesting_schedule(alice, bob);
+}
+
+#[test]
+#[should_panic(expected = "Account already has a vesting schedule")]
+fn create_twice() {
+    let (mut contract, alice, _) = setup(false);
+
+    contract.mint(alice.clone(), 1000, None);
+    contract.create_vesting_schedule(alice, VestingSchedule::new(1000, 0, 0, 10, false));
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance to lock")]
+fn create_insufficient_balance() {
+    let mut contract = Contract {};
+
+    contract.create_vesting_schedule(
+        "alice".parse().unwrap(),
+        VestingSchedule::new(1, 0, 0, 10, false),
+    );
+}
@@ -0,0 +1,109 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+    test_utils::VMContextBuilder,
+    testing_env, AccountId,
+};
+use near_sdk_contract_tools::{
+    approval::{
+        self,
+        weighted_multisig::{ApprovalState, Configuration},
+        ApprovalManager,
+    },
+    WeightedMultisig,
+};
+use serde::Serialize;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+enum Action {
+    Spend { amount: u64 },
+}
+
+impl approval::Action<Contract> for Action {
+    type Output = u64;
+
+    fn execute(self, contract: &mut Contract) -> Self::Output {
+        match self {
+            Self::Spend { amount } => {
+                contract.spent += amount;
+                contract.spent
+            }
+        }
+    }
+}
+
+fn signer_weight(account_id: &AccountId) -> u64 {
+    match account_id.as_str() {
+        "alice" => 2,
+        "bob" => 1,
+        "carol" => 1,
+        _ => 0,
+    }
+}
+
+#[derive(WeightedMultisig)]
+#[weighted_multisig(action = "Action", weight = "signer_weight")]
+#[near_bindgen]
+struct Contract {
+    spent: u64,
+}
+
+fn predecessor(account_id: &AccountId) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .build());
+}
+
+fn setup() -> Contract {
+    <Contract as ApprovalManager<_, _, _>>::init(Configuration::new(3, 0));
+    Contract { spent: 0 }
+}
+
+#[test]
+fn derive_weighted_multisig() {
+    let alice: AccountId = "alice".parse().unwrap();
+    let bob: AccountId = "bob".parse().unwrap();
+    let carol: AccountId = "carol".parse().unwrap();
+
+    let mut contract = setup();
+
+    predecessor(&bob);
+    let request_id = contract
+        .create_request(Action::Spend { amount: 10 }, ApprovalState::new())
+        .unwrap();
+    contract.approve_request(request_id).unwrap();
+
+    predecessor(&carol);
+    contract.approve_request(request_id).unwrap();
+
+    assert!(Contract::is_approved_for_execution(request_id).is_err());
+
+    predecessor(&alice);
+    contract.approve_request(request_id).unwrap();
+
+    let requests = contract.multisig_list_requests(None, None);
+    assert_eq!(contract.multisig_count_requests(), 1);
+    assert_eq!(requests[0].0, request_id);
+    assert_eq!(requests[0].1.approval_state.weight, 4);
+
+    assert_eq!(contract.execute_request(request_id).unwrap(), 10);
+    assert_eq!(contract.multisig_count_requests(), 0);
+}
+
+#[test]
+#[should_panic = "NoWeight"]
+fn derive_weighted_multisig_no_weight() {
+    let alice: AccountId = "alice".parse().unwrap();
+    let dave: AccountId = "dave".parse().unwrap();
+
+    let mut contract = setup();
+
+    predecessor(&alice);
+    let request_id = contract
+        .create_request(Action::Spend { amount: 10 }, ApprovalState::new())
+        .unwrap();
+
+    predecessor(&dave);
+    contract.approve_request(request_id).unwrap();
+}
@@ -0,0 +1,244 @@
+use near_sdk::{
+    env,
+    mock::VmAction,
+    near_bindgen,
+    test_utils::{get_created_receipts, get_logs, VMContextBuilder},
+    testing_env, AccountId, ONE_NEAR,
+};
+use near_sdk_contract_tools::{
+    standard::{
+        nep141::{Nep141, Nep141ControllerInternal},
+        nep145::{record_storage_usage, Nep145},
+    },
+    wrapped_near::WrappedNearExternal,
+    FungibleToken, WrappedNear,
+};
+
+#[derive(FungibleToken, WrappedNear)]
+#[fungible_token(
+    name = "Wrapped NEAR",
+    symbol = "wNEAR",
+    decimals = 24,
+    no_hooks,
+    require_registration
+)]
+#[near_bindgen]
+struct Contract {}
+
+fn call_as(account_id: &AccountId, attached_deposit: u128) {
+    testing_env!(VMContextBuilder::new()
+        .predecessor_account_id(account_id.clone())
+        .attached_deposit(attached_deposit)
+        .build());
+}
+
+/// Native NEAR transferred by the contract
+fn transfers() -> Vec<(AccountId, u128)> {
+    get_created_receipts()
+        .into_iter()
+        .flat_map(|receipt| {
+            let receiver_id = receipt.receiver_id;
+            receipt
+                .actions
+                .into_iter()
+                .filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+fn register(contract: &mut impl Nep145, account_id: &AccountId) {
+    call_as(account_id, ONE_NEAR);
+    contract.storage_deposit(None, None);
+}
+
+#[test]
+fn deposit_withdraw() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    register(&mut contract, &alice);
+
+    call_as(&alice, ONE_NEAR);
+    contract.near_deposit();
+
+    // The balance record is paid for by the storage deposit, so only the
+    // total supply record is charged
+    let storage_fee = u128::from(record_storage_usage(&Contract::slot_total_supply(), &0))
+        * env::storage_byte_cost();
+
+    let balance = ONE_NEAR - storage_fee;
+    assert_eq!(contract.ft_balance_of(alice.clone()).0, balance);
+    assert_eq!(contract.ft_total_supply().0, balance);
+    assert_eq!(
+        get_logs(),
+        vec![
+            format!(
+                r#"EVENT_JSON:{{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{{"owner_id":"alice","amount":"{ONE_NEAR}"}}]}}"#,
+            ),
+            format!(
+                r#"EVENT_JSON:{{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{{"owner_id":"alice","amount":"{storage_fee}","memo":"storage fee"}}]}}"#,
+            ),
+        ],
+    );
+    assert!(transfers().is_empty());
+
+    // No storage fee for subsequent deposits
+    call_as(&alice, ONE_NEAR);
+    contract.near_deposit();
+
+    assert_eq!(get_logs().len(), 1);
+
+    assert_eq!(contract.ft_balance_of(alice.clone()).0, balance + ONE_NEAR);
+
+    call_as(&alice, 1);
+    contract.near_withdraw(ONE_NEAR.into());
+
+    assert_eq!(contract.ft_balance_of(alice.clone()).0, balance);
+    assert_eq!(contract.ft_total_supply().0, balance);
+    assert_eq!(transfers(), vec![(alice, ONE_NEAR)]);
+}
+
+#[test]
+#[should_panic(expected = "Attached deposit must be larger than the storage fee")]
+fn deposit_insufficient() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    register(&mut contract, &alice);
+
+    call_as(&alice, 1);
+    contract.near_deposit();
+}
+
+#[test]
+#[should_panic(expected = "Account `alice` is not registered")]
+fn deposit_unregistered() {
+    let mut contract = Contract {};
+
+    call_as(&"alice".parse().unwrap(), ONE_NEAR);
+    contract.near_deposit();
+}
+
+#[test]
+#[should_panic(expected = "Account `bob` is not registered")]
+fn transfer_to_unregistered() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    register(&mut contract, &alice);
+
+    call_as(&alice, ONE_NEAR);
+    contract.near_deposit();
+
+    call_as(&alice, 1);
+    contract.ft_transfer("bob".parse().unwrap(), 1.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+fn withdraw_requires_one_yocto() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+    register(&mut contract, &alice);
+
+    call_as(&alice, ONE_NEAR);
+    contract.near_deposit();
+
+    call_as(&alice, 0);
+    contract.near_withdraw(1.into());
+}
+
+#[test]
+#[should_panic(expected = "Balance underflow")]
+fn withdraw_insufficient_balance() {
+    let mut contract = Contract {};
+    let alice: AccountId = "alice".parse().unwrap();
+
+    call_as(&alice, 1);
+    contract.near_withdraw(1.into());
+}
+
+mod snapshot {
+    use near_sdk_contract_tools::standard::nep141::Nep141Snapshot;
+
+    use super::*;
+
+    #[derive(FungibleToken, WrappedNear)]
+    #[fungible_token(
+        name = "Wrapped NEAR",
+        symbol = "wNEAR",
+        decimals = 24,
+        no_hooks,
+        require_registration,
+        snapshot
+    )]
+    #[near_bindgen]
+    struct SnapshotContract {}
+
+    fn call_at(account_id: &AccountId, attached_deposit: u128, block_height: u64) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(attached_deposit)
+            .block_index(block_height)
+            .build());
+    }
+
+    fn storage_fee(initial_storage_usage: u64) -> u128 {
+        u128::from(env::storage_usage() - initial_storage_usage) * env::storage_byte_cost()
+    }
+
+    #[test]
+    fn deposit_records_checkpoints() {
+        let mut contract = SnapshotContract {};
+        let alice: AccountId = "alice".parse().unwrap();
+        register(&mut contract, &alice);
+
+        call_at(&alice, ONE_NEAR, 10);
+        let initial_storage_usage = env::storage_usage();
+        contract.near_deposit();
+        let first_fee = storage_fee(initial_storage_usage)
+            - u128::from(record_storage_usage(
+                &SnapshotContract::slot_account(&alice),
+                &0,
+            )) * env::storage_byte_cost();
+
+        // Checkpoints in a new block are charged as well
+        call_at(&alice, ONE_NEAR, 20);
+        let initial_storage_usage = env::storage_usage();
+        contract.near_deposit();
+        let second_fee = storage_fee(initial_storage_usage);
+
+        assert!(second_fee > 0);
+
+        let first_balance = ONE_NEAR - first_fee;
+        let balance = first_balance + ONE_NEAR - second_fee;
+
+        assert_eq!(contract.ft_balance_of(alice.clone()).0, balance);
+        assert_eq!(contract.ft_total_supply().0, balance);
+        assert_eq!(
+            contract.ft_balance_of_at(alice.clone(), 10.into()).0,
+            first_balance,
+        );
+        assert_eq!(contract.ft_balance_of_at(alice, 20.into()).0, balance);
+        assert_eq!(contract.ft_total_supply_at(10.into()).0, first_balance);
+        assert!(transfers().is_empty());
+    }
+
+    #[test]
+    fn withdraw_pays_for_checkpoints() {
+        let mut contract = SnapshotContract {};
+        let alice: AccountId = "alice".parse().unwrap();
+        register(&mut contract, &alice);
+
+        call_at(&alice, ONE_NEAR, 10);
+        contract.near_deposit();
+
+        call_at(&alice, 1, 20);
+        let initial_storage_usage = env::storage_usage();
+        contract.near_withdraw((ONE_NEAR / 2).into());
+        let fee = storage_fee(initial_storage_usage);
+
+        assert!(fee > 0);
+        assert_eq!(transfers(), vec![(alice, ONE_NEAR / 2 - fee)]);
+    }
+}